@@ -7,14 +7,24 @@ This serves as the bridge between Tauri frontend and Python engine.
 
 Usage:
     python -m engine load-csv <path>
-    python -m engine load-sheet <url>
-    python -m engine preview --data <json> --templates <json> --overrides <json>
-    python -m engine generate --data <json> --templates <json> --overrides <json> --subject <str> --resume <path>
-
-All commands output JSON to stdout.
+    python -m engine load-sheet [--tab <name or gid> ...] <url>
+    python -m engine list-sheet-tabs <url>
+    python -m engine preview --data <json> --templates <json> --overrides <json> [--roles <json>] [--dedup <policy>]
+    python -m engine generate --data <json> --templates <json> --overrides <json> --subject <str> --resume <path> [--roles <json>] [--dedup <policy>]
+    python -m engine validate --data <json> --templates <json> --overrides <json> --subject <str> [--roles <json>] [--dedup <policy>]
+    python -m engine serve
+    python -m engine --args-stdin < args.json
+
+All commands output JSON to stdout. The `serve` command keeps the engine
+resident and answers line-delimited JSON-RPC requests on stdin instead,
+where each request's method is a subcommand name and params its arguments.
+With `--args-stdin` the full argument list is read from stdin as a JSON
+array, for payloads too large for the command line.
 """
 
 import argparse
+import contextlib
+import io
 import json
 import sys
 import re
@@ -62,6 +72,17 @@ def _is_email_valid(email: str) -> bool:
     return bool(re.match(r"^[^@\s]+@[^@\s]+\.[^@\s]+$", email))
 
 
+# Set by `serve` while a request is running so long commands can stream
+# JSON-RPC notifications back to the caller. None outside of `serve`.
+_notify = None
+
+
+def notify(method: str, params: dict) -> None:
+    """Send a JSON-RPC notification if running under `serve`; no-op otherwise."""
+    if _notify is not None:
+        _notify(method, params)
+
+
 def output_json(data: Any, success: bool = True) -> None:
     """Output JSON response to stdout."""
     response = {
@@ -85,17 +106,32 @@ def cmd_load_csv(args: argparse.Namespace) -> None:
 
 def cmd_load_sheet(args: argparse.Namespace) -> None:
     """Load Google Sheet and return rows + headers."""
-    from engine.data_sources import load_google_sheet
+    from engine.data_sources import load_google_sheet, load_google_sheet_tabs
 
     try:
-        rows, headers = load_google_sheet(args.url)
+        tabs = args.tab or []
+        if len(tabs) > 1:
+            rows, headers = load_google_sheet_tabs(args.url, tabs)
+        else:
+            rows, headers = load_google_sheet(args.url, tab=tabs[0] if tabs else None)
         output_json({"rows": rows, "headers": headers, "count": len(rows)})
     except Exception as e:
         output_json(str(e), success=False)
 
 
+def cmd_list_sheet_tabs(args: argparse.Namespace) -> None:
+    """List a Google Sheet's tabs as [{name, gid}]."""
+    from engine.data_sources import list_sheet_tabs
+
+    try:
+        output_json(list_sheet_tabs(args.url))
+    except Exception as e:
+        output_json(str(e), success=False)
+
+
 def cmd_preview(args: argparse.Namespace) -> None:
     """Build preview rows from input data."""
+    from engine.dedup import dedupe_rows
     from engine.preview import build_preview_rows
 
     try:
@@ -104,6 +140,18 @@ def cmd_preview(args: argparse.Namespace) -> None:
         headers = data.get("headers", [])
         templates = json.loads(args.templates)
         overrides = json.loads(args.overrides) if args.overrides else {}
+        roles = json.loads(args.roles) if args.roles else {}
+
+        row_numbers = {id(row): index + 1 for index, row in enumerate(rows)}
+        rows, duplicates = dedupe_rows(
+            rows,
+            headers,
+            _parse_name,
+            policy=args.dedup,
+            is_generate_true_fn=_is_generate_true,
+            is_email_valid_fn=_is_email_valid,
+            roles=roles,
+        )
 
         preview_rows = build_preview_rows(
             rows=rows,
@@ -114,9 +162,11 @@ def cmd_preview(args: argparse.Namespace) -> None:
             only_recipients=args.only_recipients,
             is_generate_true_fn=_is_generate_true,
             is_email_valid_fn=_is_email_valid,
+            roles=roles,
+            row_numbers=[row_numbers[id(row)] for row in rows],
         )
 
-        output_json({"preview_rows": preview_rows, "count": len(preview_rows)})
+        output_json({"preview_rows": preview_rows, "count": len(preview_rows), "duplicates": duplicates})
     except Exception as e:
         output_json(str(e), success=False)
 
@@ -131,6 +181,9 @@ def cmd_generate(args: argparse.Namespace) -> None:
         headers = data.get("headers", [])
         templates = json.loads(args.templates)
         overrides = json.loads(args.overrides) if args.overrides else {}
+        roles = json.loads(args.roles) if args.roles else {}
+        rendered = json.loads(args.rendered) if args.rendered else None
+        duplicates: list = []
 
         count = generate_emails(
             rows=rows,
@@ -143,9 +196,49 @@ def cmd_generate(args: argparse.Namespace) -> None:
             subject_template=args.subject or "",
             resume_path=args.resume if args.resume else None,
             dry_run=args.dry_run,
+            progress_fn=lambda index, total, email, status, template: notify(
+                "progress",
+                {"index": index, "total": total, "email": email, "status": status, "template": template},
+            ),
+            roles=roles,
+            dedup_policy=args.dedup,
+            duplicates_fn=duplicates.extend,
+            rendered=rendered,
         )
 
-        output_json({"created": count})
+        output_json({"created": count, "duplicates": duplicates})
+    except Exception as e:
+        output_json(str(e), success=False)
+
+
+def cmd_validate(args: argparse.Namespace) -> None:
+    """Report problems in the data before generating."""
+    from engine.validation import validate_dataset
+
+    try:
+        data = json.loads(args.data)
+        rows = data.get("rows", [])
+        headers = data.get("headers", [])
+        templates = json.loads(args.templates)
+        overrides = json.loads(args.overrides) if args.overrides else {}
+        roles = json.loads(args.roles) if args.roles else {}
+        rendered = json.loads(args.rendered) if args.rendered else None
+
+        report = validate_dataset(
+            rows=rows,
+            headers_lower=headers,
+            templates=templates,
+            recipient_template_overrides=overrides,
+            parse_name_fn=_parse_name,
+            subject_template=args.subject or "",
+            is_generate_true_fn=_is_generate_true,
+            is_email_valid_fn=_is_email_valid,
+            roles=roles,
+            dedup_policy=args.dedup,
+            rendered=rendered,
+        )
+
+        output_json(report)
     except Exception as e:
         output_json(str(e), success=False)
 
@@ -333,7 +426,73 @@ def cmd_validate_license(args: argparse.Namespace) -> None:
         output_json({"valid": False, "message": f"License validation failed: {e}"})
 
 
-def main() -> None:
+def _write_message(stream, message: dict) -> None:
+    """Write one JSON-RPC message as a single line and flush it."""
+    stream.write(json.dumps(message, ensure_ascii=False) + "\n")
+    stream.flush()
+
+
+def _rpc_error(req_id: Any, code: int, message: str) -> dict:
+    return {"jsonrpc": "2.0", "id": req_id, "error": {"code": code, "message": message}}
+
+
+def cmd_serve(args: argparse.Namespace) -> None:
+    """Answer JSON-RPC requests on stdin until EOF."""
+    global _notify
+
+    parser = build_parser()
+    out = sys.stdout
+
+    for line in sys.stdin:
+        line = line.strip()
+        if not line:
+            continue
+
+        try:
+            request = json.loads(line)
+        except ValueError as e:
+            _write_message(out, _rpc_error(None, -32700, f"Parse error: {e}"))
+            continue
+
+        req_id = request.get("id")
+        method = request.get("method")
+        params = request.get("params") or []
+
+        if not isinstance(method, str) or method == "serve" or not isinstance(params, list):
+            _write_message(out, _rpc_error(req_id, -32600, "Invalid request"))
+            continue
+
+        # Commands report through output_json, so capture what they print
+        # and hand it back as the result instead of letting it hit the pipe.
+        captured = io.StringIO()
+        _notify = lambda n_method, n_params: _write_message(
+            out, {"jsonrpc": "2.0", "method": n_method, "params": {"id": req_id, **n_params}}
+        )
+        try:
+            with contextlib.redirect_stdout(captured):
+                cmd_args = parser.parse_args([method, *[str(p) for p in params]])
+                cmd_args.func(cmd_args)
+        except SystemExit:
+            _write_message(out, _rpc_error(req_id, -32602, f"Invalid arguments for '{method}'"))
+            continue
+        except Exception as e:
+            _write_message(out, _rpc_error(req_id, -32603, str(e)))
+            continue
+        finally:
+            _notify = None
+
+        try:
+            result = json.loads(captured.getvalue())
+        except ValueError:
+            _write_message(out, _rpc_error(req_id, -32603, f"'{method}' did not produce JSON output"))
+            continue
+
+        _write_message(out, {"jsonrpc": "2.0", "id": req_id, "result": result})
+
+
+def build_parser() -> argparse.ArgumentParser:
+    from engine.dedup import DEDUP_POLICIES, KEEP_FIRST
+
     parser = argparse.ArgumentParser(
         prog="draftmate",
         description="DraftMate Engine CLI - JSON bridge for Tauri frontend",
@@ -348,13 +507,25 @@ def main() -> None:
     # load-sheet
     p_sheet = subparsers.add_parser("load-sheet", help="Load data from Google Sheet")
     p_sheet.add_argument("url", help="Google Sheets URL")
+    p_sheet.add_argument(
+        "--tab",
+        action="append",
+        help="Tab name or gid; repeat to load several tabs tagged with a 'source tab' column",
+    )
     p_sheet.set_defaults(func=cmd_load_sheet)
 
+    # list-sheet-tabs
+    p_tabs = subparsers.add_parser("list-sheet-tabs", help="List a Google Sheet's tabs")
+    p_tabs.add_argument("url", help="Google Sheets URL")
+    p_tabs.set_defaults(func=cmd_list_sheet_tabs)
+
     # preview
     p_preview = subparsers.add_parser("preview", help="Build preview rows")
     p_preview.add_argument("--data", required=True, help="JSON with rows and headers")
     p_preview.add_argument("--templates", required=True, help="JSON array of templates")
     p_preview.add_argument("--overrides", default="{}", help="JSON object of email->template_id overrides")
+    p_preview.add_argument("--roles", default="{}", help="JSON object of column role -> header")
+    p_preview.add_argument("--dedup", choices=DEDUP_POLICIES, default=KEEP_FIRST, help="How to handle duplicate recipients")
     p_preview.add_argument("--only-recipients", action="store_true", default=True, help="Filter to recipients only")
     p_preview.add_argument("--all-rows", dest="only_recipients", action="store_false", help="Include all rows with emails")
     p_preview.set_defaults(func=cmd_preview)
@@ -366,9 +537,23 @@ def main() -> None:
     p_gen.add_argument("--overrides", default="{}", help="JSON object of email->template_id overrides")
     p_gen.add_argument("--subject", required=True, help="Subject line template")
     p_gen.add_argument("--resume", help="Path to resume PDF")
+    p_gen.add_argument("--roles", default="{}", help="JSON object of column role -> header")
+    p_gen.add_argument("--dedup", choices=DEDUP_POLICIES, default=KEEP_FIRST, help="How to handle duplicate recipients")
     p_gen.add_argument("--dry-run", action="store_true", help="Don't create drafts, just count")
+    p_gen.add_argument("--rendered", help="JSON array of each row's pre-rendered subject and bodies")
     p_gen.set_defaults(func=cmd_generate)
 
+    # validate
+    p_validate = subparsers.add_parser("validate", help="Report problems in the data")
+    p_validate.add_argument("--data", required=True, help="JSON with rows and headers")
+    p_validate.add_argument("--templates", required=True, help="JSON array of templates")
+    p_validate.add_argument("--overrides", default="{}", help="JSON object of email->template_id overrides")
+    p_validate.add_argument("--subject", default="", help="Subject line template")
+    p_validate.add_argument("--roles", default="{}", help="JSON object of column role -> header")
+    p_validate.add_argument("--dedup", choices=DEDUP_POLICIES, default=KEEP_FIRST, help="How to handle duplicate recipients")
+    p_validate.add_argument("--rendered", help="JSON array of each row's pre-rendered subject and bodies")
+    p_validate.set_defaults(func=cmd_validate)
+
     # read-files
     p_read = subparsers.add_parser("read-files", help="Read multiple text files")
     p_read.add_argument("paths", nargs="+", help="Paths to text files")
@@ -384,7 +569,19 @@ def main() -> None:
     p_license.add_argument("license_key", help="License key to validate")
     p_license.set_defaults(func=cmd_validate_license)
 
-    args = parser.parse_args()
+    # serve
+    p_serve = subparsers.add_parser("serve", help="Serve JSON-RPC requests on stdin")
+    p_serve.set_defaults(func=cmd_serve)
+
+    return parser
+
+
+def main() -> None:
+    parser = build_parser()
+    argv = sys.argv[1:]
+    if argv == ["--args-stdin"]:
+        argv = [str(a) for a in json.load(sys.stdin)]
+    args = parser.parse_args(argv)
     args.func(args)
 
 
@@ -1,18 +1,27 @@
 # engine/data_sources.py
 
 import csv
+import html
 import io
+import os
 import re
 import ssl
 import urllib.parse
 import urllib.request
-from typing import List, Dict, Tuple
+from typing import List, Dict, Optional, Tuple
 
 import certifi
 
 
 GS_HOST = "docs.google.com"
 
+# Where spreadsheet exports and tab lists are fetched from. Point it at a
+# local HTTP server to stand in for Google, e.g. http://127.0.0.1:8000.
+GS_BASE_URL_ENV = "DRAFTMATE_SHEETS_BASE_URL"
+
+# Column added to every row when several tabs are loaded together.
+SOURCE_TAB_COLUMN = "source tab"
+
 
 # -------------------------------------------------
 # Public API
@@ -43,24 +52,90 @@ def load_csv(path: str) -> Tuple[List[Dict[str, str]], List[str]]:
     return rows, headers
 
 
-def load_google_sheet(sheet_url: str, timeout: int = 20) -> Tuple[List[Dict[str, str]], List[str]]:
+def load_google_sheet(
+    sheet_url: str,
+    timeout: int = 20,
+    tab: Optional[str] = None,
+) -> Tuple[List[Dict[str, str]], List[str]]:
     """
     Load and normalize rows from a public Google Sheets URL.
 
-    Accepts standard sharing links with optional gid.
+    Accepts standard sharing links with optional gid. tab, if given, picks
+    the tab by name (case-insensitive) or gid instead of the URL's gid.
 
     Returns:
         rows: list of normalized row dictionaries
         headers: ordered list of lowercase column headers
     """
-    export_url = _gsheet_to_export_csv_url(sheet_url)
+    gid = None
+    if tab is not None:
+        gid = _resolve_tab(list_sheet_tabs(sheet_url, timeout=timeout), tab)["gid"]
+
+    export_url = _gsheet_to_export_csv_url(sheet_url, gid=gid)
     if not export_url:
         raise ValueError("Invalid Google Sheets URL")
 
-    csv_text = _fetch_gsheet_csv_text(export_url, timeout=timeout)
+    csv_text = _fetch_gsheet_text(export_url, timeout=timeout)
     return _parse_csv_text(csv_text)
 
 
+def load_google_sheet_tabs(
+    sheet_url: str,
+    tabs: List[str],
+    timeout: int = 20,
+) -> Tuple[List[Dict[str, str]], List[str]]:
+    """
+    Load several tabs of one spreadsheet as a single table.
+
+    Each row gets a "source tab" column holding its tab's name, usable as
+    the {source tab} placeholder. Headers are every tab's headers in order
+    of first appearance; rows lacking a column get "". A tab that already
+    has a "source tab" column is an error rather than being overwritten.
+    """
+    available = list_sheet_tabs(sheet_url, timeout=timeout)
+    chosen = [_resolve_tab(available, tab) for tab in tabs]
+
+    rows: List[Dict[str, str]] = []
+    headers: List[str] = []
+    for tab in chosen:
+        export_url = _gsheet_to_export_csv_url(sheet_url, gid=tab["gid"])
+        tab_rows, tab_headers = _parse_csv_text(_fetch_gsheet_text(export_url, timeout=timeout))
+        if SOURCE_TAB_COLUMN in tab_headers:
+            raise ValueError(
+                f"Tab '{tab['name']}' already has a '{SOURCE_TAB_COLUMN}' column; "
+                "rename it to load several tabs together"
+            )
+        for header in tab_headers:
+            if header not in headers:
+                headers.append(header)
+        for row in tab_rows:
+            row[SOURCE_TAB_COLUMN] = tab["name"]
+            rows.append(row)
+
+    headers.append(SOURCE_TAB_COLUMN)
+    for row in rows:
+        for header in headers:
+            row.setdefault(header, "")
+    return rows, headers
+
+
+def list_sheet_tabs(sheet_url: str, timeout: int = 20) -> List[Dict[str, str]]:
+    """
+    List the tabs of a public Google Sheet, in order.
+
+    Returns a list of {"name": ..., "gid": ...} dicts.
+    """
+    ssid = _spreadsheet_id(sheet_url)
+    if not ssid:
+        raise ValueError("Invalid Google Sheets URL")
+
+    page = _fetch_gsheet_text(f"{_gs_base_url()}/spreadsheets/d/{ssid}/htmlview", timeout=timeout)
+    tabs = _parse_sheet_tabs(page)
+    if not tabs:
+        raise ValueError("Could not find any tabs; is the sheet shared publicly?")
+    return tabs
+
+
 # -------------------------------------------------
 # Internal helpers
 # -------------------------------------------------
@@ -83,7 +158,11 @@ def _parse_csv_text(csv_text: str) -> Tuple[List[Dict[str, str]], List[str]]:
     return rows, headers
 
 
-def _gsheet_to_export_csv_url(url: str) -> str:
+def _gs_base_url() -> str:
+    return (os.environ.get(GS_BASE_URL_ENV) or f"https://{GS_HOST}").rstrip("/")
+
+
+def _spreadsheet_id(url: str) -> str:
     try:
         parsed = urllib.parse.urlparse(url)
     except Exception:
@@ -93,22 +172,78 @@ def _gsheet_to_export_csv_url(url: str) -> str:
         return ""
 
     m = re.search(r"/spreadsheets/d/([a-zA-Z0-9\-_]+)", parsed.path)
-    if not m:
-        return ""
+    return m.group(1) if m else ""
 
-    ssid = m.group(1)
 
-    gid = "0"
-    if parsed.fragment:
-        mg = re.search(r"gid=(\d+)", parsed.fragment)
-        if mg:
-            gid = mg.group(1)
+def _gsheet_to_export_csv_url(url: str, gid: Optional[str] = None) -> str:
+    """
+    Export URL for one tab: gid if given, else the gid in the URL's
+    fragment or query, else the first tab.
+    """
+    ssid = _spreadsheet_id(url)
+    if not ssid:
+        return ""
+
+    if gid is None:
+        parsed = urllib.parse.urlparse(url)
+        gid = "0"
+        for part in (parsed.fragment, parsed.query):
+            mg = re.search(r"(?:^|[&#?])gid=(\d+)", part)
+            if mg:
+                gid = mg.group(1)
+                break
 
     q = urllib.parse.urlencode({"format": "csv", "gid": gid})
-    return f"https://{GS_HOST}/spreadsheets/d/{ssid}/export?{q}"
+    return f"{_gs_base_url()}/spreadsheets/d/{ssid}/export?{q}"
+
+
+# The htmlview page registers each tab with a script line like
+#   items.push({name: "Contacts", pageUrl: "...", gid: "0", initialSheet: true});
+_TAB_SCRIPT_PATTERN = re.compile(r'name:\s*"((?:[^"\\]|\\.)*)"[^}]*?gid:\s*"(\d+)"')
+# ...and renders it as a button, which is all some pages have.
+_TAB_BUTTON_PATTERN = re.compile(r'id="sheet-button-(\d+)"[^>]*>\s*(?:<a[^>]*>)?(.*?)<', re.DOTALL)
+
+
+def _parse_sheet_tabs(page: str) -> List[Dict[str, str]]:
+    tabs: List[Dict[str, str]] = []
+    seen = set()
+
+    for name, gid in _TAB_SCRIPT_PATTERN.findall(page):
+        if gid not in seen:
+            seen.add(gid)
+            tabs.append({"name": _unescape_js(name), "gid": gid})
+    if tabs:
+        return tabs
+
+    for gid, name in _TAB_BUTTON_PATTERN.findall(page):
+        if gid not in seen:
+            seen.add(gid)
+            tabs.append({"name": html.unescape(name).strip(), "gid": gid})
+    return tabs
+
+
+def _unescape_js(text: str) -> str:
+    def replace(m: re.Match) -> str:
+        code = m.group(1) or m.group(2)
+        return chr(int(code, 16)) if code else m.group(3)
+
+    return re.sub(r"\\x([0-9a-fA-F]{2})|\\u([0-9a-fA-F]{4})|\\(.)", replace, text)
+
+
+def _resolve_tab(tabs: List[Dict[str, str]], tab: str) -> Dict[str, str]:
+    """Find a tab by name (case-insensitive) or, failing that, by gid."""
+    wanted = tab.strip()
+    for t in tabs:
+        if t["name"].strip().lower() == wanted.lower():
+            return t
+    for t in tabs:
+        if t["gid"] == wanted:
+            return t
+    names = ", ".join(t["name"] for t in tabs)
+    raise ValueError(f"No tab named '{wanted}' (available: {names})")
 
 
-def _fetch_gsheet_csv_text(export_csv_url: str, timeout: int = 20) -> str:
+def _fetch_gsheet_text(export_csv_url: str, timeout: int = 20) -> str:
     req = urllib.request.Request(
         export_csv_url,
         headers={
@@ -0,0 +1,84 @@
+# engine/dedup.py
+
+from typing import Callable, Dict, List, Optional, Tuple
+
+from engine.resolver import PlaceholderResolver
+
+
+# What to do when one email address is on several recipient rows.
+KEEP_FIRST = "keep_first"
+KEEP_LAST = "keep_last"
+SKIP_ALL = "skip_all"
+ERROR = "error"
+DEDUP_POLICIES = (KEEP_FIRST, KEEP_LAST, SKIP_ALL, ERROR)
+
+
+class DuplicateRecipientsError(ValueError):
+    """Raised under the "error" policy when an email appears more than once."""
+
+    def __init__(self, duplicates: List[dict]):
+        self.duplicates = duplicates
+        listed = "; ".join(
+            f"{d['email']} (rows {', '.join(str(n) for n in d['rows'])})" for d in duplicates
+        )
+        super().__init__(f"Duplicate recipients: {listed}")
+
+
+def dedupe_rows(
+    rows: List[dict],
+    headers_lower: List[str],
+    parse_name_fn: Callable[[str], tuple],
+    *,
+    policy: str = KEEP_FIRST,
+    is_generate_true_fn: Callable[[dict], bool],
+    is_email_valid_fn: Callable[[str], bool],
+    roles: Optional[Dict[str, str]] = None,
+) -> Tuple[List[dict], List[dict]]:
+    """
+    Resolve recipients whose (case-insensitive) email is on more than one row.
+
+    Only recipient rows count: rows that are not marked to generate, or have
+    no valid email, pass through untouched.
+
+    Returns (rows, duplicates) where rows keeps the input order minus the
+    dropped rows, and each duplicate is a dict with keys:
+        email   normalized email
+        rows    1-based positions of every row with that email
+        kept    the position whose data is used, or None if all were skipped
+    """
+
+    if policy not in DEDUP_POLICIES:
+        raise ValueError(f"Unknown duplicate policy '{policy}' (expected one of: {', '.join(DEDUP_POLICIES)})")
+
+    positions: Dict[str, List[int]] = {}
+    for index, row in enumerate(rows):
+        if not is_generate_true_fn(row):
+            continue
+        email = PlaceholderResolver(headers_lower, row, parse_name_fn, roles).get_email()
+        if not is_email_valid_fn(email):
+            continue
+        positions.setdefault(email.lower().strip(), []).append(index)
+
+    duplicates: List[dict] = []
+    dropped = set()
+    for email, indices in positions.items():
+        if len(indices) < 2:
+            continue
+        if policy == KEEP_LAST:
+            kept = indices[-1]
+        elif policy == SKIP_ALL:
+            kept = None
+        else:
+            kept = indices[0]
+        dropped.update(i for i in indices if i != kept)
+        duplicates.append({
+            "email": email,
+            "rows": [i + 1 for i in indices],
+            "kept": kept + 1 if kept is not None else None,
+        })
+
+    if duplicates and policy == ERROR:
+        raise DuplicateRecipientsError(duplicates)
+
+    kept_rows = [row for index, row in enumerate(rows) if index not in dropped]
+    return kept_rows, duplicates
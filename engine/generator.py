@@ -1,8 +1,9 @@
 # engine/generator.py
 
 import subprocess
-from typing import List, Dict, Callable
+from typing import List, Dict, Callable, Optional
 
+from engine.dedup import KEEP_FIRST, dedupe_rows
 from engine.resolver import PlaceholderResolver
 from engine.preview import build_preview_rows
 
@@ -18,7 +19,12 @@ def _escape_applescript(s: str) -> str:
 
 
 def _wrap_in_html(text: str) -> str:
-    """Wrap plain text in HTML formatting for email body."""
+    """
+    Wrap plain text in HTML formatting for email body.
+
+    The app's render_preview shows this HTML without running the engine, so
+    template::html in the app must be changed with it.
+    """
     text = text.strip()
     html_content = text.replace("  ", "<br><br>").replace("\n", "<br>").strip()
     html_content = html_content.lstrip("<br>").rstrip("<br>")
@@ -36,11 +42,20 @@ def _build_rows_by_email(
     rows: List[Dict],
     headers_lower: List[str],
     parse_name_fn: Callable[[str], tuple[str, str]],
+    is_generate_true_fn: Callable[[Dict], bool],
+    roles: Optional[Dict[str, str]] = None,
 ) -> Dict[str, Dict]:
-    """Build email -> row mapping internally."""
+    """
+    Build email -> row mapping of the rows marked to generate.
+
+    Expects rows already passed through dedupe_rows, so each email maps to
+    the row the dedup policy chose.
+    """
     result = {}
     for r in rows:
-        resolver = PlaceholderResolver(headers_lower, r, parse_name_fn)
+        if not is_generate_true_fn(r):
+            continue
+        resolver = PlaceholderResolver(headers_lower, r, parse_name_fn, roles)
         email = (resolver.get_email() or "").lower().strip()
         if email:
             result[email] = r
@@ -63,14 +78,49 @@ def generate_emails(
     subject_template: str,
     resume_path: str | None,
     dry_run: bool = False,
+    progress_fn: Optional[Callable[[int, int, str, str, str], None]] = None,
+    roles: Optional[Dict[str, str]] = None,
+    dedup_policy: str = KEEP_FIRST,
+    duplicates_fn: Optional[Callable[[List[Dict]], None]] = None,
+    rendered: Optional[List[Dict]] = None,
 ) -> int:
     """
     Generates Outlook drafts.
 
     Builds preview rows and email mapping internally.
     UI only needs to pass raw data and callbacks.
+
+    progress_fn, if given, is called as (index, total, email, status, template)
+    once per recipient, with status one of "created", "dry_run", "skipped" or
+    "failed" and template the name of the recipient's template ("" if none).
+
+    roles, if given, pins the email/name/firm/school columns; see
+    PlaceholderResolver.
+
+    Recipients listed more than once are resolved by dedup_policy before
+    anything is created; duplicates_fn, if given, receives the conflicts as
+    described in dedupe_rows. Under the "error" policy DuplicateRecipientsError
+    is raised and no drafts are created.
+
+    rendered, if given, holds each row's subject and template bodies already
+    rendered by the app, aligned with rows, as
+    {"subject": {"text": ...}, "bodies": {template_id: {"text": ...}}}.
+    Otherwise placeholders are substituted by PlaceholderResolver.
     """
 
+    row_numbers = {id(row): index for index, row in enumerate(rows)}
+    rows, duplicates = dedupe_rows(
+        rows,
+        headers_lower,
+        parse_name_fn,
+        policy=dedup_policy,
+        is_generate_true_fn=is_generate_true_fn,
+        is_email_valid_fn=is_email_valid_fn,
+        roles=roles,
+    )
+    if duplicates_fn:
+        duplicates_fn(duplicates)
+
     # Build preview rows internally
     preview_rows = build_preview_rows(
         rows=rows,
@@ -81,49 +131,69 @@ def generate_emails(
         only_recipients=True,
         is_generate_true_fn=is_generate_true_fn,
         is_email_valid_fn=is_email_valid_fn,
+        roles=roles,
     )
 
     # Build email -> row mapping internally
-    rows_by_email = _build_rows_by_email(rows, headers_lower, parse_name_fn)
+    rows_by_email = _build_rows_by_email(rows, headers_lower, parse_name_fn, is_generate_true_fn, roles)
 
     count = 0
     templates_by_id = {t["id"]: t for t in templates}
+    total = len(preview_rows)
 
-    for p in preview_rows:
+    def report(index: int, email: str, status: str, template: str) -> None:
+        if progress_fn:
+            progress_fn(index, total, email, status, template)
+
+    for index, p in enumerate(preview_rows):
         # Use normalized (lowercase) email for lookup since rows_by_email uses lowercase keys
         email_display = p.get("email") or ""
         email_norm = p.get("email_norm") or email_display.lower().strip()
         tid = p.get("template_id")
+        template_name = p.get("template_name") or ""
 
         if not email_norm or not tid:
+            report(index, email_display, "skipped", template_name)
             continue
 
         row = rows_by_email.get(email_norm)
         if not row:
+            report(index, email_display, "skipped", template_name)
             continue
 
         tpl = templates_by_id.get(tid)
         if not tpl:
+            report(index, email_display, "skipped", template_name)
             continue
 
-        resolver = PlaceholderResolver(headers_lower, row, parse_name_fn)
-
-        subject = resolver.resolve_text(subject_template or "")
-        body_plain = resolver.resolve_text(tpl.get("text", ""))
+        if rendered is not None:
+            entry = rendered[row_numbers[id(row)]]
+            subject = entry["subject"]["text"]
+            body_plain = entry["bodies"][tid]["text"]
+        else:
+            resolver = PlaceholderResolver(headers_lower, row, parse_name_fn, roles)
+            subject = resolver.resolve_text(subject_template or "")
+            body_plain = resolver.resolve_text(tpl.get("text", ""))
         body = _wrap_in_html(body_plain)
 
         if dry_run:
             count += 1
+            report(index, email_display, "dry_run", template_name)
             continue
 
-        _create_outlook_draft(
-            to=email_display or email_norm,  # Use display email for Outlook
-            subject=subject,
-            body=body,
-            resume_path=resume_path,
-        )
+        try:
+            _create_outlook_draft(
+                to=email_display or email_norm,  # Use display email for Outlook
+                subject=subject,
+                body=body,
+                resume_path=resume_path,
+            )
+        except Exception:
+            report(index, email_display, "failed", template_name)
+            raise
 
         count += 1
+        report(index, email_display, "created", template_name)
 
     return count
 
@@ -31,13 +31,14 @@ def choose_template_for_row(
     recipient_template_overrides: dict[str, str],
     firm_counts: dict[str, int],
     parse_name_fn: Callable[[str], tuple[str, str]],
+    roles: Optional[Dict[str, str]] = None,
 ) -> Tuple[Optional[dict], bool]:
     """
     Returns:
         (template_dict or None, is_manual_override)
     """
 
-    resolver = PlaceholderResolver(headers_lower, row, parse_name_fn)
+    resolver = PlaceholderResolver(headers_lower, row, parse_name_fn, roles)
 
     email = (resolver.get_email() or "").lower().strip()
     firm = resolver.get_firm() or ""
@@ -79,10 +80,18 @@ def build_preview_rows(
     only_recipients: bool = True,
     is_generate_true_fn: Callable[[dict], bool],
     is_email_valid_fn: Callable[[str], bool],
+    roles: Optional[Dict[str, str]] = None,
+    row_numbers: Optional[list[int]] = None,
 ) -> list[dict]:
     """
     Build preview table rows.
 
+    `roles` optionally pins the email/name/firm/school columns; see
+    PlaceholderResolver.
+
+    `row_numbers` gives the 1-based number to report for each row, e.g. its
+    position before deduplication; by default it is its position in rows.
+
     Returns list of dicts with keys:
         name
         email            (display value, original casing)
@@ -91,13 +100,15 @@ def build_preview_rows(
         template_name
         template_id
         is_manual
+        is_eligible
+        row              (1-based, from row_numbers)
     """
 
     out: list[dict] = []
     firm_counts: dict[str, int] = {}
 
-    for row in rows:
-        resolver = PlaceholderResolver(headers_lower, row, parse_name_fn)
+    for index, row in enumerate(rows):
+        resolver = PlaceholderResolver(headers_lower, row, parse_name_fn, roles)
 
         email_display = resolver.get_email() or ""
         email_norm = email_display.lower().strip()
@@ -129,6 +140,7 @@ def build_preview_rows(
                 recipient_template_overrides=recipient_template_overrides,
                 firm_counts=firm_counts,
                 parse_name_fn=parse_name_fn,
+                roles=roles,
             )
         else:
             chosen, is_manual = None, False
@@ -151,6 +163,7 @@ def build_preview_rows(
                 "template_id": tpl_id,
                 "is_manual": bool(is_manual),
                 "is_eligible": eligible,
+                "row": row_numbers[index] if row_numbers else index + 1,
             }
         )
 
@@ -1,7 +1,23 @@
 # engine/resolver.py
 
 import re
-from typing import Callable, List, Dict, Tuple
+from typing import Callable, List, Dict, Optional, Tuple
+
+
+# Column roles a profile can pin to a specific header.
+COLUMN_ROLES = ("email", "name", "firm", "school")
+
+PLACEHOLDER_PATTERN = re.compile(r"\{([^{}]+)\}", flags=re.IGNORECASE)
+
+
+def placeholders_in(text: str) -> List[str]:
+    """Placeholder names used in `text`, lowercased, in first-use order."""
+    seen: List[str] = []
+    for match in PLACEHOLDER_PATTERN.finditer(text or ""):
+        key = match.group(1).strip().lower()
+        if key not in seen:
+            seen.append(key)
+    return seen
 
 
 class PlaceholderResolver:
@@ -13,6 +29,10 @@ class PlaceholderResolver:
     2. Exact CSV header matches
     3. Empty string if unresolved
 
+    The email, name, firm and school columns come from `roles` when it maps
+    the role to one of the headers, and are guessed from the headers
+    otherwise.
+
     Supported placeholders:
     {first name}
     {last name}
@@ -32,20 +52,25 @@ class PlaceholderResolver:
         headers_lower_ordered: List[str],
         row_dict_lower: Dict[str, str],
         parse_name_fn: Callable[[str], Tuple[str, str]],
+        roles: Optional[Dict[str, str]] = None,
     ):
         self.headers = headers_lower_ordered or []
         self.row = row_dict_lower or {}
         self._parse_name = parse_name_fn
+        self._roles = roles or {}
 
-        self._full_name_header = self._first_exact_header(("full name", "name"))
-        # "Exact" match candidates for firm/company
-        self._company_header = self._first_exact_header(("firm", "company", "firm name", "company name", "business"))
+        self._full_name_header = self._role_header("name") or self._first_exact_header(("full name", "name"))
+
+        self._company_header = self._role_header("firm")
+        if not self._company_header:
+            # "Exact" match candidates for firm/company
+            self._company_header = self._first_exact_header(("firm", "company", "firm name", "company name", "business"))
         if not self._company_header:
             # Fallback to substring but avoid "email" to prevent "Firm Email" being picked as firm
             self._company_header = self._first_contains_header(self.COMPANY_OR_FIRM_SUBSTRS, exclude_substrings=("email",))
 
-        self._school_header = self._first_contains_header(self.SCHOOL_SUBSTRS)
-        self._email_header = self._first_contains_header((self.EMAIL_SUBSTR,))
+        self._school_header = self._role_header("school") or self._first_contains_header(self.SCHOOL_SUBSTRS)
+        self._email_header = self._role_header("email") or self._first_contains_header((self.EMAIL_SUBSTR,))
 
         self._derived = self._build_derived_defaults()
 
@@ -53,6 +78,13 @@ class PlaceholderResolver:
     # Header detection helpers
     # -------------------------
 
+    def _role_header(self, role: str) -> Optional[str]:
+        """The header mapped to `role`, if the mapping names a loaded header."""
+        header = (self._roles.get(role) or "").strip().lower()
+        if header and header in self.headers:
+            return header
+        return None
+
     def _first_exact_header(self, candidates):
         for h in self.headers:
             if h in candidates:
@@ -149,13 +181,30 @@ class PlaceholderResolver:
         if not text or "{" not in text:
             return text or ""
 
-        pattern = re.compile(r"\{([^{}]+)\}", flags=re.IGNORECASE)
-
         def _sub(match):
             token = match.group(1)
             return self._resolve_token(token)
 
-        return pattern.sub(_sub, text)
+        return PLACEHOLDER_PATTERN.sub(_sub, text)
+
+    def resolve_placeholder(self, name: str) -> str:
+        """Value of a single placeholder, e.g. "first name", for this row."""
+        return self._resolve_token(name)
+
+    def placeholder_header(self, name: str) -> Optional[str]:
+        """The header a placeholder reads from, or None if no header backs it."""
+        key = name.strip().lower()
+        derived_sources = {
+            "first name": self._full_name_header,
+            "last name": self._full_name_header,
+            "full name": self._full_name_header,
+            "firm": self._company_header,
+            "firm name": self._company_header,
+            "school": self._school_header,
+        }
+        if key in derived_sources:
+            return derived_sources[key]
+        return key if key in self.headers else None
 
     # -------------------------
     # Convenience getters
@@ -166,6 +215,15 @@ class PlaceholderResolver:
             return self.row.get(self._email_header, "").strip()
         return self.row.get("email", "").strip()
 
+    def get_email_header(self) -> Optional[str]:
+        return self._email_header
+
+    def get_name_header(self) -> Optional[str]:
+        return self._full_name_header
+
+    def get_firm_header(self) -> Optional[str]:
+        return self._company_header
+
     def get_firm(self) -> str:
         return self._derived.get("firm", "")
 
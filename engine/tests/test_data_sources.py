@@ -0,0 +1,156 @@
+# engine/tests/test_data_sources.py
+#
+# Run from the repository root with:
+#   python3 -m unittest discover -s engine/tests -t .
+
+import http.server
+import os
+import threading
+import unittest
+import urllib.parse
+from unittest import mock
+
+from engine import data_sources
+
+
+FIXTURES = os.path.join(os.path.dirname(__file__), "fixtures", "sheets")
+
+
+class _SheetsHandler(http.server.BaseHTTPRequestHandler):
+    """
+    Serves fixtures the way docs.google.com serves a public sheet:
+      /spreadsheets/d/<id>/htmlview          -> <id>.htmlview.html
+      /spreadsheets/d/<id>/export?gid=<gid>  -> <id>-<gid>.csv
+    """
+
+    def do_GET(self):
+        url = urllib.parse.urlparse(self.path)
+        parts = url.path.strip("/").split("/")
+        name = None
+        if len(parts) == 4 and parts[:2] == ["spreadsheets", "d"]:
+            ssid, page = parts[2], parts[3]
+            if page == "htmlview":
+                name = f"{ssid}.htmlview.html"
+            elif page == "export":
+                query = urllib.parse.parse_qs(url.query)
+                if query.get("format") == ["csv"]:
+                    name = f"{ssid}-{query.get('gid', ['0'])[0]}.csv"
+
+        self.server.requests.append(self.path)
+        path = os.path.join(FIXTURES, name) if name else None
+        if not path or not os.path.isfile(path):
+            self.send_error(404)
+            return
+
+        with open(path, "rb") as f:
+            body = f.read()
+        self.send_response(200)
+        self.send_header("Content-Length", str(len(body)))
+        self.end_headers()
+        self.wfile.write(body)
+
+    def log_message(self, format, *args):
+        pass
+
+
+class GoogleSheetTabsTest(unittest.TestCase):
+    @classmethod
+    def setUpClass(cls):
+        cls.server = http.server.ThreadingHTTPServer(("127.0.0.1", 0), _SheetsHandler)
+        cls.server.requests = []
+        cls.thread = threading.Thread(target=cls.server.serve_forever, daemon=True)
+        cls.thread.start()
+        host, port = cls.server.server_address
+        cls.base_url = f"http://{host}:{port}"
+
+    @classmethod
+    def tearDownClass(cls):
+        cls.server.shutdown()
+        cls.server.server_close()
+        cls.thread.join()
+
+    def setUp(self):
+        self.server.requests.clear()
+        patcher = mock.patch.dict(os.environ, {data_sources.GS_BASE_URL_ENV: self.base_url})
+        patcher.start()
+        self.addCleanup(patcher.stop)
+
+    @staticmethod
+    def sheet_url(ssid: str) -> str:
+        return f"https://docs.google.com/spreadsheets/d/{ssid}/edit#gid=0"
+
+    def test_lists_tabs_from_the_page_script(self):
+        tabs = data_sources.list_sheet_tabs(self.sheet_url("multi"))
+
+        self.assertEqual(
+            tabs,
+            [
+                {"name": "Contacts", "gid": "0"},
+                {"name": "Alumni", "gid": "1234"},
+                {"name": "Q1 & Q2", "gid": "99"},
+            ],
+        )
+        self.assertEqual(self.server.requests, ["/spreadsheets/d/multi/htmlview"])
+
+    def test_loads_a_tab_by_name_ignoring_case(self):
+        rows, headers = data_sources.load_google_sheet(self.sheet_url("multi"), tab=" alumni ")
+
+        self.assertEqual(headers, ["email", "class year"])
+        self.assertEqual(rows, [{"email": "alan@example.com", "class year": "1934"}])
+        self.assertEqual(self.server.requests[-1], "/spreadsheets/d/multi/export?format=csv&gid=1234")
+
+    def test_loads_a_tab_by_gid(self):
+        rows, headers = data_sources.load_google_sheet(self.sheet_url("multi"), tab="99")
+
+        self.assertEqual(headers, ["email", "quarter"])
+        self.assertEqual(rows, [{"email": "katherine@example.com", "quarter": "Q2"}])
+
+    def test_an_unknown_tab_names_the_available_ones(self):
+        with self.assertRaises(ValueError) as raised:
+            data_sources.load_google_sheet(self.sheet_url("multi"), tab="Staff")
+
+        self.assertEqual(
+            str(raised.exception),
+            "No tab named 'Staff' (available: Contacts, Alumni, Q1 & Q2)",
+        )
+        # Nothing is exported once the tab can't be found.
+        self.assertEqual(self.server.requests, ["/spreadsheets/d/multi/htmlview"])
+
+    def test_loads_several_tabs_tagged_with_their_tab(self):
+        rows, headers = data_sources.load_google_sheet_tabs(
+            self.sheet_url("multi"), ["Contacts", "1234"]
+        )
+
+        self.assertEqual(headers, ["email", "first name", "class year", "source tab"])
+        self.assertEqual(
+            rows,
+            [
+                {"email": "ada@example.com", "first name": "Ada", "class year": "", "source tab": "Contacts"},
+                {"email": "grace@example.com", "first name": "Grace", "class year": "", "source tab": "Contacts"},
+                {"email": "alan@example.com", "first name": "", "class year": "1934", "source tab": "Alumni"},
+            ],
+        )
+
+    def test_a_single_tab_sheet_is_read_from_its_button(self):
+        url = self.sheet_url("single")
+
+        self.assertEqual(
+            data_sources.list_sheet_tabs(url),
+            [{"name": "Sign-ups & RSVPs", "gid": "0"}],
+        )
+        rows, headers = data_sources.load_google_sheet_tabs(url, ["sign-ups & rsvps"])
+        self.assertEqual(headers, ["email", "name", "source tab"])
+        self.assertEqual(
+            rows,
+            [{"email": "lin@example.com", "name": "Lin", "source tab": "Sign-ups & RSVPs"}],
+        )
+
+    def test_a_tab_with_its_own_source_tab_column_is_refused(self):
+        with self.assertRaises(ValueError) as raised:
+            data_sources.load_google_sheet_tabs(self.sheet_url("tagged"), ["Imported", "Manual"])
+
+        self.assertIn("'Imported' already has a 'source tab' column", str(raised.exception))
+
+
+if __name__ == "__main__":
+    unittest.main()
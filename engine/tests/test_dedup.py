@@ -0,0 +1,198 @@
+# engine/tests/test_dedup.py
+#
+# Run from the repository root with:
+#   python3 -m unittest discover -s engine/tests -t .
+
+import contextlib
+import io
+import json
+import unittest
+from unittest import mock
+
+from engine import dedup, generator
+from engine.__main__ import _is_email_valid, _is_generate_true, _parse_name, build_parser
+
+
+HEADERS = ["email", "name", "firm"]
+TEMPLATES = [{"id": "t1", "name": "Intro", "text": "Hi {first name}"}]
+
+
+def rows():
+    # Row 3 is row 1's recipient written differently.
+    return [
+        {"email": "ada@example.com", "name": "Ada Lovelace", "firm": "Acme"},
+        {"email": "grace@example.com", "name": "Grace Hopper", "firm": "Navy"},
+        {"email": " ADA@Example.com ", "name": "Ada King", "firm": "Acme"},
+        {"email": "alan@example.com", "name": "Alan Turing", "firm": "Bletchley"},
+    ]
+
+
+def dedupe(data, policy):
+    return dedup.dedupe_rows(
+        data,
+        HEADERS,
+        _parse_name,
+        policy=policy,
+        is_generate_true_fn=_is_generate_true,
+        is_email_valid_fn=_is_email_valid,
+    )
+
+
+def names(data):
+    return [row["name"] for row in data]
+
+
+class DedupeRowsTest(unittest.TestCase):
+    def test_keep_first_keeps_the_earliest_row(self):
+        kept, duplicates = dedupe(rows(), dedup.KEEP_FIRST)
+
+        self.assertEqual(names(kept), ["Ada Lovelace", "Grace Hopper", "Alan Turing"])
+        self.assertEqual(duplicates, [{"email": "ada@example.com", "rows": [1, 3], "kept": 1}])
+
+    def test_keep_last_keeps_the_latest_row_in_place(self):
+        kept, duplicates = dedupe(rows(), dedup.KEEP_LAST)
+
+        self.assertEqual(names(kept), ["Grace Hopper", "Ada King", "Alan Turing"])
+        self.assertEqual(duplicates, [{"email": "ada@example.com", "rows": [1, 3], "kept": 3}])
+
+    def test_skip_all_drops_every_row_of_a_duplicate(self):
+        kept, duplicates = dedupe(rows(), dedup.SKIP_ALL)
+
+        self.assertEqual(names(kept), ["Grace Hopper", "Alan Turing"])
+        self.assertEqual(duplicates, [{"email": "ada@example.com", "rows": [1, 3], "kept": None}])
+
+    def test_error_raises_with_the_conflicting_rows(self):
+        data = rows() + [{"email": "Grace@example.com\t", "name": "G. Hopper", "firm": "Navy"}]
+
+        with self.assertRaises(dedup.DuplicateRecipientsError) as raised:
+            dedupe(data, dedup.ERROR)
+
+        self.assertEqual(
+            raised.exception.duplicates,
+            [
+                {"email": "ada@example.com", "rows": [1, 3], "kept": 1},
+                {"email": "grace@example.com", "rows": [2, 5], "kept": 2},
+            ],
+        )
+        self.assertEqual(
+            str(raised.exception),
+            "Duplicate recipients: ada@example.com (rows 1, 3); grace@example.com (rows 2, 5)",
+        )
+
+    def test_rows_that_are_not_recipients_are_never_duplicates(self):
+        data = [
+            {"email": "ada@example.com", "name": "Ada", "firm": "", "generate": "no"},
+            {"email": "ada@example.com", "name": "Ada", "firm": "", "generate": "yes"},
+            {"email": "not an email", "name": "X", "firm": "", "generate": "yes"},
+            {"email": "not an email", "name": "Y", "firm": "", "generate": "yes"},
+        ]
+
+        kept, duplicates = dedup.dedupe_rows(
+            data,
+            HEADERS + ["generate"],
+            _parse_name,
+            policy=dedup.ERROR,
+            is_generate_true_fn=_is_generate_true,
+            is_email_valid_fn=_is_email_valid,
+        )
+
+        self.assertEqual(kept, data)
+        self.assertEqual(duplicates, [])
+
+    def test_an_unknown_policy_is_rejected(self):
+        with self.assertRaises(ValueError):
+            dedupe(rows(), "keep_both")
+
+
+class PreviewRowNumbersTest(unittest.TestCase):
+    def preview(self, policy):
+        args = build_parser().parse_args([
+            "preview",
+            "--data", json.dumps({"rows": rows(), "headers": HEADERS}),
+            "--templates", json.dumps(TEMPLATES),
+            "--dedup", policy,
+        ])
+        out = io.StringIO()
+        with contextlib.redirect_stdout(out):
+            args.func(args)
+        response = json.loads(out.getvalue())
+        self.assertTrue(response["success"], response["error"])
+        return response["data"]
+
+    def test_kept_rows_keep_their_numbers_from_before_dedup(self):
+        expected = {
+            dedup.KEEP_FIRST: [("ada@example.com", 1), ("grace@example.com", 2), ("alan@example.com", 4)],
+            dedup.KEEP_LAST: [("grace@example.com", 2), ("ada@example.com", 3), ("alan@example.com", 4)],
+            dedup.SKIP_ALL: [("grace@example.com", 2), ("alan@example.com", 4)],
+        }
+
+        for policy, numbered in expected.items():
+            with self.subTest(policy=policy):
+                data = self.preview(policy)
+                self.assertEqual([(p["email_norm"], p["row"]) for p in data["preview_rows"]], numbered)
+                self.assertEqual(data["duplicates"][0]["rows"], [1, 3])
+
+
+class GenerateRenderedLookupTest(unittest.TestCase):
+    def generate(self, policy):
+        """Run generate with each row's rendering naming its row; return the drafts made."""
+        rendered = [
+            {
+                "subject": {"text": f"Subject for row {n}"},
+                "bodies": {"t1": {"text": f"Body for row {n}"}},
+            }
+            for n in range(1, len(rows()) + 1)
+        ]
+        drafts = []
+        duplicates = []
+
+        def create_draft(*, to, subject, body, resume_path):
+            drafts.append((to.strip().lower(), subject, body))
+
+        with mock.patch.object(generator, "_create_outlook_draft", side_effect=create_draft):
+            count = generator.generate_emails(
+                rows(),
+                HEADERS,
+                TEMPLATES,
+                {},
+                _parse_name,
+                _is_generate_true,
+                _is_email_valid,
+                subject_template="unused {first name}",
+                resume_path=None,
+                dedup_policy=policy,
+                duplicates_fn=duplicates.extend,
+                rendered=rendered,
+            )
+
+        self.assertEqual(count, len(drafts))
+        return drafts, duplicates
+
+    def assert_drafts(self, drafts, expected):
+        self.assertEqual(
+            [(to, subject) for to, subject, _ in drafts],
+            [(to, f"Subject for row {n}") for to, n in expected],
+        )
+        for (_, _, body), (_, n) in zip(drafts, expected):
+            self.assertIn(f"Body for row {n}", body)
+
+    def test_each_kept_row_gets_its_own_rendering_after_rows_are_dropped(self):
+        expected = {
+            dedup.KEEP_FIRST: [("ada@example.com", 1), ("grace@example.com", 2), ("alan@example.com", 4)],
+            dedup.KEEP_LAST: [("grace@example.com", 2), ("ada@example.com", 3), ("alan@example.com", 4)],
+            dedup.SKIP_ALL: [("grace@example.com", 2), ("alan@example.com", 4)],
+        }
+
+        for policy, numbered in expected.items():
+            with self.subTest(policy=policy):
+                drafts, duplicates = self.generate(policy)
+                self.assert_drafts(drafts, numbered)
+                self.assertEqual([d["rows"] for d in duplicates], [[1, 3]])
+
+    def test_error_creates_no_drafts(self):
+        with self.assertRaises(dedup.DuplicateRecipientsError):
+            self.generate(dedup.ERROR)
+
+
+if __name__ == "__main__":
+    unittest.main()
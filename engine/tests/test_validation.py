@@ -0,0 +1,182 @@
+# engine/tests/test_validation.py
+#
+# Run from the repository root with:
+#   python3 -m unittest discover -s engine/tests -t .
+
+import unittest
+
+from engine import dedup
+from engine.__main__ import _is_email_valid, _is_generate_true, _parse_name
+from engine.validation import ERROR, WARNING, validate_dataset
+
+
+HEADERS = ["email", "name", "firm", "generate"]
+INTRO = {"id": "t1", "name": "Intro", "text": "Hello"}
+
+
+def row(email="ada@example.com", name="Ada Lovelace", firm="Acme", generate="yes"):
+    return {"email": email, "name": name, "firm": firm, "generate": generate}
+
+
+def validate(rows, templates=(INTRO,), subject="Introduction", **kwargs):
+    return validate_dataset(
+        rows,
+        HEADERS,
+        list(templates),
+        kwargs.pop("overrides", {}),
+        _parse_name,
+        subject_template=subject,
+        is_generate_true_fn=_is_generate_true,
+        is_email_valid_fn=_is_email_valid,
+        **kwargs,
+    )
+
+
+def findings(report):
+    """(row, column, code, severity) of each finding, in order."""
+    return [(f["row"], f["column"], f["code"], f["severity"]) for f in report["findings"]]
+
+
+class ValidateDatasetTest(unittest.TestCase):
+    def test_a_clean_dataset_has_no_findings(self):
+        report = validate([row(), row(email="grace@example.com", name="Grace Hopper")])
+
+        self.assertEqual(report, {"findings": [], "errors": 0, "warnings": 0})
+
+    def test_an_unreadable_generate_value_is_a_warning_and_skips_the_row(self):
+        # Everything else about row 2 is wrong too, but it won't be sent.
+        report = validate([row(), row(email="", name="", firm="", generate="maybe")])
+
+        self.assertEqual(findings(report), [(2, "generate", "invalid_generate", WARNING)])
+        self.assertEqual(
+            report["findings"][0]["message"],
+            '"maybe" is neither yes nor no; the row will be skipped',
+        )
+        self.assertEqual((report["errors"], report["warnings"]), (0, 1))
+
+    def test_rows_not_marked_to_generate_are_not_checked(self):
+        report = validate([row(email="", generate="no")])
+
+        self.assertEqual(findings(report), [])
+
+    def test_a_missing_email_is_a_warning(self):
+        report = validate([row(), row(email="")])
+
+        self.assertEqual(findings(report), [(2, "email", "missing_email", WARNING)])
+
+    def test_an_invalid_email_is_an_error(self):
+        report = validate([row(email="ada@example")])
+
+        self.assertEqual(findings(report), [(1, "email", "invalid_email", ERROR)])
+        self.assertEqual(report["findings"][0]["message"], '"ada@example" is not a valid email address')
+
+    def test_duplicate_emails_are_reported_per_dedup_policy(self):
+        rows = [
+            row(),
+            row(email="grace@example.com", name="Grace Hopper"),
+            row(email=" ADA@example.com ", name="Ada King"),
+        ]
+        expected = {
+            dedup.KEEP_FIRST: [(3, WARNING, "ada@example.com is also on row 1, whose data is used instead")],
+            dedup.KEEP_LAST: [(1, WARNING, "ada@example.com is also on row 3, whose data is used instead")],
+            dedup.SKIP_ALL: [
+                (1, WARNING, "ada@example.com is on rows 1, 3; no draft will be created for it"),
+                (3, WARNING, "ada@example.com is on rows 1, 3; no draft will be created for it"),
+            ],
+            dedup.ERROR: [(3, ERROR, "ada@example.com is also on row 1")],
+        }
+
+        for policy, notes in expected.items():
+            with self.subTest(policy=policy):
+                report = validate(rows, dedup_policy=policy)
+                self.assertEqual(
+                    [(f["row"], f["column"], f["code"], f["severity"], f["message"]) for f in report["findings"]],
+                    [(number, "email", "duplicate_email", severity, message) for number, severity, message in notes],
+                )
+
+    def test_dropped_duplicates_are_not_checked_further(self):
+        # Row 2's blank firm would be a finding, but keep_first drops the row.
+        report = validate([row(), row(email="Ada@Example.com", firm="")])
+
+        self.assertEqual(findings(report), [(2, "email", "duplicate_email", WARNING)])
+
+    def test_an_empty_name_is_a_warning(self):
+        report = validate([row(name="")])
+
+        self.assertEqual(findings(report), [(1, "name", "empty_name", WARNING)])
+
+    def test_an_empty_firm_is_a_warning(self):
+        report = validate([row(), row(email="grace@example.com", firm="")])
+
+        self.assertEqual(findings(report), [(2, "firm", "empty_firm", WARNING)])
+
+    def test_a_row_no_template_applies_to_is_an_error(self):
+        manual = dict(INTRO, manual_only=True)
+        report = validate(
+            [row(), row(email="grace@example.com")],
+            templates=[manual],
+            overrides={"ada@example.com": "t1"},
+        )
+
+        self.assertEqual(findings(report), [(2, None, "no_template", ERROR)])
+
+    def test_empty_placeholders_are_errors_naming_their_column(self):
+        template = {"id": "t1", "name": "Intro", "text": "Dear {first name}, about {school}"}
+        report = validate(
+            [row(), row(email="grace@example.com", firm="")],
+            templates=[template],
+            subject="{firm} introduction",
+        )
+
+        self.assertEqual(
+            findings(report),
+            [
+                (1, None, "empty_placeholder", ERROR),
+                (2, "firm", "empty_firm", WARNING),
+                (2, None, "empty_placeholder", ERROR),
+                (2, "firm", "empty_placeholder", ERROR),
+            ],
+        )
+        messages = [f["message"] for f in report["findings"]]
+        self.assertEqual(messages[0], '{school} in template "Intro" matches no column')
+        self.assertEqual(messages[3], "{firm} in the subject is empty for this row")
+
+    def test_rendered_rows_limit_empty_placeholders_to_the_ones_rendered_blank(self):
+        # {firm|your firm} has a default, so the app doesn't list it as empty.
+        template = {"id": "t1", "name": "Intro", "text": "Dear {first name} at {firm|your firm}"}
+        rendered = [
+            {
+                "subject": {"text": "Hello", "empty": []},
+                "bodies": {"t1": {"text": "Dear Ada at Acme", "empty": []}},
+            },
+            {
+                "subject": {"text": "Hello", "empty": []},
+                "bodies": {"t1": {"text": "Dear  at your firm", "empty": ["first name"]}},
+            },
+        ]
+        rows = [row(), row(email="grace@example.com", name="", firm="")]
+
+        report = validate(rows, templates=[template], subject="Hello", rendered=rendered)
+        self.assertEqual(
+            findings(report),
+            [
+                (2, "name", "empty_name", WARNING),
+                (2, "firm", "empty_firm", WARNING),
+                (2, "name", "empty_placeholder", ERROR),
+            ],
+        )
+
+        # Without it, the engine reads "firm|your firm" as a column name.
+        report = validate(rows, templates=[template], subject="Hello")
+        self.assertEqual(
+            [(f["row"], f["message"]) for f in report["findings"] if f["code"] == "empty_placeholder"],
+            [
+                (1, '{firm|your firm} in template "Intro" matches no column'),
+                (2, '{first name} in template "Intro" is empty for this row'),
+                (2, '{firm|your firm} in template "Intro" matches no column'),
+            ],
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,184 @@
+# engine/validation.py
+
+from typing import Callable, Dict, List, Optional
+
+from engine import dedup
+from engine.preview import choose_template_for_row
+from engine.resolver import PlaceholderResolver, placeholders_in
+
+
+# Values the Generate column may hold. Anything else is probably a typo,
+# and is treated as "no" by _parse_bool.
+GENERATE_TRUE = {"1", "true", "yes", "y"}
+GENERATE_FALSE = {"", "0", "false", "no", "n"}
+GENERATE_HEADERS = ("generate", "gen")
+
+ERROR = "error"
+WARNING = "warning"
+
+
+def _finding(row: int, column: Optional[str], code: str, severity: str, message: str) -> dict:
+    return {
+        "row": row,
+        "column": column,
+        "code": code,
+        "severity": severity,
+        "message": message,
+    }
+
+
+def validate_dataset(
+    rows: List[dict],
+    headers_lower: List[str],
+    templates: List[dict],
+    recipient_template_overrides: Dict[str, str],
+    parse_name_fn: Callable[[str], tuple],
+    *,
+    subject_template: str,
+    is_generate_true_fn: Callable[[dict], bool],
+    is_email_valid_fn: Callable[[str], bool],
+    roles: Optional[Dict[str, str]] = None,
+    dedup_policy: str = dedup.KEEP_FIRST,
+    rendered: Optional[List[dict]] = None,
+) -> dict:
+    """
+    Check rows for problems that would otherwise surface as missing or
+    broken drafts.
+
+    Rows are numbered from 1 in the order given. Rows whose Generate column
+    says "no" are only checked for that column. Templates are picked exactly
+    as preview and generate pick them, so placeholder findings refer to the
+    template each row will actually get. An unreadable Generate value is a
+    warning, since the row is just skipped. Duplicate recipients are errors
+    under the "error" dedup policy and warnings under the others, which
+    resolve them.
+
+    rendered, if given, is the app's rendering of each row as described in
+    generate_emails; placeholders are then only reported where its "empty"
+    lists name them, so ones with a default or in a skipped block are not.
+
+    Returns a dict with keys:
+        findings    list of {row, column, code, severity, message}
+        errors      number of findings with severity "error"
+        warnings    number of findings with severity "warning"
+    """
+
+    findings: List[dict] = []
+    firm_counts: Dict[str, int] = {}
+    # Find duplicates without raising, so "error" can be reported per row.
+    kept_rows, duplicates = dedup.dedupe_rows(
+        rows, headers_lower, parse_name_fn,
+        policy=dedup.KEEP_FIRST if dedup_policy == dedup.ERROR else dedup_policy,
+        is_generate_true_fn=is_generate_true_fn,
+        is_email_valid_fn=is_email_valid_fn,
+        roles=roles,
+    )
+    kept_ids = {id(row) for row in kept_rows}
+    duplicate_notes = _duplicate_notes(duplicates, dedup_policy)
+    generate_header = next((h for h in GENERATE_HEADERS if h in headers_lower), None)
+    subject_placeholders = placeholders_in(subject_template)
+
+    for number, row in enumerate(rows, start=1):
+        resolver = PlaceholderResolver(headers_lower, row, parse_name_fn, roles)
+
+        if generate_header:
+            value = str(row.get(generate_header, "")).strip().lower()
+            if value not in GENERATE_TRUE and value not in GENERATE_FALSE:
+                findings.append(_finding(
+                    number, generate_header, "invalid_generate", WARNING,
+                    f'"{row.get(generate_header)}" is neither yes nor no; the row will be skipped',
+                ))
+
+        if not is_generate_true_fn(row):
+            continue
+
+        email_header = resolver.get_email_header()
+        email = resolver.get_email()
+        if not email:
+            findings.append(_finding(
+                number, email_header, "missing_email", WARNING,
+                "No email address; the row will be skipped",
+            ))
+            continue
+        if not is_email_valid_fn(email):
+            findings.append(_finding(
+                number, email_header, "invalid_email", ERROR,
+                f'"{email}" is not a valid email address',
+            ))
+            continue
+        if number in duplicate_notes:
+            severity, message = duplicate_notes[number]
+            findings.append(_finding(number, email_header, "duplicate_email", severity, message))
+        if id(row) not in kept_ids:
+            continue
+
+        if not resolver.get_full_name() and not any(resolver.get_first_last()):
+            findings.append(_finding(
+                number, resolver.get_name_header(), "empty_name", WARNING, "Name is empty",
+            ))
+        if not resolver.get_firm():
+            findings.append(_finding(
+                number, resolver.get_firm_header(), "empty_firm", WARNING, "Firm is empty",
+            ))
+
+        template, _ = choose_template_for_row(
+            row=row,
+            headers_lower=headers_lower,
+            templates=templates,
+            recipient_template_overrides=recipient_template_overrides,
+            firm_counts=firm_counts,
+            parse_name_fn=parse_name_fn,
+            roles=roles,
+        )
+        if template is None:
+            findings.append(_finding(
+                number, None, "no_template", ERROR, "No template applies to this row",
+            ))
+            continue
+
+        if rendered is not None:
+            entry = rendered[number - 1]
+            body_placeholders = entry["bodies"][template["id"]]["empty"]
+            subject_empty = entry["subject"]["empty"]
+            used = body_placeholders + [p for p in subject_empty if p not in body_placeholders]
+        else:
+            body_placeholders = placeholders_in(template.get("text", ""))
+            used = body_placeholders + [p for p in subject_placeholders if p not in body_placeholders]
+        for name in used:
+            if resolver.resolve_placeholder(name):
+                continue
+            if name in body_placeholders:
+                where = f'template "{template.get("name", "")}"'
+            else:
+                where = "the subject"
+            header = resolver.placeholder_header(name)
+            problem = "is empty for this row" if header else "matches no column"
+            findings.append(_finding(
+                number, header, "empty_placeholder", ERROR, f"{{{name}}} in {where} {problem}",
+            ))
+
+    errors = sum(1 for f in findings if f["severity"] == ERROR)
+    return {
+        "findings": findings,
+        "errors": errors,
+        "warnings": len(findings) - errors,
+    }
+
+
+def _duplicate_notes(duplicates: List[dict], policy: str) -> Dict[int, tuple]:
+    """Row number -> (severity, message) for rows hit by the dedup policy."""
+    notes: Dict[int, tuple] = {}
+    for d in duplicates:
+        rows = d["rows"]
+        if policy == dedup.ERROR:
+            for number in rows[1:]:
+                notes[number] = (ERROR, f"{d['email']} is also on row {rows[0]}")
+        elif policy == dedup.SKIP_ALL:
+            listed = ", ".join(str(n) for n in rows)
+            for number in rows:
+                notes[number] = (WARNING, f"{d['email']} is on rows {listed}; no draft will be created for it")
+        else:
+            for number in rows:
+                if number != d["kept"]:
+                    notes[number] = (WARNING, f"{d['email']} is also on row {d['kept']}, whose data is used instead")
+    return notes
@@ -0,0 +1,453 @@
+//! The typed commands' behaviour, independent of Tauri and of the backend.
+//!
+//! Each function validates its parameters, then either reads local files
+//! with the native loaders in `data` or registers a job for the call and
+//! hands the work to an `EngineBackend`. The Tauri commands in
+//! `commands` are thin wrappers around these, so everything here can be
+//! exercised against `MockBackend` on a machine without Python or Outlook.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::data::watch::OnDataChanged;
+use crate::data::{self, DataWatcher, Drafted, JoinResult, RolesReport};
+use crate::engine::{
+    ops, EngineBackend, EngineError, GenerateProgress, GenerateSummary, JobId, JobManager,
+};
+use crate::filter::Filter;
+use crate::model::{
+    ColumnRoles, CsvLoadResult, DataLoadResult, Dataset, DuplicateRecipient, EmailPreview,
+    ExportResult, FilterMatches, GenerateRequest, GenerateResult, JoinRequest, LicenseResult,
+    LintRequest, PreviewRequest, PreviewResult, ReadFilesResult, RenderRequest, SheetTab, Template,
+    ValidateRequest, ValidationReport, WriteBack, WriteBackReport,
+};
+use crate::template::lint::LintReport;
+use crate::template::{self, Resolver, TemplateText};
+
+/// Outcome of `cancel_job`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CancelReport {
+    pub id: JobId,
+    /// Recipients the engine had finished before it was stopped.
+    pub processed: usize,
+}
+
+/// What `generate` reports while it runs.
+#[derive(Debug)]
+pub enum GenerateEvent<'a> {
+    /// One recipient was handled.
+    Progress(&'a GenerateProgress),
+    /// The run finished, successfully or not. Always the last event.
+    Complete(&'a GenerateSummary),
+}
+
+/// Read a local CSV file natively; see `data::csv` for the sniffing rules.
+pub fn load_csv(path: &str) -> Result<CsvLoadResult, EngineError> {
+    ops::check_csv_path(path)?;
+    data::load_csv(Path::new(path))
+}
+
+/// Watch a local CSV file and report each change to `on_change`, matching
+/// recipients across reloads by the email column `roles` resolves to.
+pub fn watch_data_file(
+    watcher: &DataWatcher,
+    path: &str,
+    roles: ColumnRoles,
+    on_change: OnDataChanged,
+) -> Result<(), EngineError> {
+    ops::check_csv_path(path)?;
+    watcher.watch(Path::new(path), roles, on_change)
+}
+
+/// Sheet names of a local `.xlsx`/`.xls`/`.ods` workbook, in order.
+pub fn list_sheets(path: &str) -> Result<Vec<String>, EngineError> {
+    ops::check_workbook_path(path)?;
+    data::list_sheets(Path::new(path))
+}
+
+/// Load one sheet of a local workbook, or its first sheet if `sheet` is `None`.
+pub fn load_workbook(path: &str, sheet: Option<&str>) -> Result<DataLoadResult, EngineError> {
+    ops::check_workbook_path(path)?;
+    data::load_sheet(Path::new(path), sheet)
+}
+
+/// Guess which loaded header holds each column role.
+pub fn suggest_column_roles(headers: &[String]) -> ColumnRoles {
+    data::suggest_roles(headers)
+}
+
+/// Check a profile's column roles against the loaded headers.
+pub fn validate_column_roles(roles: &ColumnRoles, headers: &[String]) -> RolesReport {
+    data::validate_roles(roles, headers)
+}
+
+/// Load a Google Sheet: the tab in the URL, or `tabs` by name or gid.
+pub async fn load_sheet(
+    backend: &dyn EngineBackend,
+    jobs: &JobManager,
+    url: &str,
+    tabs: &[String],
+) -> Result<DataLoadResult, EngineError> {
+    ops::check_sheet_url(url)?;
+    ops::check_sheet_tabs(tabs)?;
+    let guard = jobs.start("load-sheet");
+    backend.load_sheet(guard.job(), url, tabs).await
+}
+
+/// The tabs of a Google Sheet, in order.
+pub async fn list_sheet_tabs(
+    backend: &dyn EngineBackend,
+    jobs: &JobManager,
+    url: &str,
+) -> Result<Vec<SheetTab>, EngineError> {
+    ops::check_sheet_url(url)?;
+    let guard = jobs.start("list-sheet-tabs");
+    backend.list_sheet_tabs(guard.job(), url).await
+}
+
+/// Join already-loaded sources into one dataset; see `data::join`.
+pub fn join_sources(request: &JoinRequest) -> Result<JoinResult, EngineError> {
+    data::join_sources(request)
+}
+
+/// Which rows of `data` the profile filter selects.
+pub fn filter_rows(
+    filter: &str,
+    data: &Dataset,
+    roles: &ColumnRoles,
+) -> Result<FilterMatches, EngineError> {
+    let total = data.rows.len();
+    let rows = match Filter::compile(filter, &data.headers, roles)? {
+        Some(filter) => filter.matching_rows(&data.rows),
+        None => (0..total).collect(),
+    };
+    Ok(FilterMatches { rows, total })
+}
+
+/// Lint the subject and templates; see `template::lint`.
+///
+/// Only a bad filter fails; problems in the templates are in the report.
+pub fn lint_templates(request: &LintRequest) -> Result<LintReport, EngineError> {
+    let narrowed = Narrowed::new(&request.data, request.filter.as_deref(), &request.roles)?;
+    let data = narrowed
+        .as_ref()
+        .map_or(&request.data, |narrowed| &narrowed.data);
+    Ok(template::lint::lint(request, data))
+}
+
+/// A request's data cut down to the rows its filter selects.
+struct Narrowed {
+    data: Dataset,
+    /// Index in the original data of each kept row.
+    kept: Vec<usize>,
+}
+
+impl Narrowed {
+    /// `None` when there is no filter, so the request can be sent as is.
+    fn new(
+        data: &Dataset,
+        filter: Option<&str>,
+        roles: &ColumnRoles,
+    ) -> Result<Option<Self>, EngineError> {
+        let Some(filter) = Filter::compile(filter.unwrap_or(""), &data.headers, roles)? else {
+            return Ok(None);
+        };
+        let kept = filter.matching_rows(&data.rows);
+        let data = Dataset {
+            rows: kept.iter().map(|&index| data.rows[index].clone()).collect(),
+            headers: data.headers.clone(),
+        };
+        Ok(Some(Self { data, kept }))
+    }
+
+    /// Map a 1-based row number in the narrowed data back to the original.
+    fn original_row(&self, row: usize) -> usize {
+        row.checked_sub(1)
+            .and_then(|index| self.kept.get(index))
+            .map_or(row, |index| index + 1)
+    }
+
+    fn renumber_duplicates(&self, duplicates: &mut [DuplicateRecipient]) {
+        for duplicate in duplicates {
+            for row in &mut duplicate.rows {
+                *row = self.original_row(*row);
+            }
+            duplicate.kept = duplicate.kept.map(|row| self.original_row(row));
+        }
+    }
+}
+
+/// Build the preview for the rows the request's filter selects.
+///
+/// Row numbers in the result refer to `request.data`, filtered or not.
+pub async fn preview(
+    backend: &dyn EngineBackend,
+    jobs: &JobManager,
+    request: &PreviewRequest,
+) -> Result<PreviewResult, EngineError> {
+    let narrowed = Narrowed::new(&request.data, request.filter.as_deref(), &request.roles)?;
+    template::parse_templates(&request.templates)?;
+    let guard = jobs.start("preview");
+    let Some(narrowed) = narrowed else {
+        return backend.preview(guard.job(), request).await;
+    };
+
+    let request = PreviewRequest {
+        data: narrowed.data.clone(),
+        ..request.clone()
+    };
+    let mut result = backend.preview(guard.job(), &request).await?;
+    narrowed.renumber_duplicates(&mut result.duplicates);
+    for row in &mut result.preview_rows {
+        row.row = row.row.map(|row| narrowed.original_row(row));
+    }
+    Ok(result)
+}
+
+/// Render the subject, body and HTML the recipients at `emails` will get,
+/// in preview order. Templates are chosen by the engine's preview, as for
+/// `generate`.
+///
+/// Emails are compared ignoring case. Any that won't get a draft, because
+/// they aren't a recipient or the filter or duplicate policy leaves them
+/// out, are skipped; no emails renders every recipient.
+pub async fn render_previews(
+    backend: &dyn EngineBackend,
+    jobs: &JobManager,
+    request: &RenderRequest,
+    emails: &[String],
+) -> Result<Vec<EmailPreview>, EngineError> {
+    let subject = TemplateText::parse(&request.subject).map_err(|e| e.in_template(None))?;
+    let bodies = template::parse_templates(&request.templates)?;
+    let chosen = preview(
+        backend,
+        jobs,
+        &PreviewRequest {
+            data: request.data.clone(),
+            templates: request.templates.clone(),
+            overrides: request.overrides.clone(),
+            only_recipients: true,
+            roles: request.roles.clone(),
+            dedup: request.dedup,
+            filter: request.filter.clone(),
+        },
+    )
+    .await?;
+
+    let wanted: HashSet<String> = emails.iter().map(|e| e.trim().to_lowercase()).collect();
+    let resolved = data::validate_roles(&request.roles, &request.data.headers).resolved;
+    let mut previews = Vec::new();
+    for recipient in chosen.preview_rows {
+        if !wanted.is_empty() && !wanted.contains(&recipient.email_norm) {
+            continue;
+        }
+        let Some(number) = recipient.row else {
+            continue;
+        };
+        let Some(row) = number.checked_sub(1).and_then(|i| request.data.rows.get(i)) else {
+            continue;
+        };
+        let resolver = Resolver::with_resolved(&request.data.headers, row, &resolved);
+        let body = recipient
+            .template_id
+            .as_deref()
+            .and_then(|id| bodies.get(id))
+            .map(|body| body.annotate(&resolver));
+        previews.push(EmailPreview {
+            email: recipient.email,
+            row: number,
+            template_id: recipient.template_id,
+            template_name: recipient.template_name,
+            subject: subject.annotate(&resolver),
+            html: body.as_ref().map(|body| template::wrap_in_html(&body.text)),
+            body,
+        });
+    }
+    Ok(previews)
+}
+
+/// `render_previews` for one recipient, failing if `email` won't get a
+/// draft.
+pub async fn render_preview(
+    backend: &dyn EngineBackend,
+    jobs: &JobManager,
+    request: &RenderRequest,
+    email: &str,
+) -> Result<EmailPreview, EngineError> {
+    ops::check_email(email)?;
+    render_previews(backend, jobs, request, &[email.to_string()])
+        .await?
+        .pop()
+        .ok_or_else(|| EngineError::InvalidArgument {
+            message: format!(
+                "No draft for {}: not a recipient, or left out by the filter or duplicate policy",
+                email.trim()
+            ),
+        })
+}
+
+/// Generate drafts, passing each progress update and then a final summary
+/// to `on_event`.
+///
+/// With a `write_back`, the recipients drafted are then marked in the CSV,
+/// even if the run failed or was cancelled partway.
+///
+/// Subjects and bodies are rendered here; the engine only picks templates.
+pub async fn generate(
+    backend: &dyn EngineBackend,
+    jobs: &JobManager,
+    request: &GenerateRequest,
+    on_event: &mut (dyn FnMut(GenerateEvent<'_>) + Send),
+) -> Result<GenerateResult, EngineError> {
+    ops::check_generate(request)?;
+    let narrowed = Narrowed::new(&request.data, request.filter.as_deref(), &request.roles)?;
+    let mut request = match &narrowed {
+        Some(narrowed) => GenerateRequest {
+            data: narrowed.data.clone(),
+            ..request.clone()
+        },
+        None => request.clone(),
+    };
+    request.rendered = template::render_rows(
+        &request.data,
+        &request.subject,
+        &request.templates,
+        &request.roles,
+    )?;
+
+    let guard = jobs.start("generate");
+    let job = guard.job();
+    let mut summary = GenerateSummary::new(job.id());
+    let mut drafted = Vec::new();
+
+    let mut result = backend
+        .generate(job, &request, &mut |progress| {
+            summary.record(&progress);
+            if progress.status == "created" {
+                drafted.push(Drafted {
+                    email: progress.email.clone(),
+                    template: progress.template.clone(),
+                });
+            }
+            on_event(GenerateEvent::Progress(&progress));
+        })
+        .await;
+
+    if let (Ok(result), Some(narrowed)) = (&mut result, &narrowed) {
+        narrowed.renumber_duplicates(&mut result.duplicates);
+    }
+    if let Some(write_back) = &request.write_back {
+        if !request.dry_run && !drafted.is_empty() {
+            let report = mark_drafted(write_back, &drafted, &request.roles);
+            if let Ok(result) = &mut result {
+                result.write_back = Some(report);
+            }
+        }
+    }
+    summary.finish(&result);
+    on_event(GenerateEvent::Complete(&summary));
+    result
+}
+
+/// Mark `drafted` in the write-back CSV. Failures are reported rather than
+/// returned, since the drafts already exist either way.
+fn mark_drafted(
+    write_back: &WriteBack,
+    drafted: &[Drafted],
+    roles: &ColumnRoles,
+) -> WriteBackReport {
+    ops::check_csv_path(&write_back.path)
+        .and_then(|()| {
+            data::write_back(
+                Path::new(&write_back.path),
+                drafted,
+                &write_back.drafted_at,
+                roles,
+            )
+        })
+        .unwrap_or_else(|err| WriteBackReport {
+            error: Some(err.to_string()),
+            ..WriteBackReport::default()
+        })
+}
+
+/// Check the data for problems before generating; see `ValidationReport`.
+///
+/// Only rows the request's filter selects are checked, but finding row
+/// numbers refer to `request.data`.
+pub async fn validate_dataset(
+    backend: &dyn EngineBackend,
+    jobs: &JobManager,
+    request: &ValidateRequest,
+) -> Result<ValidationReport, EngineError> {
+    let narrowed = Narrowed::new(&request.data, request.filter.as_deref(), &request.roles)?;
+    let mut request = match &narrowed {
+        Some(narrowed) => ValidateRequest {
+            data: narrowed.data.clone(),
+            ..request.clone()
+        },
+        None => request.clone(),
+    };
+    request.rendered = template::render_rows(
+        &request.data,
+        &request.subject,
+        &request.templates,
+        &request.roles,
+    )?;
+
+    let guard = jobs.start("validate");
+    let mut report = backend.validate_dataset(guard.job(), &request).await?;
+    if let Some(narrowed) = &narrowed {
+        for finding in &mut report.findings {
+            finding.row = narrowed.original_row(finding.row);
+        }
+    }
+    Ok(report)
+}
+
+pub async fn read_files(
+    backend: &dyn EngineBackend,
+    jobs: &JobManager,
+    paths: &[String],
+) -> Result<ReadFilesResult, EngineError> {
+    ops::check_paths(paths)?;
+    let guard = jobs.start("read-files");
+    backend.read_files(guard.job(), paths).await
+}
+
+pub async fn export_templates(
+    backend: &dyn EngineBackend,
+    jobs: &JobManager,
+    templates: &[Template],
+) -> Result<ExportResult, EngineError> {
+    ops::check_templates(templates)?;
+    let guard = jobs.start("export-templates");
+    backend.export_templates(guard.job(), templates).await
+}
+
+pub async fn validate_license(
+    backend: &dyn EngineBackend,
+    jobs: &JobManager,
+    license_key: &str,
+) -> Result<LicenseResult, EngineError> {
+    ops::check_license_key(license_key)?;
+    let guard = jobs.start("validate-license");
+    backend.validate_license(guard.job(), license_key).await
+}
+
+/// Flag job `id` as cancelled and interrupt it on the backend.
+pub fn cancel_job(
+    backend: &dyn EngineBackend,
+    jobs: &JobManager,
+    id: JobId,
+) -> Result<CancelReport, EngineError> {
+    let job = jobs.cancel(id).ok_or(EngineError::NoSuchJob { id })?;
+    backend.interrupt(id);
+
+    Ok(CancelReport {
+        id,
+        processed: job.processed(),
+    })
+}
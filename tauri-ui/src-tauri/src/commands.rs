@@ -0,0 +1,208 @@
+//! One typed Tauri command per engine operation.
+//!
+//! Each command forwards to `bridge`, which validates parameters and runs
+//! the operation on the managed `EngineBackend`.
+
+use tauri::{AppHandle, Emitter, State};
+
+use crate::bridge::{self, CancelReport, GenerateEvent};
+use crate::data::{DataWatcher, JoinResult, RolesReport, DATA_CHANGED_EVENT};
+use crate::engine::{
+    EngineBackend, EngineError, JobId, JobManager, GENERATE_COMPLETE_EVENT, GENERATE_PROGRESS_EVENT,
+};
+use crate::model::{
+    ColumnRoles, CsvLoadResult, DataLoadResult, Dataset, EmailPreview, ExportResult, FilterMatches,
+    GenerateRequest, GenerateResult, JoinRequest, LicenseResult, LintRequest, PreviewRequest,
+    PreviewResult, ReadFilesResult, RenderRequest, SheetTab, Template, ValidateRequest,
+    ValidationReport,
+};
+use crate::template::lint::LintReport;
+
+/// The backend as managed Tauri state.
+pub type Backend = Box<dyn EngineBackend>;
+
+/// Runs off the main thread; large files take a moment to decode.
+#[tauri::command(async)]
+pub fn load_csv(path: String) -> Result<CsvLoadResult, EngineError> {
+    bridge::load_csv(&path)
+}
+
+/// Re-read `path` whenever it changes on disk and emit `data-changed` with
+/// the new rows and a diff against the previous load.
+#[tauri::command]
+pub fn watch_data_file(
+    app: AppHandle,
+    watcher: State<'_, DataWatcher>,
+    path: String,
+    roles: ColumnRoles,
+) -> Result<(), EngineError> {
+    bridge::watch_data_file(
+        &watcher,
+        &path,
+        roles,
+        Box::new(move |change| {
+            let _ = app.emit(DATA_CHANGED_EVENT, &change);
+        }),
+    )
+}
+
+#[tauri::command]
+pub fn unwatch_data_file(watcher: State<'_, DataWatcher>) {
+    watcher.unwatch();
+}
+
+#[tauri::command(async)]
+pub fn list_sheets(path: String) -> Result<Vec<String>, EngineError> {
+    bridge::list_sheets(&path)
+}
+
+#[tauri::command(async)]
+pub fn load_workbook(path: String, sheet: Option<String>) -> Result<DataLoadResult, EngineError> {
+    bridge::load_workbook(&path, sheet.as_deref())
+}
+
+#[tauri::command]
+pub fn suggest_column_roles(headers: Vec<String>) -> ColumnRoles {
+    bridge::suggest_column_roles(&headers)
+}
+
+#[tauri::command]
+pub fn validate_column_roles(roles: ColumnRoles, headers: Vec<String>) -> RolesReport {
+    bridge::validate_column_roles(&roles, &headers)
+}
+
+/// Runs off the main thread; joining large sources takes a moment.
+#[tauri::command(async)]
+pub fn join_sources(request: JoinRequest) -> Result<JoinResult, EngineError> {
+    bridge::join_sources(&request)
+}
+
+#[tauri::command]
+pub fn filter_rows(
+    filter: String,
+    data: Dataset,
+    roles: ColumnRoles,
+) -> Result<FilterMatches, EngineError> {
+    bridge::filter_rows(&filter, &data, &roles)
+}
+
+/// Runs off the main thread; counting empty placeholders renders every
+/// row.
+#[tauri::command(async)]
+pub fn lint_templates(request: LintRequest) -> Result<LintReport, EngineError> {
+    bridge::lint_templates(&request)
+}
+
+#[tauri::command]
+pub async fn load_sheet(
+    backend: State<'_, Backend>,
+    jobs: State<'_, JobManager>,
+    url: String,
+    tabs: Option<Vec<String>>,
+) -> Result<DataLoadResult, EngineError> {
+    let tabs = tabs.unwrap_or_default();
+    bridge::load_sheet(backend.as_ref(), &jobs, &url, &tabs).await
+}
+
+#[tauri::command]
+pub async fn list_sheet_tabs(
+    backend: State<'_, Backend>,
+    jobs: State<'_, JobManager>,
+    url: String,
+) -> Result<Vec<SheetTab>, EngineError> {
+    bridge::list_sheet_tabs(backend.as_ref(), &jobs, &url).await
+}
+
+#[tauri::command]
+pub async fn preview(
+    backend: State<'_, Backend>,
+    jobs: State<'_, JobManager>,
+    request: PreviewRequest,
+) -> Result<PreviewResult, EngineError> {
+    bridge::preview(backend.as_ref(), &jobs, &request).await
+}
+
+#[tauri::command]
+pub async fn render_preview(
+    backend: State<'_, Backend>,
+    jobs: State<'_, JobManager>,
+    request: RenderRequest,
+    email: String,
+) -> Result<EmailPreview, EngineError> {
+    bridge::render_preview(backend.as_ref(), &jobs, &request, &email).await
+}
+
+#[tauri::command]
+pub async fn render_previews(
+    backend: State<'_, Backend>,
+    jobs: State<'_, JobManager>,
+    request: RenderRequest,
+    emails: Vec<String>,
+) -> Result<Vec<EmailPreview>, EngineError> {
+    bridge::render_previews(backend.as_ref(), &jobs, &request, &emails).await
+}
+
+#[tauri::command]
+pub async fn validate_dataset(
+    backend: State<'_, Backend>,
+    jobs: State<'_, JobManager>,
+    request: ValidateRequest,
+) -> Result<ValidationReport, EngineError> {
+    bridge::validate_dataset(backend.as_ref(), &jobs, &request).await
+}
+
+/// Generate drafts, emitting per-recipient progress as `generate-progress`
+/// events and a final `generate-complete` summary so the UI can show a
+/// live progress bar.
+#[tauri::command]
+pub async fn generate(
+    app: AppHandle,
+    backend: State<'_, Backend>,
+    jobs: State<'_, JobManager>,
+    request: GenerateRequest,
+) -> Result<GenerateResult, EngineError> {
+    bridge::generate(backend.as_ref(), &jobs, &request, &mut |event| {
+        let _ = match event {
+            GenerateEvent::Progress(progress) => app.emit(GENERATE_PROGRESS_EVENT, progress),
+            GenerateEvent::Complete(summary) => app.emit(GENERATE_COMPLETE_EVENT, summary),
+        };
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn read_files(
+    backend: State<'_, Backend>,
+    jobs: State<'_, JobManager>,
+    paths: Vec<String>,
+) -> Result<ReadFilesResult, EngineError> {
+    bridge::read_files(backend.as_ref(), &jobs, &paths).await
+}
+
+#[tauri::command]
+pub async fn export_templates(
+    backend: State<'_, Backend>,
+    jobs: State<'_, JobManager>,
+    templates: Vec<Template>,
+) -> Result<ExportResult, EngineError> {
+    bridge::export_templates(backend.as_ref(), &jobs, &templates).await
+}
+
+#[tauri::command]
+pub async fn validate_license(
+    backend: State<'_, Backend>,
+    jobs: State<'_, JobManager>,
+    license_key: String,
+) -> Result<LicenseResult, EngineError> {
+    bridge::validate_license(backend.as_ref(), &jobs, &license_key).await
+}
+
+/// Stop a running engine job.
+#[tauri::command]
+pub fn cancel_job(
+    backend: State<'_, Backend>,
+    jobs: State<'_, JobManager>,
+    id: JobId,
+) -> Result<CancelReport, EngineError> {
+    bridge::cancel_job(backend.as_ref(), &jobs, id)
+}
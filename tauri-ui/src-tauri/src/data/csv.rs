@@ -0,0 +1,156 @@
+//! CSV files as Excel and friends actually write them: any of UTF-8,
+//! UTF-16 or Windows-1252, separated by commas, semicolons, tabs or pipes.
+
+use std::fs;
+use std::path::Path;
+
+use ::csv::ReaderBuilder;
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+
+use super::normalize;
+use crate::engine::EngineError;
+use crate::model::CsvLoadResult;
+
+/// Delimiters tried by `sniff_delimiter`, in order of preference on a tie.
+pub const DELIMITERS: &[u8] = b",;\t|";
+
+/// Lines inspected when sniffing the delimiter.
+const SNIFF_LINES: usize = 20;
+
+/// Bytes inspected when guessing a BOM-less UTF-16 file.
+const SNIFF_BYTES: usize = 4096;
+
+pub fn load_csv(path: &Path) -> Result<CsvLoadResult, EngineError> {
+    let bytes = fs::read(path)
+        .map_err(|e| EngineError::io(format!("Failed to read {}: {}", path.display(), e)))?;
+    parse_csv(&bytes)
+}
+
+/// Decode `bytes`, sniff the delimiter and normalize the records.
+pub fn parse_csv(bytes: &[u8]) -> Result<CsvLoadResult, EngineError> {
+    let (text, encoding) = decode(bytes);
+    let delimiter = sniff_delimiter(&text);
+
+    let mut reader = ReaderBuilder::new()
+        .delimiter(delimiter)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+
+    let mut records = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|e| EngineError::InvalidData {
+            message: e.to_string(),
+        })?;
+        records.push(record.iter().map(str::to_string).collect::<Vec<_>>());
+    }
+
+    let mut records = records.into_iter();
+    let header = records.next().unwrap_or_default();
+
+    Ok(CsvLoadResult {
+        data: normalize(&header, records),
+        encoding: encoding.name().to_string(),
+        delimiter: char::from(delimiter),
+    })
+}
+
+/// Decode a file's bytes, returning the text and the encoding used.
+///
+/// A byte order mark wins. Without one, NUL-heavy input is read as UTF-16,
+/// valid UTF-8 as UTF-8, and anything else as Windows-1252, which is what
+/// Excel on Windows writes for "CSV (Comma delimited)".
+pub fn decode(bytes: &[u8]) -> (String, &'static Encoding) {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(&bytes[bom_len..]);
+        return (text.into_owned(), encoding);
+    }
+
+    // Checked before UTF-8 because NUL bytes are valid UTF-8.
+    if let Some(encoding) = guess_utf16(bytes) {
+        let (text, _) = encoding.decode_without_bom_handling(bytes);
+        return (text.into_owned(), encoding);
+    }
+
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return (text.to_string(), UTF_8);
+    }
+
+    let (text, _) = WINDOWS_1252.decode_without_bom_handling(bytes);
+    (text.into_owned(), WINDOWS_1252)
+}
+
+/// Spot BOM-less UTF-16 by where the NUL bytes fall: mostly-ASCII text has
+/// a NUL in every other byte.
+fn guess_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    let sample = &bytes[..bytes.len().min(SNIFF_BYTES)];
+    let pairs = sample.len() / 2;
+    if pairs == 0 {
+        return None;
+    }
+
+    let even_nuls = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd_nuls = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|&&b| b == 0)
+        .count();
+
+    let mostly = |count: usize| count * 10 >= pairs * 4;
+    let rarely = |count: usize| count * 20 <= pairs;
+
+    if mostly(odd_nuls) && rarely(even_nuls) {
+        Some(UTF_16LE)
+    } else if mostly(even_nuls) && rarely(odd_nuls) {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Pick the delimiter that splits the first lines most consistently.
+///
+/// Each candidate is scored by how many of the sampled lines contain the
+/// same number of it (outside quotes) as the header line, then by that
+/// number. Candidates missing from the header are ignored. Falls back to a
+/// comma.
+pub fn sniff_delimiter(text: &str) -> u8 {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(SNIFF_LINES)
+        .collect();
+    let Some(header) = lines.first() else {
+        return b',';
+    };
+
+    let mut best = (b',', 0, 0);
+    for &delimiter in DELIMITERS {
+        let expected = count_unquoted(header, delimiter);
+        if expected == 0 {
+            continue;
+        }
+        let consistent = lines
+            .iter()
+            .filter(|line| count_unquoted(line, delimiter) == expected)
+            .count();
+        if (consistent, expected) > (best.1, best.2) {
+            best = (delimiter, consistent, expected);
+        }
+    }
+    best.0
+}
+
+fn count_unquoted(line: &str, delimiter: u8) -> usize {
+    let mut in_quotes = false;
+    let mut count = 0;
+    for byte in line.bytes() {
+        if byte == b'"' {
+            in_quotes = !in_quotes;
+        } else if byte == delimiter && !in_quotes {
+            count += 1;
+        }
+    }
+    count
+}
@@ -0,0 +1,54 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::model::Row;
+
+/// Recipients that differ between two loads of the same data, by email.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct DataDiff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    /// Present in both loads with at least one different cell.
+    pub changed: Vec<String>,
+}
+
+impl DataDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Compare two sets of rows by their (trimmed, lowercased) email, read
+/// from the `email_column` header.
+///
+/// Rows without an email are ignored. If an email appears more than once,
+/// its last row is the one compared. Each list is sorted.
+pub fn diff_rows(old: &[Row], new: &[Row], email_column: &str) -> DataDiff {
+    let old = by_email(old, email_column);
+    let new = by_email(new, email_column);
+
+    let mut diff = DataDiff::default();
+    for (email, row) in &new {
+        match old.get(email) {
+            None => diff.added.push(email.clone()),
+            Some(previous) if previous != row => diff.changed.push(email.clone()),
+            Some(_) => {}
+        }
+    }
+    diff.removed = old
+        .keys()
+        .filter(|email| !new.contains_key(*email))
+        .cloned()
+        .collect();
+    diff
+}
+
+fn by_email<'a>(rows: &'a [Row], email_column: &str) -> BTreeMap<String, &'a Row> {
+    rows.iter()
+        .filter_map(|row| {
+            let email = row.get(email_column)?.trim().to_lowercase();
+            (!email.is_empty()).then_some((email, row))
+        })
+        .collect()
+}
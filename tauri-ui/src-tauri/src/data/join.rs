@@ -0,0 +1,237 @@
+//! Combine several loaded sources into one, joined on a key column.
+//!
+//! The first source is the primary one and decides which rows exist; the
+//! others only add columns to primary rows with the same key. Keys compare
+//! trimmed and case-insensitively, so `Ada@X.com` joins `ada@x.com`.
+
+use std::collections::{BTreeMap, HashSet};
+
+use serde::Serialize;
+
+use super::roles::suggest_roles;
+use crate::engine::EngineError;
+use crate::model::{DataLoadResult, Dataset, HeaderMode, JoinRequest, Row};
+
+/// A row whose key is missing from one or more of the other sources.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct UnmatchedRow {
+    /// Source the row is from.
+    pub source: String,
+    /// 1-based position of the row in that source.
+    pub row: usize,
+    /// The row's key as written; empty if the key cell is blank.
+    pub key: String,
+    /// Sources with no row for the key. Rows from sources after the first
+    /// that are unmatched here are left out of the result.
+    pub missing_from: Vec<String>,
+}
+
+/// Two sources disagree about a merged cell.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct JoinConflict {
+    /// 1-based position of the joined row, which is its primary row.
+    pub row: usize,
+    pub key: String,
+    pub column: String,
+    pub kept: String,
+    pub kept_from: String,
+    pub ignored: String,
+    pub ignored_from: String,
+}
+
+/// The joined data, plus what didn't line up.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct JoinResult {
+    #[serde(flatten)]
+    pub data: DataLoadResult,
+    pub unmatched: Vec<UnmatchedRow>,
+    pub conflicts: Vec<JoinConflict>,
+}
+
+/// Left-join every source after the first onto the first.
+///
+/// Fails if there are no sources, two sources share a name, or a source
+/// has no key column.
+pub fn join_sources(request: &JoinRequest) -> Result<JoinResult, EngineError> {
+    let Some((primary, others)) = request.sources.split_first() else {
+        return Err(invalid("No sources to join"));
+    };
+
+    let mut names = HashSet::new();
+    for source in &request.sources {
+        if !names.insert(source.name.trim().to_lowercase()) {
+            return Err(invalid(format!(
+                "Two sources are named '{}'",
+                source.name.trim()
+            )));
+        }
+    }
+
+    let key = request.key.trim().to_lowercase();
+    let primary_key = key_header(&primary.name, &primary.data, &key)?;
+
+    let mut headers = primary.data.headers.clone();
+    let mut joined = Vec::with_capacity(others.len());
+    for source in others {
+        let source_key = key_header(&source.name, &source.data, &key)?;
+        let columns: Vec<(String, String)> = source
+            .data
+            .headers
+            .iter()
+            .filter(|header| **header != source_key)
+            .map(|header| {
+                let column = match request.headers {
+                    HeaderMode::Merge => header.clone(),
+                    HeaderMode::Prefix => {
+                        format!("{}.{}", source.name.trim().to_lowercase(), header)
+                    }
+                };
+                (header.clone(), column)
+            })
+            .collect();
+        for (_, column) in &columns {
+            if !headers.contains(column) {
+                headers.push(column.clone());
+            }
+        }
+        joined.push(Joined {
+            name: source.name.trim(),
+            rows: &source.data.rows,
+            key: source_key,
+            columns,
+            by_key: index_by_key(&source.data.rows, source_key),
+        });
+    }
+
+    let mut result = JoinResult::default();
+    let mut primary_keys = HashSet::new();
+
+    for (index, row) in primary.data.rows.iter().enumerate() {
+        let raw_key = row.get(primary_key).map(String::as_str).unwrap_or("");
+        let normalized = normalize(raw_key);
+        primary_keys.insert(normalized.clone());
+
+        let mut out: Row = headers
+            .iter()
+            .map(|header| (header.clone(), row.get(header).cloned().unwrap_or_default()))
+            .collect();
+        // Where each non-blank cell came from, for conflict reports.
+        let mut origin: BTreeMap<String, &str> = BTreeMap::new();
+        let mut missing_from = Vec::new();
+
+        for source in &joined {
+            let Some(matches) = source.by_key.get(&normalized) else {
+                missing_from.push(source.name.to_string());
+                continue;
+            };
+            for &other in matches {
+                for (header, column) in &source.columns {
+                    let value = source.rows[other].get(header).cloned().unwrap_or_default();
+                    let current = out.get(column).cloned().unwrap_or_default();
+                    if current.trim().is_empty() {
+                        out.insert(column.clone(), value);
+                        origin.insert(column.clone(), source.name);
+                    } else if !value.trim().is_empty() && !value.eq_ignore_ascii_case(&current) {
+                        result.conflicts.push(JoinConflict {
+                            row: index + 1,
+                            key: raw_key.to_string(),
+                            column: column.clone(),
+                            kept: current,
+                            kept_from: origin
+                                .get(column)
+                                .copied()
+                                .unwrap_or(primary.name.trim())
+                                .to_string(),
+                            ignored: value,
+                            ignored_from: source.name.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+
+        if !missing_from.is_empty() {
+            result.unmatched.push(UnmatchedRow {
+                source: primary.name.trim().to_string(),
+                row: index + 1,
+                key: raw_key.to_string(),
+                missing_from,
+            });
+        }
+        result.data.rows.push(out);
+    }
+
+    for source in &joined {
+        for (index, row) in source.rows.iter().enumerate() {
+            let raw_key = row.get(source.key).map(String::as_str).unwrap_or("");
+            let normalized = normalize(raw_key);
+            if normalized.is_empty() || !primary_keys.contains(&normalized) {
+                result.unmatched.push(UnmatchedRow {
+                    source: source.name.to_string(),
+                    row: index + 1,
+                    key: raw_key.to_string(),
+                    missing_from: vec![primary.name.trim().to_string()],
+                });
+            }
+        }
+    }
+
+    result.data.count = result.data.rows.len();
+    result.data.headers = headers;
+    Ok(result)
+}
+
+/// A source after the first, ready to be looked up by key.
+struct Joined<'a> {
+    name: &'a str,
+    rows: &'a [Row],
+    key: &'a str,
+    /// Each non-key header and the column it becomes in the result.
+    columns: Vec<(String, String)>,
+    /// Normalized key to the indices of the rows with it, in order.
+    by_key: BTreeMap<String, Vec<usize>>,
+}
+
+/// The header `key` names in `data`: the header itself, or else the
+/// header the column role of that name would be guessed as.
+fn key_header<'a>(name: &str, data: &'a Dataset, key: &str) -> Result<&'a str, EngineError> {
+    if let Some(header) = data.headers.iter().find(|header| *header == key) {
+        return Ok(header);
+    }
+    let guessed = suggest_roles(&data.headers)
+        .entries()
+        .into_iter()
+        .find(|(role, _)| role.as_str() == key)
+        .and_then(|(_, header)| header.map(str::to_string));
+    guessed
+        .and_then(|guess| data.headers.iter().find(|header| **header == guess))
+        .map(String::as_str)
+        .ok_or_else(|| {
+            invalid(format!(
+                "Source '{}' has no '{}' column to join on",
+                name.trim(),
+                key
+            ))
+        })
+}
+
+fn index_by_key(rows: &[Row], key: &str) -> BTreeMap<String, Vec<usize>> {
+    let mut by_key: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (index, row) in rows.iter().enumerate() {
+        let normalized = normalize(row.get(key).map(String::as_str).unwrap_or(""));
+        if !normalized.is_empty() {
+            by_key.entry(normalized).or_default().push(index);
+        }
+    }
+    by_key
+}
+
+fn normalize(key: &str) -> String {
+    key.trim().to_lowercase()
+}
+
+fn invalid(message: impl Into<String>) -> EngineError {
+    EngineError::InvalidArgument {
+        message: message.into(),
+    }
+}
@@ -0,0 +1,56 @@
+//! Native loaders for local data files.
+//!
+//! Every loader produces the same normalized shape as the engine's loaders:
+//! headers trimmed and lowercased, cell values trimmed, and one `Row` per
+//! record keyed by header.
+
+pub mod csv;
+pub mod diff;
+pub mod join;
+pub mod roles;
+pub mod watch;
+pub mod workbook;
+pub mod writeback;
+
+pub use self::csv::{decode, load_csv, parse_csv, sniff_delimiter};
+pub use self::diff::{diff_rows, DataDiff};
+pub use self::join::{join_sources, JoinConflict, JoinResult, UnmatchedRow};
+pub use self::roles::{suggest_roles, validate_roles, RolesReport};
+pub use self::watch::{DataChanged, DataWatcher, DATA_CHANGED_EVENT};
+pub use self::workbook::{is_workbook, list_sheets, load_sheet, WORKBOOK_EXTENSIONS};
+pub use self::writeback::{mark_drafted, write_back, Drafted};
+
+use crate::model::{DataLoadResult, Row};
+
+/// Normalize a header row and its records into a `DataLoadResult`.
+///
+/// Records shorter than the header get empty strings for the missing cells;
+/// cells beyond the last header are dropped. When two headers normalize to
+/// the same name, the later column wins.
+pub fn normalize<R, C>(header: &[String], records: R) -> DataLoadResult
+where
+    R: IntoIterator<Item = Vec<C>>,
+    C: AsRef<str>,
+{
+    let headers: Vec<String> = header.iter().map(|h| h.trim().to_lowercase()).collect();
+
+    let rows: Vec<Row> = records
+        .into_iter()
+        .map(|record| {
+            headers
+                .iter()
+                .enumerate()
+                .map(|(i, name)| {
+                    let value = record.get(i).map(|v| v.as_ref().trim()).unwrap_or("");
+                    (name.clone(), value.to_string())
+                })
+                .collect()
+        })
+        .collect();
+
+    DataLoadResult {
+        count: rows.len(),
+        rows,
+        headers,
+    }
+}
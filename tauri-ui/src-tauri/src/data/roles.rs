@@ -0,0 +1,137 @@
+//! Which header holds the email, name, firm and school of each recipient.
+//!
+//! `suggest_roles` mirrors the engine's `PlaceholderResolver` guesses, so a
+//! suggestion is exactly what the engine would pick with no mapping at all.
+
+use serde::Serialize;
+
+use crate::model::{ColumnRole, ColumnRoles};
+
+const NAME_HEADERS: &[&str] = &["full name", "name"];
+const FIRM_HEADERS: &[&str] = &["firm", "company", "firm name", "company name", "business"];
+const FIRM_SUBSTRINGS: &[&str] = &["company", "firm"];
+const SCHOOL_SUBSTRINGS: &[&str] = &["school", "college", "university", "uni"];
+const EMAIL_SUBSTRING: &str = "email";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoleProblem {
+    /// The mapped header is not in the loaded data; the engine will guess.
+    MissingHeader,
+    /// Another role is mapped to the same header.
+    SharedHeader,
+    /// Nothing is mapped and no header looks right.
+    Unresolved,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RoleIssue {
+    pub role: ColumnRole,
+    /// The mapped header, if the role is mapped.
+    pub header: Option<String>,
+    pub problem: RoleProblem,
+}
+
+/// Outcome of `validate_roles`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RolesReport {
+    /// The header each role will actually use: the mapping where it is
+    /// valid, the guess otherwise.
+    pub resolved: ColumnRoles,
+    pub issues: Vec<RoleIssue>,
+}
+
+impl RolesReport {
+    pub fn is_ok(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Guess each role from lowercased `headers`, preferring exact names.
+pub fn suggest_roles(headers: &[String]) -> ColumnRoles {
+    let firm = first_exact(headers, FIRM_HEADERS)
+        .or_else(|| first_containing(headers, FIRM_SUBSTRINGS, &[EMAIL_SUBSTRING]));
+    ColumnRoles {
+        email: first_containing(headers, &[EMAIL_SUBSTRING], &[]),
+        name: first_exact(headers, NAME_HEADERS),
+        firm,
+        school: first_containing(headers, SCHOOL_SUBSTRINGS, &[]),
+    }
+}
+
+/// Check `roles` against the loaded `headers`.
+///
+/// Only the email role is required: without it no recipient has an
+/// address. The other roles merely feed placeholders.
+pub fn validate_roles(roles: &ColumnRoles, headers: &[String]) -> RolesReport {
+    let suggested = suggest_roles(headers);
+    let mut report = RolesReport::default();
+
+    let mapped: Vec<(ColumnRole, String)> = roles
+        .entries()
+        .into_iter()
+        .filter_map(|(role, header)| {
+            let header = header?.trim().to_lowercase();
+            (!header.is_empty()).then_some((role, header))
+        })
+        .collect();
+
+    for (role, header) in &mapped {
+        if !headers.contains(header) {
+            report.issues.push(RoleIssue {
+                role: *role,
+                header: Some(header.clone()),
+                problem: RoleProblem::MissingHeader,
+            });
+        } else if mapped.iter().any(|(other, h)| other != role && h == header) {
+            report.issues.push(RoleIssue {
+                role: *role,
+                header: Some(header.clone()),
+                problem: RoleProblem::SharedHeader,
+            });
+        }
+    }
+
+    for (role, guess) in suggested.entries() {
+        let header = mapped
+            .iter()
+            .find(|(r, h)| *r == role && headers.contains(h))
+            .map(|(_, h)| h.clone())
+            .or_else(|| guess.map(str::to_string));
+        if role == ColumnRole::Email && header.is_none() {
+            report.issues.push(RoleIssue {
+                role,
+                header: None,
+                problem: RoleProblem::Unresolved,
+            });
+        }
+        *slot(&mut report.resolved, role) = header;
+    }
+
+    report
+}
+
+fn slot(roles: &mut ColumnRoles, role: ColumnRole) -> &mut Option<String> {
+    match role {
+        ColumnRole::Email => &mut roles.email,
+        ColumnRole::Name => &mut roles.name,
+        ColumnRole::Firm => &mut roles.firm,
+        ColumnRole::School => &mut roles.school,
+    }
+}
+
+fn first_exact(headers: &[String], candidates: &[&str]) -> Option<String> {
+    headers
+        .iter()
+        .find(|h| candidates.contains(&h.as_str()))
+        .cloned()
+}
+
+fn first_containing(headers: &[String], substrings: &[&str], exclude: &[&str]) -> Option<String> {
+    headers
+        .iter()
+        .find(|h| {
+            substrings.iter().any(|s| h.contains(s)) && !exclude.iter().any(|s| h.contains(s))
+        })
+        .cloned()
+}
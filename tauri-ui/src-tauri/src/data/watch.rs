@@ -0,0 +1,150 @@
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+
+use super::diff::{diff_rows, DataDiff};
+use super::{load_csv, validate_roles};
+use crate::engine::EngineError;
+use crate::model::{ColumnRoles, CsvLoadResult};
+
+/// Event emitted after a watched data file changes on disk.
+pub const DATA_CHANGED_EVENT: &str = "data-changed";
+
+/// How long the file must stay quiet before it is re-read. Excel saves by
+/// writing a temporary file and renaming it, which arrives as a burst.
+const SETTLE: Duration = Duration::from_millis(300);
+
+/// Payload of `data-changed`.
+#[derive(Debug, Clone, Serialize)]
+pub struct DataChanged {
+    pub path: PathBuf,
+    /// The file as it is now.
+    pub data: CsvLoadResult,
+    /// What changed since the previous load. Empty when the file has no
+    /// email column to tell recipients apart by.
+    pub diff: DataDiff,
+}
+
+/// Called on the watcher thread with each change.
+pub type OnDataChanged = Box<dyn Fn(DataChanged) + Send>;
+
+/// Watches at most one data file, re-parsing it whenever it changes.
+///
+/// Watching a new file replaces the previous watch.
+#[derive(Default)]
+pub struct DataWatcher {
+    /// Dropping the watcher closes its channel, which ends the reload thread.
+    active: Mutex<Option<RecommendedWatcher>>,
+}
+
+impl DataWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start watching `path`, reporting each change to `on_change`.
+    ///
+    /// The file is parsed once up front as the baseline for the first diff.
+    /// Rows are matched across loads by the email column `roles` resolves
+    /// to. The parent directory is watched rather than the file itself so
+    /// the watch survives editors that replace the file on save.
+    pub fn watch(
+        &self,
+        path: &Path,
+        roles: ColumnRoles,
+        on_change: OnDataChanged,
+    ) -> Result<(), EngineError> {
+        let path = path.to_path_buf();
+        let dir = path
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."))
+            .to_path_buf();
+        let baseline = load_csv(&path)?;
+
+        let (tx, rx) = mpsc::channel();
+        let target = path.clone();
+        let mut watcher =
+            notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+                let Ok(event) = event else {
+                    return;
+                };
+                if matches!(event.kind, EventKind::Access(_)) {
+                    return;
+                }
+                if event.paths.iter().any(|p| same_file_name(p, &target)) {
+                    let _ = tx.send(());
+                }
+            })
+            .map_err(watch_failed)?;
+        watcher
+            .watch(&dir, RecursiveMode::NonRecursive)
+            .map_err(watch_failed)?;
+
+        thread::spawn(move || reload_loop(path, roles, baseline, rx, on_change));
+
+        if let Ok(mut active) = self.active.lock() {
+            *active = Some(watcher);
+        }
+        Ok(())
+    }
+
+    /// Stop watching, if anything is being watched.
+    pub fn unwatch(&self) {
+        if let Ok(mut active) = self.active.lock() {
+            *active = None;
+        }
+    }
+}
+
+fn reload_loop(
+    path: PathBuf,
+    roles: ColumnRoles,
+    mut current: CsvLoadResult,
+    rx: mpsc::Receiver<()>,
+    on_change: OnDataChanged,
+) {
+    // Ends once the watcher, and with it the sender, is dropped.
+    while rx.recv().is_ok() {
+        loop {
+            match rx.recv_timeout(SETTLE) {
+                Ok(()) => continue,
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        // A failed parse usually means the file is mid-write; the write
+        // that completes it triggers another reload.
+        let Ok(data) = load_csv(&path) else {
+            continue;
+        };
+        if data == current {
+            continue;
+        }
+
+        let diff = match validate_roles(&roles, &data.data.headers).resolved.email {
+            Some(email) => diff_rows(&current.data.rows, &data.data.rows, &email),
+            None => DataDiff::default(),
+        };
+        current = data.clone();
+        on_change(DataChanged {
+            path: path.clone(),
+            data,
+            diff,
+        });
+    }
+}
+
+fn same_file_name(candidate: &Path, target: &Path) -> bool {
+    candidate.file_name().is_some() && candidate.file_name() == target.file_name()
+}
+
+fn watch_failed(error: notify::Error) -> EngineError {
+    EngineError::io(format!("Failed to watch data file: {}", error))
+}
@@ -0,0 +1,128 @@
+//! Excel (`.xlsx`, `.xlsm`, `.xlsb`, `.xls`) and OpenDocument (`.ods`) workbooks.
+
+use std::path::Path;
+
+use calamine::{open_workbook_auto, Data, ExcelDateTime, Reader, Sheets};
+
+use super::normalize;
+use crate::engine::EngineError;
+use crate::model::DataLoadResult;
+
+/// File extensions `open_workbook_auto` understands, lowercased.
+pub const WORKBOOK_EXTENSIONS: &[&str] = &["xlsx", "xlsm", "xlsb", "xls", "ods"];
+
+pub fn is_workbook(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| WORKBOOK_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+fn open(path: &Path) -> Result<Sheets<std::io::BufReader<std::fs::File>>, EngineError> {
+    open_workbook_auto(path).map_err(|e| EngineError::InvalidData {
+        message: format!("{}: {}", path.display(), e),
+    })
+}
+
+/// Sheet names in workbook order.
+pub fn list_sheets(path: &Path) -> Result<Vec<String>, EngineError> {
+    Ok(open(path)?.sheet_names())
+}
+
+/// Load one sheet, or the first sheet when `sheet` is `None`.
+///
+/// The first non-empty row is the header row. Rows with no values at all
+/// are skipped, like blank lines in a CSV.
+pub fn load_sheet(path: &Path, sheet: Option<&str>) -> Result<DataLoadResult, EngineError> {
+    let mut workbook = open(path)?;
+    let names = workbook.sheet_names();
+
+    let name = match sheet {
+        Some(wanted) => names
+            .iter()
+            .find(|name| name.as_str() == wanted)
+            .cloned()
+            .ok_or_else(|| EngineError::InvalidArgument {
+                message: format!("No sheet named '{}' (sheets: {})", wanted, names.join(", ")),
+            })?,
+        None => names
+            .first()
+            .cloned()
+            .ok_or_else(|| EngineError::InvalidData {
+                message: format!("{} has no sheets", path.display()),
+            })?,
+    };
+
+    let range = workbook
+        .worksheet_range(&name)
+        .map_err(|e| EngineError::InvalidData {
+            message: format!("{} / {}: {}", path.display(), name, e),
+        })?;
+
+    let mut rows = range
+        .rows()
+        .map(|row| row.iter().map(cell_to_string).collect::<Vec<_>>())
+        .filter(|row| row.iter().any(|cell| !cell.trim().is_empty()));
+    let header = rows.next().unwrap_or_default();
+
+    Ok(normalize(&header, rows))
+}
+
+/// Render a cell the way a user would type it back in.
+///
+/// Whole numbers lose their `.0` so phone numbers and ZIP codes survive;
+/// dates become `YYYY-MM-DD`, with ` HH:MM:SS` appended when there is a
+/// time part; booleans become `TRUE`/`FALSE` as Excel shows them; error
+/// cells such as `#N/A` become empty.
+pub fn cell_to_string(cell: &Data) -> String {
+    match cell {
+        Data::Empty | Data::Error(_) => String::new(),
+        Data::String(s) => s.clone(),
+        Data::Int(i) => i.to_string(),
+        Data::Float(f) => format_number(*f),
+        Data::Bool(true) => "TRUE".to_string(),
+        Data::Bool(false) => "FALSE".to_string(),
+        Data::DateTime(dt) if dt.is_duration() => format_duration(dt),
+        Data::DateTime(dt) => format_serial_date(dt),
+        Data::DateTimeIso(s) | Data::DurationIso(s) => s.replacen('T', " ", 1),
+    }
+}
+
+fn format_number(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Format an Excel serial date in the workbook's date system, 1900 or
+/// 1904; serials that aren't a valid date are shown as the number.
+pub fn format_serial_date(dt: &ExcelDateTime) -> String {
+    let Some(datetime) = dt.as_datetime() else {
+        return format_number(dt.as_f64());
+    };
+    let millis = datetime.and_utc().timestamp_millis().rem_euclid(86_400_000);
+    // Rounded to the second, but never past the end of the day.
+    let seconds = ((millis + 500) / 1000).min(86_399);
+    if seconds == 0 {
+        datetime.date().to_string()
+    } else {
+        format!("{} {}", datetime.date(), format_clock(seconds))
+    }
+}
+
+fn format_duration(dt: &ExcelDateTime) -> String {
+    match dt.as_duration() {
+        Some(duration) => format_clock((duration.num_milliseconds() + 500).div_euclid(1000)),
+        None => format_number(dt.as_f64()),
+    }
+}
+
+fn format_clock(seconds: i64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
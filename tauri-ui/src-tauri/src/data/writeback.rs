@@ -0,0 +1,321 @@
+//! Mark drafted recipients in the CSV they were loaded from.
+//!
+//! Only the cells that change are rewritten: every other byte of the file,
+//! including its encoding, byte order mark, delimiter, quoting and line
+//! endings, is kept as it was. Missing `Generate`, `Drafted At` and
+//! `Template` columns are appended after the last column.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE};
+
+use super::csv::{decode, sniff_delimiter};
+use super::roles::validate_roles;
+use crate::engine::EngineError;
+use crate::model::{ColumnRoles, WriteBackReport};
+
+/// Headers the engine reads as the generate flag, lowercased.
+const GENERATE_HEADERS: &[&str] = &["generate", "gen"];
+const GENERATE_HEADER: &str = "Generate";
+const DRAFTED_AT_HEADER: &str = "Drafted At";
+const TEMPLATE_HEADER: &str = "Template";
+
+/// A recipient a draft was created for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Drafted {
+    pub email: String,
+    /// Name of the template used.
+    pub template: String,
+}
+
+/// Rewrite the CSV at `path` so each `drafted` recipient's rows have
+/// `Generate` off, `Drafted At` set to `drafted_at` and `Template` set.
+///
+/// The original is first copied to `<path>.bak`. Rows are matched by the
+/// email column `roles` resolve to, ignoring case.
+pub fn write_back(
+    path: &Path,
+    drafted: &[Drafted],
+    drafted_at: &str,
+    roles: &ColumnRoles,
+) -> Result<WriteBackReport, EngineError> {
+    let bytes = fs::read(path)
+        .map_err(|e| EngineError::io(format!("Failed to read {}: {}", path.display(), e)))?;
+
+    let bom = Encoding::for_bom(&bytes).map_or(&[][..], |(_, len)| &bytes[..len]);
+    let (text, encoding) = decode(&bytes);
+    let (text, mut report) =
+        mark_drafted(&text, sniff_delimiter(&text), drafted, drafted_at, roles)?;
+
+    let backup = backup_path(path);
+    fs::copy(path, &backup)
+        .map_err(|e| EngineError::io(format!("Failed to back up {}: {}", path.display(), e)))?;
+
+    let mut out = bom.to_vec();
+    out.extend(encode(&text, encoding));
+    fs::write(path, out)
+        .map_err(|e| EngineError::io(format!("Failed to write {}: {}", path.display(), e)))?;
+
+    report.backup = backup.display().to_string();
+    Ok(report)
+}
+
+/// The text-level half of `write_back`, with `delimiter` already sniffed.
+///
+/// The returned report has no `backup`.
+pub fn mark_drafted(
+    text: &str,
+    delimiter: u8,
+    drafted: &[Drafted],
+    drafted_at: &str,
+    roles: &ColumnRoles,
+) -> Result<(String, WriteBackReport), EngineError> {
+    let delimiter = char::from(delimiter);
+    let mut records = split_records(text, delimiter);
+    let Some(header_index) = records.iter().position(|record| !record.is_blank()) else {
+        return Err(invalid("The file has no header row"));
+    };
+
+    let headers: Vec<String> = records[header_index]
+        .fields
+        .iter()
+        .map(|field| unquote(field).trim().to_lowercase())
+        .collect();
+    let email_header = validate_roles(roles, &headers)
+        .resolved
+        .email
+        .ok_or_else(|| invalid("The file has no email column"))?;
+    let email_column = headers.iter().position(|h| *h == email_header);
+    let email_column = email_column.ok_or_else(|| invalid("The file has no email column"))?;
+
+    let find = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+    let mut appended = Vec::new();
+    let mut column = |existing: Option<usize>, name: &'static str| {
+        existing.unwrap_or_else(|| {
+            appended.push(name);
+            headers.len() + appended.len() - 1
+        })
+    };
+    let generate_column = column(find(GENERATE_HEADERS), GENERATE_HEADER);
+    let drafted_at_column = column(find(&["drafted at"]), DRAFTED_AT_HEADER);
+    let template_column = column(find(&["template"]), TEMPLATE_HEADER);
+    let width = headers.len() + appended.len();
+
+    let mut pending: HashMap<String, &Drafted> = drafted
+        .iter()
+        .map(|d| (d.email.trim().to_lowercase(), d))
+        .collect();
+    let mut found = HashSet::new();
+    let mut report = WriteBackReport::default();
+
+    for (index, record) in records.iter_mut().enumerate() {
+        if index < header_index || record.is_blank() {
+            continue;
+        }
+        if index == header_index {
+            for name in &appended {
+                record.append(name);
+            }
+            continue;
+        }
+
+        let email = record
+            .fields
+            .get(email_column)
+            .map(|field| unquote(field).trim().to_lowercase())
+            .unwrap_or_default();
+        let recipient = pending.get(&email).copied();
+        if !appended.is_empty() {
+            record.pad_to(headers.len());
+            // Rows without a flag column were all eligible; keep them so.
+            for name in &appended {
+                let value = match (*name, recipient) {
+                    (GENERATE_HEADER, None) => "TRUE",
+                    _ => "",
+                };
+                record.append(value);
+            }
+        }
+        let Some(recipient) = recipient else {
+            continue;
+        };
+
+        record.pad_to(width);
+        let flag = unquote(&record.fields[generate_column]);
+        record.set(generate_column, &falsy_like(&flag));
+        record.set(drafted_at_column, drafted_at);
+        record.set(template_column, &recipient.template);
+        found.insert(email);
+        report.updated += 1;
+    }
+
+    pending.retain(|email, _| !found.contains(email));
+    report.missing = drafted
+        .iter()
+        .map(|d| d.email.trim().to_string())
+        .filter(|email| pending.contains_key(&email.to_lowercase()))
+        .collect();
+
+    let text = records.iter().map(RawRecord::render).collect();
+    Ok((text, report))
+}
+
+/// One line of the file, split into fields exactly as written.
+struct RawRecord {
+    /// Fields with their quotes and escapes; joined with the delimiter they
+    /// reproduce the line.
+    fields: Vec<String>,
+    /// `"\n"`, `"\r\n"`, `"\r"` or `""` at the end of the file.
+    terminator: String,
+    delimiter: char,
+}
+
+impl RawRecord {
+    fn is_blank(&self) -> bool {
+        self.fields.len() == 1 && self.fields[0].trim().is_empty()
+    }
+
+    /// Whether every field is quoted, so new fields should be too.
+    fn all_quoted(&self) -> bool {
+        self.fields.iter().all(|field| field.starts_with('"'))
+    }
+
+    fn pad_to(&mut self, width: usize) {
+        while self.fields.len() < width {
+            self.fields.push(String::new());
+        }
+    }
+
+    fn append(&mut self, value: &str) {
+        let always = self.all_quoted();
+        let field = self.quote(value, always);
+        self.fields.push(field);
+    }
+
+    /// Replace a field, quoting the new value if the old one was quoted.
+    fn set(&mut self, column: usize, value: &str) {
+        let was_quoted = self.fields[column].starts_with('"');
+        self.fields[column] = self.quote(value, was_quoted);
+    }
+
+    fn quote(&self, value: &str, always: bool) -> String {
+        let needs = value.contains(['"', '\n', '\r', self.delimiter]);
+        if always || needs {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+
+    fn render(&self) -> String {
+        let mut line = self.fields.join(&self.delimiter.to_string());
+        line.push_str(&self.terminator);
+        line
+    }
+}
+
+/// Split `text` into records and fields, honoring quotes, without
+/// unescaping anything.
+fn split_records(text: &str, delimiter: char) -> Vec<RawRecord> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                field.push(c);
+            }
+            c if in_quotes => field.push(c),
+            c if c == delimiter => fields.push(std::mem::take(&mut field)),
+            '\r' | '\n' => {
+                let mut terminator = c.to_string();
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    terminator.push(chars.next().unwrap_or('\n'));
+                }
+                fields.push(std::mem::take(&mut field));
+                records.push(RawRecord {
+                    fields: std::mem::take(&mut fields),
+                    terminator,
+                    delimiter,
+                });
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push(RawRecord {
+            fields,
+            terminator: String::new(),
+            delimiter,
+        });
+    }
+    records
+}
+
+/// A raw field's value: outer quotes removed and `""` unescaped.
+fn unquote(field: &str) -> String {
+    let trimmed = field.trim();
+    match trimmed
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+    {
+        Some(inner) => inner.replace("\"\"", "\""),
+        None => field.to_string(),
+    }
+}
+
+/// The "off" spelling matching how `current` spells "on": `1` becomes
+/// `0`, `Yes` becomes `No`, `TRUE` becomes `FALSE` and so on.
+fn falsy_like(current: &str) -> String {
+    let current = current.trim();
+    let off = match current.to_lowercase().as_str() {
+        "1" => return "0".into(),
+        "0" => return "0".into(),
+        "yes" | "no" => "no",
+        "y" | "n" => "n",
+        "true" | "false" => "false",
+        _ => return "FALSE".into(),
+    };
+    if current.chars().all(|c| c.is_uppercase()) {
+        off.to_uppercase()
+    } else if current.starts_with(char::is_uppercase) {
+        let mut chars = off.chars();
+        chars
+            .next()
+            .map(|first| first.to_uppercase().chain(chars).collect())
+            .unwrap_or_default()
+    } else {
+        off.to_string()
+    }
+}
+
+/// Encode `text` back into the file's encoding. `encoding_rs` only
+/// decodes UTF-16, so that is done by hand.
+fn encode(text: &str, encoding: &'static Encoding) -> Vec<u8> {
+    if encoding == UTF_16LE {
+        text.encode_utf16().flat_map(u16::to_le_bytes).collect()
+    } else if encoding == UTF_16BE {
+        text.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    } else {
+        encoding.encode(text).0.into_owned()
+    }
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+fn invalid(message: &str) -> EngineError {
+    EngineError::InvalidData {
+        message: message.to_string(),
+    }
+}
@@ -0,0 +1,206 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+
+use super::jobs::{Job, JobId};
+use super::{ops, EngineError, EngineWorker, GenerateProgress};
+use crate::model::{
+    DataLoadResult, ExportResult, GenerateRequest, GenerateResult, LicenseResult, PreviewRequest,
+    PreviewResult, ReadFilesResult, SheetTab, Template, ValidateRequest, ValidationReport,
+};
+use crate::settings::SettingsStore;
+
+/// A boxed `Send` future, so `EngineBackend` stays usable as a trait object.
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+/// Callback for per-recipient progress while `generate` runs.
+pub type ProgressFn<'a> = dyn FnMut(GenerateProgress) + Send + 'a;
+
+/// Whatever carries out engine operations for the typed commands.
+///
+/// Parameters arrive already validated. `job` is the tracked job for the
+/// call; implementations should stop early once it is cancelled or
+/// `interrupt`ed and report `EngineError::Cancelled`.
+pub trait EngineBackend: Send + Sync {
+    /// Load the URL's tab, or the named `tabs`: one by name or gid, or
+    /// several at once with each row's tab in a `source tab` column.
+    fn load_sheet<'a>(
+        &'a self,
+        job: &'a Job,
+        url: &'a str,
+        tabs: &'a [String],
+    ) -> BoxFuture<'a, Result<DataLoadResult, EngineError>>;
+
+    fn list_sheet_tabs<'a>(
+        &'a self,
+        job: &'a Job,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<SheetTab>, EngineError>>;
+
+    fn preview<'a>(
+        &'a self,
+        job: &'a Job,
+        request: &'a PreviewRequest,
+    ) -> BoxFuture<'a, Result<PreviewResult, EngineError>>;
+
+    /// Create drafts, handing each recipient's progress to `on_progress`
+    /// with its `job_id` filled in.
+    fn generate<'a>(
+        &'a self,
+        job: &'a Job,
+        request: &'a GenerateRequest,
+        on_progress: &'a mut ProgressFn<'_>,
+    ) -> BoxFuture<'a, Result<GenerateResult, EngineError>>;
+
+    fn validate_dataset<'a>(
+        &'a self,
+        job: &'a Job,
+        request: &'a ValidateRequest,
+    ) -> BoxFuture<'a, Result<ValidationReport, EngineError>>;
+
+    fn read_files<'a>(
+        &'a self,
+        job: &'a Job,
+        paths: &'a [String],
+    ) -> BoxFuture<'a, Result<ReadFilesResult, EngineError>>;
+
+    fn export_templates<'a>(
+        &'a self,
+        job: &'a Job,
+        templates: &'a [Template],
+    ) -> BoxFuture<'a, Result<ExportResult, EngineError>>;
+
+    fn validate_license<'a>(
+        &'a self,
+        job: &'a Job,
+        license_key: &'a str,
+    ) -> BoxFuture<'a, Result<LicenseResult, EngineError>>;
+
+    /// Stop `job` if it is currently running. Called after the job has
+    /// been flagged as cancelled.
+    fn interrupt(&self, job: JobId);
+}
+
+/// The real backend: the persistent `python -m engine serve` worker.
+pub struct SubprocessBackend {
+    worker: Arc<EngineWorker>,
+    settings: Arc<SettingsStore>,
+}
+
+impl SubprocessBackend {
+    pub fn new(worker: Arc<EngineWorker>, settings: Arc<SettingsStore>) -> Self {
+        Self { worker, settings }
+    }
+
+    /// Run prepared engine arguments (`args[0]` is the subcommand) and
+    /// unpack the envelope.
+    async fn run<T: DeserializeOwned>(
+        &self,
+        job: &Job,
+        args: Vec<String>,
+    ) -> Result<T, EngineError> {
+        let (command, rest) = args.split_first().expect("ops always produce a subcommand");
+        let value = self
+            .worker
+            .call(&self.settings.get(), job, command, rest)
+            .await?;
+        ops::unwrap_envelope(value)
+    }
+}
+
+impl EngineBackend for SubprocessBackend {
+    fn load_sheet<'a>(
+        &'a self,
+        job: &'a Job,
+        url: &'a str,
+        tabs: &'a [String],
+    ) -> BoxFuture<'a, Result<DataLoadResult, EngineError>> {
+        Box::pin(self.run(job, ops::load_sheet(url, tabs)))
+    }
+
+    fn list_sheet_tabs<'a>(
+        &'a self,
+        job: &'a Job,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<SheetTab>, EngineError>> {
+        Box::pin(self.run(job, ops::list_sheet_tabs(url)))
+    }
+
+    fn preview<'a>(
+        &'a self,
+        job: &'a Job,
+        request: &'a PreviewRequest,
+    ) -> BoxFuture<'a, Result<PreviewResult, EngineError>> {
+        Box::pin(async move { self.run(job, ops::preview(request)?).await })
+    }
+
+    fn generate<'a>(
+        &'a self,
+        job: &'a Job,
+        request: &'a GenerateRequest,
+        on_progress: &'a mut ProgressFn<'_>,
+    ) -> BoxFuture<'a, Result<GenerateResult, EngineError>> {
+        Box::pin(async move {
+            let args = ops::generate(request)?;
+            let value = self
+                .worker
+                .call_with_notifications(
+                    &self.settings.get(),
+                    job,
+                    "generate",
+                    &args[1..],
+                    &mut |method, params| {
+                        if method != "progress" {
+                            return;
+                        }
+                        if let Ok(mut progress) =
+                            serde_json::from_value::<GenerateProgress>(params.clone())
+                        {
+                            progress.job_id = job.id();
+                            on_progress(progress);
+                        }
+                    },
+                )
+                .await?;
+            ops::unwrap_envelope(value)
+        })
+    }
+
+    fn validate_dataset<'a>(
+        &'a self,
+        job: &'a Job,
+        request: &'a ValidateRequest,
+    ) -> BoxFuture<'a, Result<ValidationReport, EngineError>> {
+        Box::pin(async move { self.run(job, ops::validate_dataset(request)?).await })
+    }
+
+    fn read_files<'a>(
+        &'a self,
+        job: &'a Job,
+        paths: &'a [String],
+    ) -> BoxFuture<'a, Result<ReadFilesResult, EngineError>> {
+        Box::pin(self.run(job, ops::read_files(paths)))
+    }
+
+    fn export_templates<'a>(
+        &'a self,
+        job: &'a Job,
+        templates: &'a [Template],
+    ) -> BoxFuture<'a, Result<ExportResult, EngineError>> {
+        Box::pin(async move { self.run(job, ops::export_templates(templates)?).await })
+    }
+
+    fn validate_license<'a>(
+        &'a self,
+        job: &'a Job,
+        license_key: &'a str,
+    ) -> BoxFuture<'a, Result<LicenseResult, EngineError>> {
+        Box::pin(self.run(job, ops::validate_license(license_key)))
+    }
+
+    fn interrupt(&self, job: JobId) {
+        self.worker.interrupt(job);
+    }
+}
@@ -0,0 +1,222 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::{python_version, EngineError, EngineLaunch, PythonInfo};
+use crate::settings::Settings;
+
+/// Engine modules the bridge depends on, checked for importability.
+pub const ENGINE_MODULES: &[&str] = &["data_sources", "preview", "generator", "resolver"];
+
+/// How long the import probe may take before it is reported as hung.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Imports each engine module and certifi in one interpreter run and prints
+/// `{"modules": {name: error-or-null}, "certifi": {...}}`.
+const PROBE_SCRIPT: &str = r#"
+import importlib, json, sys
+modules = {}
+for name in sys.argv[1:]:
+    try:
+        importlib.import_module("engine." + name)
+        modules[name] = None
+    except Exception as e:
+        modules[name] = f"{type(e).__name__}: {e}"
+try:
+    import certifi
+    cert = {"installed": True, "version": getattr(certifi, "__version__", None), "error": None}
+except Exception as e:
+    cert = {"installed": False, "version": None, "error": f"{type(e).__name__}: {e}"}
+print(json.dumps({"modules": modules, "certifi": cert}))
+"#;
+
+/// Everything `diagnose` could find out about the engine setup.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsReport {
+    /// True when the engine, its interpreter and its modules are all usable.
+    /// Outlook support is informational and doesn't count towards this, since
+    /// everything but creating drafts works without it.
+    pub ok: bool,
+    pub engine_dir: Option<PathBuf>,
+    pub engine_dir_error: Option<EngineError>,
+    pub python: Option<PythonInfo>,
+    pub python_error: Option<EngineError>,
+    pub modules: Vec<ModuleCheck>,
+    pub certifi: CertifiCheck,
+    pub outlook: OutlookSupport,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModuleCheck {
+    pub name: String,
+    pub importable: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CertifiCheck {
+    pub installed: bool,
+    pub version: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Whether drafts can be created on this machine. The Outlook backend drives
+/// Classic Outlook through AppleScript, so it only works on macOS.
+#[derive(Debug, Clone, Serialize)]
+pub struct OutlookSupport {
+    pub platform: String,
+    pub supported: bool,
+    pub osascript_available: bool,
+    pub outlook_installed: bool,
+}
+
+#[derive(Deserialize)]
+struct ProbeOutput {
+    modules: std::collections::HashMap<String, Option<String>>,
+    certifi: CertifiCheck,
+}
+
+/// Gather a diagnostics report. Never fails; problems are recorded in the report.
+pub async fn diagnose(settings: &Settings, resource_dir: Option<&Path>) -> DiagnosticsReport {
+    let outlook = outlook_support();
+
+    let launch = match EngineLaunch::resolve(settings, resource_dir) {
+        Ok(launch) => launch,
+        Err(e) => {
+            return DiagnosticsReport {
+                ok: false,
+                engine_dir: None,
+                engine_dir_error: Some(e),
+                python: None,
+                python_error: None,
+                modules: Vec::new(),
+                certifi: CertifiCheck::default(),
+                outlook,
+            };
+        }
+    };
+
+    let (python, python_error) = match python_version(&launch.python.path).await {
+        Ok(version) => (
+            Some(PythonInfo {
+                path: launch.python.path.clone(),
+                source: launch.python.source,
+                version,
+            }),
+            None,
+        ),
+        Err(e) => (None, Some(e)),
+    };
+
+    let (modules, certifi) = if python.is_some() {
+        probe_imports(&launch).await
+    } else {
+        let skipped = "interpreter unavailable".to_string();
+        (
+            failed_modules(&skipped),
+            CertifiCheck {
+                error: Some(skipped),
+                ..CertifiCheck::default()
+            },
+        )
+    };
+
+    let ok = python.is_some()
+        && modules.iter().all(|m| m.importable)
+        && certifi.installed;
+
+    DiagnosticsReport {
+        ok,
+        engine_dir: Some(launch.engine_dir),
+        engine_dir_error: None,
+        python,
+        python_error,
+        modules,
+        certifi,
+        outlook,
+    }
+}
+
+async fn probe_imports(launch: &EngineLaunch) -> (Vec<ModuleCheck>, CertifiCheck) {
+    let run = tokio::process::Command::from(launch.command_with_script(PROBE_SCRIPT))
+        .args(ENGINE_MODULES)
+        .kill_on_drop(true)
+        .output();
+
+    let output = match tokio::time::timeout(PROBE_TIMEOUT, run).await {
+        Ok(Ok(output)) => output,
+        Ok(Err(e)) => return probe_failed(launch.spawn_failed(e).to_string()),
+        Err(_) => {
+            return probe_failed(format!(
+                "import check timed out after {}s",
+                PROBE_TIMEOUT.as_secs()
+            ))
+        }
+    };
+
+    let parsed: ProbeOutput = match serde_json::from_slice(&output.stdout) {
+        Ok(parsed) => parsed,
+        Err(_) => {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return probe_failed(format!("import check failed: {}", stderr));
+        }
+    };
+
+    let modules = ENGINE_MODULES
+        .iter()
+        .map(|name| {
+            let error = parsed
+                .modules
+                .get(*name)
+                .cloned()
+                .unwrap_or_else(|| Some("not checked".to_string()));
+            ModuleCheck {
+                name: name.to_string(),
+                importable: error.is_none(),
+                error,
+            }
+        })
+        .collect();
+
+    (modules, parsed.certifi)
+}
+
+fn probe_failed(error: String) -> (Vec<ModuleCheck>, CertifiCheck) {
+    (
+        failed_modules(&error),
+        CertifiCheck {
+            error: Some(error),
+            ..CertifiCheck::default()
+        },
+    )
+}
+
+fn failed_modules(error: &str) -> Vec<ModuleCheck> {
+    ENGINE_MODULES
+        .iter()
+        .map(|name| ModuleCheck {
+            name: name.to_string(),
+            importable: false,
+            error: Some(error.to_string()),
+        })
+        .collect()
+}
+
+fn outlook_support() -> OutlookSupport {
+    let platform = std::env::consts::OS.to_string();
+    let is_macos = cfg!(target_os = "macos");
+    let osascript_available = Path::new("/usr/bin/osascript").is_file();
+    let user_apps = std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Applications"));
+    let outlook_installed = [Some(PathBuf::from("/Applications")), user_apps]
+        .into_iter()
+        .flatten()
+        .any(|apps| apps.join("Microsoft Outlook.app").is_dir());
+
+    OutlookSupport {
+        platform,
+        supported: is_macos && osascript_available && outlook_installed,
+        osascript_available,
+        outlook_installed,
+    }
+}
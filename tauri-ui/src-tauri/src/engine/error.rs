@@ -0,0 +1,224 @@
+use std::fmt;
+
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use super::jobs::JobId;
+
+/// Every way an engine call can fail.
+///
+/// Serializes to `{"kind": "...", "message": "...", ...fields}` so the
+/// frontend can branch on `kind` and still show `message` as-is.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EngineError {
+    /// The engine directory could not be located. `tried` lists each
+    /// location that was checked, in order.
+    NotFound { message: String, tried: Vec<String> },
+    /// The Python process could not be started.
+    SpawnFailed { message: String },
+    /// The command ran longer than its configured timeout and was killed.
+    Timeout { command: String, seconds: u64 },
+    /// A one-shot engine process exited unsuccessfully.
+    NonZeroExit {
+        code: Option<i32>,
+        stderr: String,
+        stdout: String,
+    },
+    /// The engine wrote something that isn't valid JSON.
+    InvalidJson { message: String },
+    /// The engine wrote output that isn't valid UTF-8.
+    InvalidOutput { message: String },
+    /// The job was cancelled before it finished.
+    Cancelled { job_id: JobId, processed: usize },
+    /// The arguments don't fit on a command line.
+    ArgvTooLarge { size: usize, limit: usize },
+    /// Reading from or writing to the engine process failed.
+    Io { message: String },
+    /// The engine worker rejected or failed the request.
+    Rpc { code: i64, message: String },
+    /// `cancel_job` was given an id that isn't running.
+    NoSuchJob { id: JobId },
+    /// A command parameter failed validation before reaching the engine.
+    InvalidArgument { message: String },
+    /// The engine ran the command and reported a failure.
+    Engine { message: String },
+    /// A data file was read but its contents couldn't be parsed.
+    InvalidData { message: String },
+    /// A row filter failed to parse or names an unknown column. `column`
+    /// is the 1-based position in the filter.
+    InvalidFilter { message: String, column: usize },
+    /// A template or the subject line failed to parse. `template` is the
+    /// template's name, or `None` for the subject line.
+    InvalidTemplate {
+        template: Option<String>,
+        message: String,
+        line: usize,
+        column: usize,
+    },
+}
+
+impl EngineError {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::NotFound { .. } => "NotFound",
+            Self::SpawnFailed { .. } => "SpawnFailed",
+            Self::Timeout { .. } => "Timeout",
+            Self::NonZeroExit { .. } => "NonZeroExit",
+            Self::InvalidJson { .. } => "InvalidJson",
+            Self::InvalidOutput { .. } => "InvalidOutput",
+            Self::Cancelled { .. } => "Cancelled",
+            Self::ArgvTooLarge { .. } => "ArgvTooLarge",
+            Self::Io { .. } => "Io",
+            Self::Rpc { .. } => "Rpc",
+            Self::NoSuchJob { .. } => "NoSuchJob",
+            Self::InvalidArgument { .. } => "InvalidArgument",
+            Self::Engine { .. } => "Engine",
+            Self::InvalidData { .. } => "InvalidData",
+            Self::InvalidFilter { .. } => "InvalidFilter",
+            Self::InvalidTemplate { .. } => "InvalidTemplate",
+        }
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        Self::Io {
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound { message, tried } => {
+                f.write_str(message)?;
+                if !tried.is_empty() {
+                    write!(f, " Searched:\n  {}", tried.join("\n  "))?;
+                }
+                Ok(())
+            }
+            Self::SpawnFailed { message } => {
+                write!(f, "Failed to execute Python engine: {}", message)
+            }
+            Self::Timeout { command, seconds } => {
+                write!(
+                    f,
+                    "Engine command '{}' timed out after {}s",
+                    command, seconds
+                )
+            }
+            Self::NonZeroExit { stderr, stdout, .. } => {
+                write!(f, "Engine command failed: {}", stderr)?;
+                if !stdout.is_empty() {
+                    write!(f, "\nOutput: {}", stdout)?;
+                }
+                Ok(())
+            }
+            Self::InvalidJson { message } => write!(f, "Engine sent invalid JSON: {}", message),
+            Self::InvalidOutput { message } => write!(f, "Failed to parse stdout: {}", message),
+            Self::Cancelled { job_id, processed } => write!(
+                f,
+                "Job {} was cancelled after {} recipients were processed",
+                job_id, processed
+            ),
+            Self::ArgvTooLarge { size, limit } => write!(
+                f,
+                "The data or templates sent to the engine are too large to pass on the \
+                 command line ({} bytes, limit {}). Try fewer rows or shorter templates.",
+                size, limit
+            ),
+            Self::Io { message } => f.write_str(message),
+            Self::Rpc { message, .. } => write!(f, "Engine command failed: {}", message),
+            Self::NoSuchJob { id } => write!(f, "No running job with id {}", id),
+            Self::InvalidArgument { message } | Self::Engine { message } => f.write_str(message),
+            Self::InvalidData { message } => write!(f, "Failed to parse data: {}", message),
+            Self::InvalidFilter { message, column } => {
+                write!(f, "Invalid filter at column {}: {}", column, message)
+            }
+            Self::InvalidTemplate {
+                template,
+                message,
+                line,
+                column,
+            } => {
+                match template {
+                    Some(name) => write!(f, "Template '{}'", name)?,
+                    None => f.write_str("Subject line")?,
+                }
+                write!(f, ", line {}, column {}: {}", line, column, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+impl From<crate::filter::FilterError> for EngineError {
+    fn from(error: crate::filter::FilterError) -> Self {
+        Self::InvalidFilter {
+            message: error.message,
+            column: error.column,
+        }
+    }
+}
+
+impl Serialize for EngineError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("kind", self.kind())?;
+        map.serialize_entry("message", &self.to_string())?;
+
+        match self {
+            Self::NotFound { tried, .. } => {
+                map.serialize_entry("tried", tried)?;
+            }
+            Self::SpawnFailed { .. }
+            | Self::InvalidJson { .. }
+            | Self::InvalidOutput { .. }
+            | Self::Io { .. }
+            | Self::InvalidArgument { .. }
+            | Self::Engine { .. }
+            | Self::InvalidData { .. } => {}
+            Self::Timeout { command, seconds } => {
+                map.serialize_entry("command", command)?;
+                map.serialize_entry("seconds", seconds)?;
+            }
+            Self::NonZeroExit {
+                code,
+                stderr,
+                stdout,
+            } => {
+                map.serialize_entry("code", code)?;
+                map.serialize_entry("stderr", stderr)?;
+                map.serialize_entry("stdout", stdout)?;
+            }
+            Self::Cancelled { job_id, processed } => {
+                map.serialize_entry("job_id", job_id)?;
+                map.serialize_entry("processed", processed)?;
+            }
+            Self::ArgvTooLarge { size, limit } => {
+                map.serialize_entry("size", size)?;
+                map.serialize_entry("limit", limit)?;
+            }
+            Self::Rpc { code, .. } => {
+                map.serialize_entry("code", code)?;
+            }
+            Self::NoSuchJob { id } => {
+                map.serialize_entry("id", id)?;
+            }
+            Self::InvalidFilter { column, .. } => {
+                map.serialize_entry("column", column)?;
+            }
+            Self::InvalidTemplate {
+                template,
+                line,
+                column,
+                ..
+            } => {
+                map.serialize_entry("template", template)?;
+                map.serialize_entry("line", line)?;
+                map.serialize_entry("column", column)?;
+            }
+        }
+
+        map.end()
+    }
+}
@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+pub type JobId = u64;
+
+/// One engine invocation, from the moment it is queued until it returns.
+#[derive(Debug)]
+pub struct Job {
+    id: JobId,
+    command: String,
+    started_at_ms: u64,
+    cancelled: AtomicBool,
+    processed: AtomicUsize,
+}
+
+impl Job {
+    pub fn id(&self) -> JobId {
+        self.id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Recipients the engine has reported progress for so far.
+    pub fn processed(&self) -> usize {
+        self.processed.load(Ordering::SeqCst)
+    }
+
+    pub fn record_processed(&self) {
+        self.processed.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn info(&self) -> JobInfo {
+        JobInfo {
+            id: self.id,
+            command: self.command.clone(),
+            started_at_ms: self.started_at_ms,
+            processed: self.processed(),
+        }
+    }
+}
+
+/// Snapshot of a running job, as returned by `list_jobs`.
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: JobId,
+    pub command: String,
+    pub started_at_ms: u64,
+    pub processed: usize,
+}
+
+/// Tracks in-flight engine jobs so they can be listed and cancelled.
+#[derive(Debug, Default)]
+pub struct JobManager {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<JobId, Arc<Job>>>,
+}
+
+/// Keeps a job registered for as long as it is alive.
+pub struct JobGuard<'a> {
+    manager: &'a JobManager,
+    job: Arc<Job>,
+}
+
+impl JobGuard<'_> {
+    pub fn job(&self) -> &Arc<Job> {
+        &self.job
+    }
+}
+
+impl Drop for JobGuard<'_> {
+    fn drop(&mut self) {
+        if let Ok(mut jobs) = self.manager.jobs.lock() {
+            jobs.remove(&self.job.id);
+        }
+    }
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new job for `command`. It is unregistered when the guard drops.
+    pub fn start(&self, command: &str) -> JobGuard<'_> {
+        let started_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        let job = Arc::new(Job {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed) + 1,
+            command: command.to_string(),
+            started_at_ms,
+            cancelled: AtomicBool::new(false),
+            processed: AtomicUsize::new(0),
+        });
+
+        if let Ok(mut jobs) = self.jobs.lock() {
+            jobs.insert(job.id, Arc::clone(&job));
+        }
+
+        JobGuard { manager: self, job }
+    }
+
+    pub fn list(&self) -> Vec<JobInfo> {
+        let mut infos: Vec<JobInfo> = match self.jobs.lock() {
+            Ok(jobs) => jobs.values().map(|job| job.info()).collect(),
+            Err(_) => Vec::new(),
+        };
+        infos.sort_by_key(|info| info.id);
+        infos
+    }
+
+    /// Flag a job as cancelled and return it, or None if it isn't running.
+    pub fn cancel(&self, id: JobId) -> Option<Arc<Job>> {
+        let jobs = self.jobs.lock().ok()?;
+        let job = jobs.get(&id)?;
+        job.cancelled.store(true, Ordering::SeqCst);
+        Some(Arc::clone(job))
+    }
+}
@@ -0,0 +1,96 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use super::EngineError;
+
+/// Environment variable naming the directory that contains `engine/`.
+pub const ENGINE_DIR_ENV_VAR: &str = "DRAFTMATE_ENGINE_DIR";
+
+/// Find the directory containing the `engine` package, trying in order:
+///
+/// 1. `configured` (the `engine_dir` app setting)
+/// 2. the `DRAFTMATE_ENGINE_DIR` environment variable
+/// 3. the bundled resources directory, for packaged builds
+/// 4. the working directory and its parent, for `tauri dev`
+/// 5. the executable's ancestors, for dev builds run directly
+///
+/// On failure the error lists every location that was checked.
+pub fn find_engine_dir(
+    configured: Option<&Path>,
+    resource_dir: Option<&Path>,
+) -> Result<PathBuf, EngineError> {
+    let mut search = Search::default();
+
+    let configured = configured.filter(|p| !p.as_os_str().is_empty());
+    if let Some(found) = search.check_opt("settings engine_dir", configured) {
+        return Ok(found);
+    }
+
+    let from_env = env::var_os(ENGINE_DIR_ENV_VAR)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from);
+    if let Some(found) = search.check_opt(ENGINE_DIR_ENV_VAR, from_env.as_deref()) {
+        return Ok(found);
+    }
+
+    if let Some(found) = search.check_opt("bundled resources", resource_dir) {
+        return Ok(found);
+    }
+
+    if let Ok(cwd) = env::current_dir() {
+        if let Some(found) = search.check("working directory", &cwd) {
+            return Ok(found);
+        }
+        if let Some(parent) = cwd.parent() {
+            if let Some(found) = search.check("working directory parent", parent) {
+                return Ok(found);
+            }
+        }
+    }
+
+    // In dev: target/debug/draftmate -> go up to find project root
+    if let Ok(exe_path) = env::current_exe() {
+        for dir in exe_path.ancestors().skip(1).take(5) {
+            if let Some(found) = search.check("executable ancestor", dir) {
+                return Ok(found);
+            }
+        }
+    }
+
+    Err(EngineError::NotFound {
+        message: "Could not find engine directory. Make sure the 'engine' folder exists."
+            .to_string(),
+        tried: search.tried,
+    })
+}
+
+/// Records each location checked so a failed search can explain itself.
+#[derive(Default)]
+struct Search {
+    tried: Vec<String>,
+}
+
+impl Search {
+    fn check(&mut self, label: &str, dir: &Path) -> Option<PathBuf> {
+        if is_engine_root(dir) {
+            return Some(dir.to_path_buf());
+        }
+        self.tried.push(format!("{}: {}", label, dir.display()));
+        None
+    }
+
+    fn check_opt(&mut self, label: &str, dir: Option<&Path>) -> Option<PathBuf> {
+        match dir {
+            Some(dir) => self.check(label, dir),
+            None => {
+                self.tried.push(format!("{}: not set", label));
+                None
+            }
+        }
+    }
+}
+
+/// Whether `dir` contains a runnable `engine` package.
+fn is_engine_root(dir: &Path) -> bool {
+    dir.join("engine").join("__main__.py").is_file()
+}
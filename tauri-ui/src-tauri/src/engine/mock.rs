@@ -0,0 +1,296 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use super::backend::{BoxFuture, EngineBackend, ProgressFn};
+use super::jobs::{Job, JobId};
+use super::{EngineError, GenerateProgress};
+use crate::model::{
+    DataLoadResult, ExportResult, GenerateRequest, GenerateResult, LicenseResult, PreviewRequest,
+    PreviewResult, ReadFilesResult, SheetTab, Template, ValidateRequest, ValidationReport,
+};
+
+/// How often a hanging reply checks whether its job was cancelled.
+const CANCEL_POLL: Duration = Duration::from_millis(5);
+
+/// An in-memory `EngineBackend` that replays scripted replies, so the
+/// command layer can be tested without Python or Outlook.
+///
+/// Replies are queued per engine subcommand (`"load-sheet"`, `"generate"`,
+/// ...) and consumed in order. A call with nothing queued fails with
+/// `EngineError::Engine`. Every call is recorded for later inspection.
+#[derive(Default)]
+pub struct MockBackend {
+    replies: Mutex<HashMap<String, VecDeque<Reply>>>,
+    calls: Mutex<Vec<MockCall>>,
+    interrupted: Mutex<Vec<JobId>>,
+}
+
+/// One call the mock received.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockCall {
+    pub command: String,
+    pub job_id: JobId,
+    /// The call's parameters, serialized.
+    pub input: Value,
+}
+
+struct Reply {
+    /// `(email, status, template)` reported as progress before the outcome.
+    progress: Vec<(String, String, String)>,
+    outcome: Outcome,
+}
+
+enum Outcome {
+    Data(Value),
+    Fail(EngineError),
+    /// Wait until the job is cancelled, then report `Cancelled`.
+    UntilCancelled,
+}
+
+impl MockBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a successful reply to `command` carrying `data`.
+    pub fn reply(&self, command: &str, data: impl Serialize) -> &Self {
+        self.push(command, Vec::new(), Outcome::Data(to_value(data)))
+    }
+
+    /// Queue a reply that reports `progress` as `(email, status)` pairs
+    /// before succeeding with `data`.
+    pub fn reply_with_progress(
+        &self,
+        command: &str,
+        progress: &[(&str, &str)],
+        data: impl Serialize,
+    ) -> &Self {
+        self.push(command, owned(progress), Outcome::Data(to_value(data)))
+    }
+
+    /// Like `reply_with_progress`, with each recipient's template name as a
+    /// third element.
+    pub fn reply_with_templates(
+        &self,
+        command: &str,
+        progress: &[(&str, &str, &str)],
+        data: impl Serialize,
+    ) -> &Self {
+        let progress = progress
+            .iter()
+            .map(|(email, status, template)| {
+                (email.to_string(), status.to_string(), template.to_string())
+            })
+            .collect();
+        self.push(command, progress, Outcome::Data(to_value(data)))
+    }
+
+    /// Queue a failed reply to `command`.
+    pub fn fail(&self, command: &str, error: EngineError) -> &Self {
+        self.push(command, Vec::new(), Outcome::Fail(error))
+    }
+
+    /// Queue a reply that reports `progress`, then blocks until its job is
+    /// cancelled.
+    pub fn hang(&self, command: &str, progress: &[(&str, &str)]) -> &Self {
+        self.push(command, owned(progress), Outcome::UntilCancelled)
+    }
+
+    /// Every call received so far, oldest first.
+    pub fn calls(&self) -> Vec<MockCall> {
+        self.calls.lock().map(|c| c.clone()).unwrap_or_default()
+    }
+
+    /// Jobs passed to `interrupt`, oldest first.
+    pub fn interrupted(&self) -> Vec<JobId> {
+        self.interrupted
+            .lock()
+            .map(|i| i.clone())
+            .unwrap_or_default()
+    }
+
+    fn push(
+        &self,
+        command: &str,
+        progress: Vec<(String, String, String)>,
+        outcome: Outcome,
+    ) -> &Self {
+        if let Ok(mut replies) = self.replies.lock() {
+            replies
+                .entry(command.to_string())
+                .or_default()
+                .push_back(Reply { progress, outcome });
+        }
+        self
+    }
+
+    fn next_reply(&self, command: &str) -> Option<Reply> {
+        self.replies
+            .lock()
+            .ok()?
+            .get_mut(command)
+            .and_then(VecDeque::pop_front)
+    }
+
+    async fn respond<T: DeserializeOwned>(
+        &self,
+        job: &Job,
+        command: &str,
+        input: Value,
+        mut on_progress: Option<&mut ProgressFn<'_>>,
+    ) -> Result<T, EngineError> {
+        if let Ok(mut calls) = self.calls.lock() {
+            calls.push(MockCall {
+                command: command.to_string(),
+                job_id: job.id(),
+                input,
+            });
+        }
+
+        let reply = self
+            .next_reply(command)
+            .ok_or_else(|| EngineError::Engine {
+                message: format!("No scripted reply for {}", command),
+            })?;
+
+        let total = reply.progress.len();
+        for (index, (email, status, template)) in reply.progress.into_iter().enumerate() {
+            if job.is_cancelled() {
+                return Err(cancelled(job));
+            }
+            job.record_processed();
+            if let Some(on_progress) = on_progress.as_mut() {
+                on_progress(GenerateProgress {
+                    job_id: job.id(),
+                    index,
+                    total,
+                    email,
+                    status,
+                    template,
+                });
+            }
+        }
+
+        match reply.outcome {
+            Outcome::Data(data) => {
+                serde_json::from_value(data).map_err(|e| EngineError::InvalidJson {
+                    message: e.to_string(),
+                })
+            }
+            Outcome::Fail(error) => Err(error),
+            Outcome::UntilCancelled => {
+                while !job.is_cancelled() {
+                    tokio::time::sleep(CANCEL_POLL).await;
+                }
+                Err(cancelled(job))
+            }
+        }
+    }
+}
+
+impl EngineBackend for MockBackend {
+    fn load_sheet<'a>(
+        &'a self,
+        job: &'a Job,
+        url: &'a str,
+        tabs: &'a [String],
+    ) -> BoxFuture<'a, Result<DataLoadResult, EngineError>> {
+        Box::pin(self.respond(job, "load-sheet", json!({ "url": url, "tabs": tabs }), None))
+    }
+
+    fn list_sheet_tabs<'a>(
+        &'a self,
+        job: &'a Job,
+        url: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<SheetTab>, EngineError>> {
+        Box::pin(self.respond(job, "list-sheet-tabs", json!({ "url": url }), None))
+    }
+
+    fn preview<'a>(
+        &'a self,
+        job: &'a Job,
+        request: &'a PreviewRequest,
+    ) -> BoxFuture<'a, Result<PreviewResult, EngineError>> {
+        Box::pin(self.respond(job, "preview", to_value(request), None))
+    }
+
+    fn generate<'a>(
+        &'a self,
+        job: &'a Job,
+        request: &'a GenerateRequest,
+        on_progress: &'a mut ProgressFn<'_>,
+    ) -> BoxFuture<'a, Result<GenerateResult, EngineError>> {
+        Box::pin(self.respond(job, "generate", to_value(request), Some(on_progress)))
+    }
+
+    fn validate_dataset<'a>(
+        &'a self,
+        job: &'a Job,
+        request: &'a ValidateRequest,
+    ) -> BoxFuture<'a, Result<ValidationReport, EngineError>> {
+        Box::pin(self.respond(job, "validate", to_value(request), None))
+    }
+
+    fn read_files<'a>(
+        &'a self,
+        job: &'a Job,
+        paths: &'a [String],
+    ) -> BoxFuture<'a, Result<ReadFilesResult, EngineError>> {
+        Box::pin(self.respond(job, "read-files", json!({ "paths": paths }), None))
+    }
+
+    fn export_templates<'a>(
+        &'a self,
+        job: &'a Job,
+        templates: &'a [Template],
+    ) -> BoxFuture<'a, Result<ExportResult, EngineError>> {
+        Box::pin(self.respond(
+            job,
+            "export-templates",
+            json!({ "templates": templates }),
+            None,
+        ))
+    }
+
+    fn validate_license<'a>(
+        &'a self,
+        job: &'a Job,
+        license_key: &'a str,
+    ) -> BoxFuture<'a, Result<LicenseResult, EngineError>> {
+        Box::pin(self.respond(
+            job,
+            "validate-license",
+            json!({ "license_key": license_key }),
+            None,
+        ))
+    }
+
+    fn interrupt(&self, job: JobId) {
+        if let Ok(mut interrupted) = self.interrupted.lock() {
+            interrupted.push(job);
+        }
+    }
+}
+
+fn to_value(value: impl Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn owned(progress: &[(&str, &str)]) -> Vec<(String, String, String)> {
+    progress
+        .iter()
+        .map(|(email, status)| (email.to_string(), status.to_string(), String::new()))
+        .collect()
+}
+
+fn cancelled(job: &Job) -> EngineError {
+    EngineError::Cancelled {
+        job_id: job.id(),
+        processed: job.processed(),
+    }
+}
@@ -0,0 +1,78 @@
+//! Locating and talking to the Python engine.
+
+mod backend;
+mod diagnose;
+mod error;
+mod jobs;
+mod locate;
+#[cfg(any(test, feature = "mock"))]
+mod mock;
+#[cfg(feature = "debug-passthrough")]
+mod oneshot;
+pub mod ops;
+mod progress;
+mod python;
+mod worker;
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::settings::Settings;
+
+pub use backend::{BoxFuture, EngineBackend, ProgressFn, SubprocessBackend};
+pub use diagnose::{
+    diagnose, CertifiCheck, DiagnosticsReport, ModuleCheck, OutlookSupport, ENGINE_MODULES,
+};
+pub use error::EngineError;
+pub use jobs::{JobId, JobInfo, JobManager};
+pub use locate::{find_engine_dir, ENGINE_DIR_ENV_VAR};
+#[cfg(any(test, feature = "mock"))]
+pub use mock::{MockBackend, MockCall};
+#[cfg(feature = "debug-passthrough")]
+pub use oneshot::{run_once, run_once_stdin};
+pub use progress::{
+    GenerateProgress, GenerateSummary, GENERATE_COMPLETE_EVENT, GENERATE_PROGRESS_EVENT,
+};
+pub use python::{find_python, python_version, Interpreter, PythonInfo, PythonSource};
+pub use worker::EngineWorker;
+
+/// Where the engine lives and which interpreter runs it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EngineLaunch {
+    pub engine_dir: PathBuf,
+    pub python: Interpreter,
+}
+
+impl EngineLaunch {
+    /// `resource_dir` is the app's bundled resources directory, if any.
+    pub fn resolve(settings: &Settings, resource_dir: Option<&Path>) -> Result<Self, EngineError> {
+        let engine_dir = find_engine_dir(settings.engine_dir.as_deref(), resource_dir)?;
+        let python = find_python(settings.python_path.as_deref(), &engine_dir);
+        Ok(Self { engine_dir, python })
+    }
+
+    /// `<python> -m engine` rooted in the engine directory; callers add arguments.
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(&self.python.path);
+        command
+            .arg("-m")
+            .arg("engine")
+            .current_dir(&self.engine_dir);
+        command
+    }
+
+    /// `<python> -c <script>` rooted in the engine directory, so the script
+    /// can import the `engine` package.
+    pub fn command_with_script(&self, script: &str) -> Command {
+        let mut command = Command::new(&self.python.path);
+        command.arg("-c").arg(script).current_dir(&self.engine_dir);
+        command
+    }
+
+    /// Spawn error that names the interpreter we tried to run.
+    pub fn spawn_failed(&self, error: std::io::Error) -> EngineError {
+        EngineError::SpawnFailed {
+            message: format!("{}: {}", self.python.path.display(), error),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::path::Path;
+use std::process::{Output, Stdio};
+use std::time::Duration;
+
+use tokio::io::AsyncWriteExt;
+
+use super::{EngineError, EngineLaunch};
+use crate::settings::Settings;
+
+/// Conservative ceiling for the arguments handed to a one-shot engine process.
+/// Linux rejects any single argument over 128 KiB and macOS caps the whole
+/// argv plus environment at 1 MiB, so stay under the smaller of the two.
+pub const MAX_ARGV_BYTES: usize = 128 * 1024;
+
+/// Bytes the arguments occupy on the command line, including terminators.
+pub fn argv_size(args: &[String]) -> usize {
+    args.iter().map(|a| a.len() + 1).sum()
+}
+
+/// Run `python -m engine <args>` in a fresh process and return its stdout.
+/// The process is killed if it runs longer than the command's timeout.
+pub async fn run_once(
+    settings: &Settings,
+    resource_dir: Option<&Path>,
+    args: &[String],
+) -> Result<String, EngineError> {
+    let size = argv_size(args);
+    if size > MAX_ARGV_BYTES {
+        return Err(EngineError::ArgvTooLarge {
+            size,
+            limit: MAX_ARGV_BYTES,
+        });
+    }
+
+    let launch = EngineLaunch::resolve(settings, resource_dir)?;
+    let timeout = settings.timeout_for(command_name(args));
+
+    let child = tokio::process::Command::from(launch.command())
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| launch.spawn_failed(e))?;
+
+    let output = with_timeout(command_name(args), timeout, child.wait_with_output()).await?;
+    collect_output(output)
+}
+
+/// Run a fresh engine process that reads its argument list from stdin as a
+/// JSON array, so payload size is not bounded by the OS argv limit.
+pub async fn run_once_stdin(
+    settings: &Settings,
+    resource_dir: Option<&Path>,
+    args: &[String],
+) -> Result<String, EngineError> {
+    let payload = serde_json::to_vec(args).map_err(|e| EngineError::io(e.to_string()))?;
+    let launch = EngineLaunch::resolve(settings, resource_dir)?;
+    let timeout = settings.timeout_for(command_name(args));
+
+    let mut child = tokio::process::Command::from(launch.command())
+        .arg("--args-stdin")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| launch.spawn_failed(e))?;
+
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| EngineError::io("Engine process has no stdin"))?;
+
+    // Feed stdin alongside collecting output so a large payload can't
+    // deadlock against the engine filling its stdout pipe.
+    let write = async move {
+        stdin.write_all(&payload).await?;
+        stdin.shutdown().await
+    };
+    let run = async {
+        let (written, output) = tokio::join!(write, child.wait_with_output());
+        written.map_err(|e| {
+            std::io::Error::new(e.kind(), format!("failed to send arguments: {}", e))
+        })?;
+        output
+    };
+
+    let output = with_timeout(command_name(args), timeout, run).await?;
+    collect_output(output)
+}
+
+fn command_name(args: &[String]) -> &str {
+    args.first().map(String::as_str).unwrap_or("engine")
+}
+
+/// Await a child's output, giving up after `timeout`. The child is killed
+/// when its future is dropped, since it was spawned with `kill_on_drop`.
+async fn with_timeout(
+    command: &str,
+    timeout: Duration,
+    run: impl std::future::Future<Output = std::io::Result<Output>>,
+) -> Result<Output, EngineError> {
+    match tokio::time::timeout(timeout, run).await {
+        Ok(output) => output.map_err(|e| EngineError::io(format!("Engine process failed: {}", e))),
+        Err(_) => Err(EngineError::Timeout {
+            command: command.to_string(),
+            seconds: timeout.as_secs(),
+        }),
+    }
+}
+
+fn collect_output(output: Output) -> Result<String, EngineError> {
+    if output.status.success() {
+        String::from_utf8(output.stdout).map_err(|e| EngineError::InvalidOutput {
+            message: e.to_string(),
+        })
+    } else {
+        Err(EngineError::NonZeroExit {
+            code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        })
+    }
+}
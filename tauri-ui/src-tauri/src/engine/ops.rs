@@ -0,0 +1,229 @@
+//! Parameter validation and argument building for each engine subcommand.
+//!
+//! The `check_*` functions run in the command layer, whichever backend is in
+//! use. The argument builders are only used by the subprocess backend; they
+//! pass every value as `--flag=value` or after a `--` separator so a
+//! user-supplied string starting with `-` can't be read as an option.
+
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::EngineError;
+use crate::data;
+use crate::model::{GenerateRequest, PreviewRequest, Template, ValidateRequest};
+
+const SHEETS_PREFIXES: &[&str] = &[
+    "https://docs.google.com/spreadsheets/d/",
+    "http://docs.google.com/spreadsheets/d/",
+];
+
+fn invalid(message: impl Into<String>) -> EngineError {
+    EngineError::InvalidArgument {
+        message: message.into(),
+    }
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, EngineError> {
+    serde_json::to_string(value).map_err(|e| invalid(e.to_string()))
+}
+
+fn require_file(path: &str, what: &str) -> Result<(), EngineError> {
+    if path.trim().is_empty() {
+        return Err(invalid(format!("No {} selected", what)));
+    }
+    if !Path::new(path).is_file() {
+        return Err(invalid(format!("{} not found: {}", what, path)));
+    }
+    Ok(())
+}
+
+fn require_templates(templates: &[Template]) -> Result<(), EngineError> {
+    if templates.is_empty() {
+        return Err(invalid("No templates provided"));
+    }
+    Ok(())
+}
+
+pub fn check_email(email: &str) -> Result<(), EngineError> {
+    if email.trim().is_empty() {
+        return Err(invalid("No email given"));
+    }
+    Ok(())
+}
+
+pub fn check_csv_path(path: &str) -> Result<(), EngineError> {
+    require_file(path, "CSV file")
+}
+
+pub fn check_workbook_path(path: &str) -> Result<(), EngineError> {
+    require_file(path, "Workbook")?;
+    if !data::is_workbook(Path::new(path)) {
+        return Err(invalid(format!(
+            "Unsupported workbook type (expected one of: {})",
+            data::WORKBOOK_EXTENSIONS.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+pub fn check_sheet_url(url: &str) -> Result<(), EngineError> {
+    let url = url.trim();
+    if !SHEETS_PREFIXES.iter().any(|prefix| url.starts_with(prefix)) {
+        return Err(invalid("Invalid Google Sheets URL"));
+    }
+    Ok(())
+}
+
+/// Tabs are picked by name or gid, so a blank one can't mean anything.
+pub fn check_sheet_tabs(tabs: &[String]) -> Result<(), EngineError> {
+    if tabs.iter().any(|tab| tab.trim().is_empty()) {
+        return Err(invalid("Tab names can't be blank"));
+    }
+    Ok(())
+}
+
+pub fn check_generate(request: &GenerateRequest) -> Result<(), EngineError> {
+    require_templates(&request.templates)?;
+    if let Some(resume) = request.resume_path.as_deref().filter(|p| !p.is_empty()) {
+        require_file(resume, "Resume file")?;
+    }
+    Ok(())
+}
+
+pub fn check_paths(paths: &[String]) -> Result<(), EngineError> {
+    if paths.is_empty() {
+        return Err(invalid("No files selected"));
+    }
+    for path in paths {
+        require_file(path, "File")?;
+    }
+    Ok(())
+}
+
+pub fn check_templates(templates: &[Template]) -> Result<(), EngineError> {
+    require_templates(templates)
+}
+
+pub fn check_license_key(license_key: &str) -> Result<(), EngineError> {
+    if license_key.trim().is_empty() {
+        return Err(invalid("No license key provided"));
+    }
+    Ok(())
+}
+
+pub fn load_sheet(url: &str, tabs: &[String]) -> Vec<String> {
+    let mut args = vec!["load-sheet".to_string()];
+    args.extend(tabs.iter().map(|tab| format!("--tab={}", tab.trim())));
+    args.extend(["--".into(), url.trim().into()]);
+    args
+}
+
+pub fn list_sheet_tabs(url: &str) -> Vec<String> {
+    vec!["list-sheet-tabs".into(), "--".into(), url.trim().into()]
+}
+
+pub fn preview(request: &PreviewRequest) -> Result<Vec<String>, EngineError> {
+    let mut args = vec![
+        "preview".to_string(),
+        format!("--data={}", to_json(&request.data)?),
+        format!("--templates={}", to_json(&request.templates)?),
+        format!("--overrides={}", to_json(&request.overrides)?),
+        format!("--roles={}", to_json(&request.roles)?),
+        format!("--dedup={}", request.dedup.as_str()),
+    ];
+    if !request.only_recipients {
+        args.push("--all-rows".into());
+    }
+    Ok(args)
+}
+
+pub fn generate(request: &GenerateRequest) -> Result<Vec<String>, EngineError> {
+    let mut args = vec![
+        "generate".to_string(),
+        format!("--data={}", to_json(&request.data)?),
+        format!("--templates={}", to_json(&request.templates)?),
+        format!("--overrides={}", to_json(&request.overrides)?),
+        format!("--subject={}", request.subject),
+        format!("--roles={}", to_json(&request.roles)?),
+        format!("--dedup={}", request.dedup.as_str()),
+    ];
+    if let Some(resume) = request.resume_path.as_deref().filter(|p| !p.is_empty()) {
+        args.push(format!("--resume={}", resume));
+    }
+    if request.dry_run {
+        args.push("--dry-run".into());
+    }
+    if !request.rendered.is_empty() {
+        args.push(format!("--rendered={}", to_json(&request.rendered)?));
+    }
+    Ok(args)
+}
+
+pub fn validate_dataset(request: &ValidateRequest) -> Result<Vec<String>, EngineError> {
+    let mut args = vec![
+        "validate".to_string(),
+        format!("--data={}", to_json(&request.data)?),
+        format!("--templates={}", to_json(&request.templates)?),
+        format!("--overrides={}", to_json(&request.overrides)?),
+        format!("--subject={}", request.subject),
+        format!("--roles={}", to_json(&request.roles)?),
+        format!("--dedup={}", request.dedup.as_str()),
+    ];
+    if !request.rendered.is_empty() {
+        args.push(format!("--rendered={}", to_json(&request.rendered)?));
+    }
+    Ok(args)
+}
+
+pub fn read_files(paths: &[String]) -> Vec<String> {
+    let mut args = vec!["read-files".to_string(), "--".to_string()];
+    args.extend(paths.iter().cloned());
+    args
+}
+
+pub fn export_templates(templates: &[Template]) -> Result<Vec<String>, EngineError> {
+    Ok(vec![
+        "export-templates".into(),
+        format!("--templates={}", to_json(&templates)?),
+    ])
+}
+
+pub fn validate_license(license_key: &str) -> Vec<String> {
+    vec![
+        "validate-license".into(),
+        "--".into(),
+        license_key.trim().into(),
+    ]
+}
+
+#[derive(Deserialize)]
+struct Envelope {
+    success: bool,
+    #[serde(default)]
+    data: Value,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+/// Unpack the engine's `{"success", "data", "error"}` response into `T`.
+pub fn unwrap_envelope<T: DeserializeOwned>(value: Value) -> Result<T, EngineError> {
+    let envelope: Envelope =
+        serde_json::from_value(value).map_err(|e| EngineError::InvalidJson {
+            message: e.to_string(),
+        })?;
+
+    if !envelope.success {
+        return Err(EngineError::Engine {
+            message: envelope
+                .error
+                .unwrap_or_else(|| "Engine command failed".to_string()),
+        });
+    }
+
+    serde_json::from_value(envelope.data).map_err(|e| EngineError::InvalidJson {
+        message: e.to_string(),
+    })
+}
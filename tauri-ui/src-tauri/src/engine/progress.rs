@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+use super::jobs::JobId;
+use super::EngineError;
+use crate::model::GenerateResult;
+
+/// Event emitted once per recipient while `generate` runs.
+pub const GENERATE_PROGRESS_EVENT: &str = "generate-progress";
+
+/// Event emitted once after `generate` finishes, successfully or not.
+pub const GENERATE_COMPLETE_EVENT: &str = "generate-complete";
+
+/// One recipient handled by the engine's generate loop.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateProgress {
+    /// Filled in by the bridge; the engine doesn't know about job ids.
+    #[serde(default)]
+    pub job_id: JobId,
+    pub index: usize,
+    pub total: usize,
+    pub email: String,
+    /// "created", "dry_run", "skipped" or "failed".
+    pub status: String,
+    /// Name of the recipient's template; empty if it has none.
+    #[serde(default)]
+    pub template: String,
+}
+
+/// Totals for a finished generate run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct GenerateSummary {
+    pub job_id: JobId,
+    pub total: usize,
+    pub created: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub success: bool,
+    pub cancelled: bool,
+    pub error: Option<String>,
+}
+
+impl GenerateSummary {
+    pub fn new(job_id: JobId) -> Self {
+        Self {
+            job_id,
+            ..Self::default()
+        }
+    }
+
+    /// Count one progress notification towards the summary.
+    pub fn record(&mut self, progress: &GenerateProgress) {
+        self.total = progress.total;
+        match progress.status.as_str() {
+            "created" | "dry_run" => self.created += 1,
+            "failed" => self.failed += 1,
+            _ => self.skipped += 1,
+        }
+    }
+
+    /// Fill in the outcome from the backend's final result.
+    pub fn finish(&mut self, result: &Result<GenerateResult, EngineError>) {
+        match result {
+            Ok(generated) => {
+                self.success = true;
+                self.error = None;
+                self.created = generated.created;
+            }
+            Err(e) => {
+                self.success = false;
+                self.cancelled = matches!(e, EngineError::Cancelled { .. });
+                self.error = Some(e.to_string());
+            }
+        }
+    }
+}
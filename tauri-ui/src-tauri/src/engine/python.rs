@@ -0,0 +1,111 @@
+use std::env;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use super::EngineError;
+
+/// Environment variable naming the interpreter to run the engine with.
+pub const PYTHON_ENV_VAR: &str = "DRAFTMATE_PYTHON";
+
+/// Where the chosen interpreter came from, in the order they are tried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PythonSource {
+    /// `python_path` in the app settings.
+    Settings,
+    /// The `DRAFTMATE_PYTHON` environment variable.
+    Env,
+    /// A `.venv` next to the engine directory, as the README sets up.
+    Venv,
+    /// `python3` on PATH.
+    Path,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Interpreter {
+    pub path: PathBuf,
+    pub source: PythonSource,
+}
+
+/// Interpreter details reported by `python_info`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PythonInfo {
+    pub path: PathBuf,
+    pub source: PythonSource,
+    pub version: String,
+}
+
+/// Pick the interpreter to run the engine with: the configured path, then
+/// `DRAFTMATE_PYTHON`, then `<engine_dir>/.venv`, then `python3` on PATH.
+pub fn find_python(configured: Option<&Path>, engine_dir: &Path) -> Interpreter {
+    if let Some(path) = configured.filter(|p| !p.as_os_str().is_empty()) {
+        return Interpreter {
+            path: path.to_path_buf(),
+            source: PythonSource::Settings,
+        };
+    }
+
+    if let Some(path) = env::var_os(PYTHON_ENV_VAR).filter(|v| !v.is_empty()) {
+        return Interpreter {
+            path: PathBuf::from(path),
+            source: PythonSource::Env,
+        };
+    }
+
+    let venv_python = venv_python(engine_dir);
+    if venv_python.is_file() {
+        return Interpreter {
+            path: venv_python,
+            source: PythonSource::Venv,
+        };
+    }
+
+    Interpreter {
+        path: search_path("python3").unwrap_or_else(|| PathBuf::from("python3")),
+        source: PythonSource::Path,
+    }
+}
+
+fn venv_python(engine_dir: &Path) -> PathBuf {
+    if cfg!(windows) {
+        engine_dir.join(".venv").join("Scripts").join("python.exe")
+    } else {
+        engine_dir.join(".venv").join("bin").join("python")
+    }
+}
+
+/// Resolve a bare program name against PATH so we can report a full path.
+fn search_path(program: &str) -> Option<PathBuf> {
+    let paths = env::var_os("PATH")?;
+    env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Ask the interpreter for its version, e.g. "Python 3.12.4".
+pub async fn python_version(python: &Path) -> Result<String, EngineError> {
+    let output = tokio::process::Command::new(python)
+        .arg("--version")
+        .output()
+        .await
+        .map_err(|e| EngineError::SpawnFailed {
+            message: format!("{}: {}", python.display(), e),
+        })?;
+
+    if !output.status.success() {
+        return Err(EngineError::NonZeroExit {
+            code: output.status.code(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+        });
+    }
+
+    // Python 2 printed its version to stderr; take whichever stream has it.
+    let text = if output.stdout.is_empty() {
+        output.stderr
+    } else {
+        output.stdout
+    };
+    Ok(String::from_utf8_lossy(&text).trim().to_string())
+}
@@ -0,0 +1,281 @@
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout};
+use tokio::sync::Notify;
+
+use super::jobs::{Job, JobId};
+use super::{EngineError, EngineLaunch};
+use crate::settings::Settings;
+
+/// A long-lived `python -m engine serve` child speaking line-delimited
+/// JSON-RPC over its stdin/stdout.
+///
+/// The child is spawned lazily on the first call and respawned on the next
+/// call if it has died. A request that was in flight when the child died is
+/// reported as failed rather than replayed, since replaying `generate` could
+/// create duplicate drafts.
+///
+/// Calls are serialized. Cancelling the running job or hitting its timeout
+/// kills the child; the next call starts a fresh one.
+pub struct EngineWorker {
+    /// Bundled resources directory, searched for the engine in packaged builds.
+    resource_dir: Option<PathBuf>,
+    process: tokio::sync::Mutex<Option<WorkerProcess>>,
+    /// The job currently talking to the child, and a signal to interrupt it.
+    /// Kept outside `process` so it can be reached while a call holds that lock.
+    running: Mutex<Option<(JobId, Arc<Notify>)>>,
+    next_id: AtomicU64,
+}
+
+struct WorkerProcess {
+    /// What the child was started with, so a settings change respawns it.
+    launch: EngineLaunch,
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl WorkerProcess {
+    fn spawn(launch: EngineLaunch) -> Result<Self, EngineError> {
+        let mut child = tokio::process::Command::from(launch.command())
+            .arg("serve")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| launch.spawn_failed(e))?;
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| EngineError::io("Engine worker has no stdin"))?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| EngineError::io("Engine worker has no stdout"))?;
+
+        Ok(Self {
+            launch,
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+        })
+    }
+
+    fn is_alive(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    /// Send one request and read lines until the response with the same id,
+    /// passing any notifications that arrive before it to `on_notification`.
+    async fn roundtrip(
+        &mut self,
+        id: u64,
+        request: &Value,
+        on_notification: &mut (dyn FnMut(&str, &Value) + Send),
+    ) -> Result<Value, EngineError> {
+        let mut line =
+            serde_json::to_string(request).map_err(|e| EngineError::io(e.to_string()))?;
+        line.push('\n');
+        let write_failed =
+            |e: std::io::Error| EngineError::io(format!("Failed to write to engine worker: {}", e));
+        self.stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(write_failed)?;
+        self.stdin.flush().await.map_err(write_failed)?;
+
+        loop {
+            let mut buf = String::new();
+            let read = self.stdout.read_line(&mut buf).await.map_err(|e| {
+                EngineError::io(format!("Failed to read from engine worker: {}", e))
+            })?;
+            if read == 0 {
+                return Err(EngineError::io("Engine worker exited before responding"));
+            }
+
+            let message: Value =
+                serde_json::from_str(buf.trim()).map_err(|e| EngineError::InvalidJson {
+                    message: e.to_string(),
+                })?;
+            if message.get("id").and_then(Value::as_u64) == Some(id) {
+                return Ok(message);
+            }
+            if let Some(method) = message.get("method").and_then(Value::as_str) {
+                on_notification(method, message.get("params").unwrap_or(&Value::Null));
+            }
+        }
+    }
+}
+
+impl EngineWorker {
+    pub fn new(resource_dir: Option<PathBuf>) -> Self {
+        Self {
+            resource_dir,
+            process: tokio::sync::Mutex::new(None),
+            running: Mutex::new(None),
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    /// Run one engine subcommand on the worker as `job` and return the JSON
+    /// envelope it produced (`{"success", "data", "error"}`).
+    pub async fn call(
+        &self,
+        settings: &Settings,
+        job: &Job,
+        command: &str,
+        args: &[String],
+    ) -> Result<Value, EngineError> {
+        self.call_with_notifications(settings, job, command, args, &mut |_, _| {})
+            .await
+    }
+
+    /// Like `call`, but hands each notification the engine sends while the
+    /// command runs (e.g. `progress`) to `on_notification` as it arrives.
+    /// Each `progress` notification also counts towards `job.processed()`.
+    ///
+    /// The command's timeout from `settings` covers the time spent running on
+    /// the worker, not time spent queued behind other calls.
+    pub async fn call_with_notifications(
+        &self,
+        settings: &Settings,
+        job: &Job,
+        command: &str,
+        args: &[String],
+        on_notification: &mut (dyn FnMut(&str, &Value) + Send),
+    ) -> Result<Value, EngineError> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": command,
+            "params": args,
+        });
+
+        let mut guard = self.process.lock().await;
+
+        // Claim the running slot before looking at the cancel flag. `cancel`
+        // sets the flag and then interrupts, so a cancel that lands after the
+        // check below still finds the slot, and `Notify` keeps the permit
+        // until the roundtrip waits on it.
+        let interrupt = Arc::new(Notify::new());
+        let _running = RunningSlot::claim(self, job.id(), Arc::clone(&interrupt));
+
+        // The job may have been cancelled while it waited for the worker.
+        if job.is_cancelled() {
+            return Err(cancelled(job));
+        }
+
+        let launch = EngineLaunch::resolve(settings, self.resource_dir.as_deref())?;
+        let timeout = settings.timeout_for(command);
+
+        let reusable = guard
+            .as_mut()
+            .is_some_and(|process| process.launch == launch && process.is_alive());
+        if !reusable {
+            *guard = Some(WorkerProcess::spawn(launch)?);
+        }
+
+        let process = guard.as_mut().expect("worker spawned above");
+
+        let mut on_message = |method: &str, params: &Value| {
+            if method == "progress" {
+                job.record_processed();
+            }
+            on_notification(method, params);
+        };
+
+        let result = tokio::select! {
+            result = process.roundtrip(id, &request, &mut on_message) => result,
+            _ = interrupt.notified() => Err(cancelled(job)),
+            _ = tokio::time::sleep(timeout) => Err(EngineError::Timeout {
+                command: command.to_string(),
+                seconds: timeout.as_secs(),
+            }),
+        };
+
+        let response = match result {
+            Ok(response) => response,
+            Err(e) => {
+                // Kill and drop the child so the next call starts a fresh one.
+                if let Some(mut process) = guard.take() {
+                    let _ = process.child.kill().await;
+                }
+                if job.is_cancelled() {
+                    return Err(cancelled(job));
+                }
+                return Err(e);
+            }
+        };
+
+        if let Some(error) = response.get("error") {
+            return Err(EngineError::Rpc {
+                code: error.get("code").and_then(Value::as_i64).unwrap_or(0),
+                message: error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("unknown error")
+                    .to_string(),
+            });
+        }
+
+        response
+            .get("result")
+            .cloned()
+            .ok_or_else(|| EngineError::InvalidJson {
+                message: "worker response has no result".to_string(),
+            })
+    }
+
+    /// Interrupt `job` if it is the one currently running on the worker. The
+    /// call then kills the child and returns a cancellation error.
+    pub fn interrupt(&self, job: JobId) {
+        let Ok(running) = self.running.lock() else {
+            return;
+        };
+        if let Some((running_job, interrupt)) = running.as_ref() {
+            if *running_job == job {
+                interrupt.notify_one();
+            }
+        }
+    }
+
+    fn set_running(&self, running: Option<(JobId, Arc<Notify>)>) {
+        if let Ok(mut slot) = self.running.lock() {
+            *slot = running;
+        }
+    }
+}
+
+/// Holds the worker's running slot for one call and empties it on drop, so
+/// every early return releases it.
+struct RunningSlot<'a> {
+    worker: &'a EngineWorker,
+}
+
+impl<'a> RunningSlot<'a> {
+    fn claim(worker: &'a EngineWorker, job: JobId, interrupt: Arc<Notify>) -> Self {
+        worker.set_running(Some((job, interrupt)));
+        Self { worker }
+    }
+}
+
+impl Drop for RunningSlot<'_> {
+    fn drop(&mut self) {
+        self.worker.set_running(None);
+    }
+}
+
+fn cancelled(job: &Job) -> EngineError {
+    EngineError::Cancelled {
+        job_id: job.id(),
+        processed: job.processed(),
+    }
+}
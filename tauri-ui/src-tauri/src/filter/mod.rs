@@ -0,0 +1,197 @@
+//! Row filters saved per profile, e.g.
+//! `firm == "Evercore" and school contains "Wharton" and not empty(email)`.
+//!
+//! Bare words are columns; headers with spaces are written in backticks,
+//! as in `` `first name` ``. A word that is not a header but names a column
+//! role (`email`, `name`, `firm`, `school`) refers to the header the role
+//! resolves to. Strings go in single or double quotes.
+//!
+//! Comparisons ignore case. `<`, `<=`, `>` and `>=` compare numerically
+//! when both sides are numbers. `contains`, `startswith` and `endswith`
+//! test substrings. `and` binds tighter than `or`; `not` negates. The
+//! functions `empty(x)`, `lower(x)`, `upper(x)` and `trim(x)` take one
+//! argument. A value on its own is true when it is not blank.
+
+mod parse;
+
+use std::fmt;
+
+use serde::Serialize;
+
+use self::parse::{CompareOp, Expr, Function};
+use crate::data;
+use crate::model::{ColumnRoles, Row};
+
+/// A filter that failed to parse or names a column the data lacks.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FilterError {
+    pub message: String,
+    /// 1-based character position in the filter where the problem is.
+    pub column: usize,
+}
+
+impl FilterError {
+    fn new(column: usize, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            column,
+        }
+    }
+}
+
+impl fmt::Display for FilterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.message)
+    }
+}
+
+impl std::error::Error for FilterError {}
+
+/// A filter bound to a set of headers, ready to test rows.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    expr: Expr,
+}
+
+impl Filter {
+    /// Parse `source` and resolve its columns against `headers`.
+    ///
+    /// Returns `None` for a blank filter, which matches every row.
+    pub fn compile(
+        source: &str,
+        headers: &[String],
+        roles: &ColumnRoles,
+    ) -> Result<Option<Self>, FilterError> {
+        if source.trim().is_empty() {
+            return Ok(None);
+        }
+        let mut expr = parse::parse(source)?;
+        let resolved = data::validate_roles(roles, headers).resolved;
+        resolve_columns(&mut expr, headers, &resolved)?;
+        Ok(Some(Self { expr }))
+    }
+
+    pub fn matches(&self, row: &Row) -> bool {
+        eval(&self.expr, row).truthy()
+    }
+
+    /// Indices of the rows that match, in order.
+    pub fn matching_rows(&self, rows: &[Row]) -> Vec<usize> {
+        rows.iter()
+            .enumerate()
+            .filter(|(_, row)| self.matches(row))
+            .map(|(index, _)| index)
+            .collect()
+    }
+}
+
+fn resolve_columns(
+    expr: &mut Expr,
+    headers: &[String],
+    roles: &ColumnRoles,
+) -> Result<(), FilterError> {
+    match expr {
+        Expr::Or(lhs, rhs) | Expr::And(lhs, rhs) | Expr::Compare(_, lhs, rhs) => {
+            resolve_columns(lhs, headers, roles)?;
+            resolve_columns(rhs, headers, roles)
+        }
+        Expr::Not(inner) | Expr::Call(_, inner) => resolve_columns(inner, headers, roles),
+        Expr::Column(name, column) => {
+            if headers.contains(name) {
+                return Ok(());
+            }
+            let role = roles
+                .entries()
+                .into_iter()
+                .find(|(role, _)| role.as_str() == name.as_str())
+                .and_then(|(_, header)| header);
+            match role {
+                Some(header) => {
+                    *name = header.to_string();
+                    Ok(())
+                }
+                None => Err(FilterError::new(
+                    *column,
+                    format!("No column named '{}'", name),
+                )),
+            }
+        }
+        Expr::Text(_) | Expr::Bool(_) => Ok(()),
+    }
+}
+
+enum Value {
+    Text(String),
+    Bool(bool),
+}
+
+impl Value {
+    fn truthy(&self) -> bool {
+        match self {
+            Value::Text(text) => !text.trim().is_empty(),
+            Value::Bool(value) => *value,
+        }
+    }
+
+    fn into_text(self) -> String {
+        match self {
+            Value::Text(text) => text,
+            Value::Bool(true) => "true".into(),
+            Value::Bool(false) => "false".into(),
+        }
+    }
+}
+
+fn eval(expr: &Expr, row: &Row) -> Value {
+    match expr {
+        Expr::Or(lhs, rhs) => Value::Bool(eval(lhs, row).truthy() || eval(rhs, row).truthy()),
+        Expr::And(lhs, rhs) => Value::Bool(eval(lhs, row).truthy() && eval(rhs, row).truthy()),
+        Expr::Not(inner) => Value::Bool(!eval(inner, row).truthy()),
+        Expr::Compare(op, lhs, rhs) => {
+            let lhs = eval(lhs, row).into_text();
+            let rhs = eval(rhs, row).into_text();
+            Value::Bool(compare(*op, lhs.trim(), rhs.trim()))
+        }
+        Expr::Call(function, arg) => {
+            let arg = eval(arg, row);
+            match function {
+                Function::Empty => Value::Bool(!arg.truthy()),
+                Function::Lower => Value::Text(arg.into_text().to_lowercase()),
+                Function::Upper => Value::Text(arg.into_text().to_uppercase()),
+                Function::Trim => Value::Text(arg.into_text().trim().to_string()),
+            }
+        }
+        Expr::Column(name, _) => Value::Text(row.get(name).cloned().unwrap_or_default()),
+        Expr::Text(text) => Value::Text(text.clone()),
+        Expr::Bool(value) => Value::Bool(*value),
+    }
+}
+
+fn compare(op: CompareOp, lhs: &str, rhs: &str) -> bool {
+    let numbers = lhs.parse::<f64>().ok().zip(rhs.parse::<f64>().ok());
+    let lhs = lhs.to_lowercase();
+    let rhs = rhs.to_lowercase();
+
+    let ordering = || match numbers {
+        Some((a, b)) => a.partial_cmp(&b),
+        None => Some(lhs.cmp(&rhs)),
+    };
+
+    match op {
+        CompareOp::Eq => match numbers {
+            Some((a, b)) => a == b,
+            None => lhs == rhs,
+        },
+        CompareOp::Ne => match numbers {
+            Some((a, b)) => a != b,
+            None => lhs != rhs,
+        },
+        CompareOp::Lt => ordering().is_some_and(|o| o.is_lt()),
+        CompareOp::Le => ordering().is_some_and(|o| o.is_le()),
+        CompareOp::Gt => ordering().is_some_and(|o| o.is_gt()),
+        CompareOp::Ge => ordering().is_some_and(|o| o.is_ge()),
+        CompareOp::Contains => lhs.contains(&rhs),
+        CompareOp::StartsWith => lhs.starts_with(&rhs),
+        CompareOp::EndsWith => lhs.ends_with(&rhs),
+    }
+}
@@ -0,0 +1,345 @@
+//! Tokenizer and recursive-descent parser for filter expressions.
+
+use super::FilterError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    StartsWith,
+    EndsWith,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Function {
+    /// True if the value is blank.
+    Empty,
+    Lower,
+    Upper,
+    Trim,
+}
+
+impl Function {
+    fn named(name: &str) -> Option<Self> {
+        match name {
+            "empty" => Some(Self::Empty),
+            "lower" => Some(Self::Lower),
+            "upper" => Some(Self::Upper),
+            "trim" => Some(Self::Trim),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Or(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    Call(Function, Box<Expr>),
+    /// A column reference, lowercased, with the 1-based column it starts at.
+    Column(String, usize),
+    Text(String),
+    Bool(bool),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Ident(String),
+    /// A `` `quoted column` ``.
+    Quoted(String),
+    Text(String),
+    Number(String),
+    Op(CompareOp),
+    And,
+    Or,
+    Not,
+    True,
+    False,
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Ident(name) => format!("'{}'", name),
+            Token::Quoted(name) => format!("`{}`", name),
+            Token::Text(text) => format!("\"{}\"", text),
+            Token::Number(number) => number.clone(),
+            Token::Op(_) => "an operator".into(),
+            Token::And => "'and'".into(),
+            Token::Or => "'or'".into(),
+            Token::Not => "'not'".into(),
+            Token::True => "'true'".into(),
+            Token::False => "'false'".into(),
+            Token::LParen => "'('".into(),
+            Token::RParen => "')'".into(),
+            Token::Comma => "','".into(),
+        }
+    }
+}
+
+/// A token and the 1-based column it starts at.
+type Spanned = (Token, usize);
+
+pub fn parse(source: &str) -> Result<Expr, FilterError> {
+    let tokens = tokenize(source)?;
+    let end = source.chars().count() + 1;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        end,
+    };
+    let expr = parser.or()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some((token, column)) => Err(FilterError::new(
+            column,
+            format!(
+                "Expected 'and', 'or' or the end of the filter, found {}",
+                token.describe()
+            ),
+        )),
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Spanned>, FilterError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        let two: String = chars[i..chars.len().min(i + 2)].iter().collect();
+        let op = match two.as_str() {
+            "==" => Some((CompareOp::Eq, 2)),
+            "!=" => Some((CompareOp::Ne, 2)),
+            "<=" => Some((CompareOp::Le, 2)),
+            ">=" => Some((CompareOp::Ge, 2)),
+            _ => match c {
+                '=' => Some((CompareOp::Eq, 1)),
+                '<' => Some((CompareOp::Lt, 1)),
+                '>' => Some((CompareOp::Gt, 1)),
+                _ => None,
+            },
+        };
+        if let Some((op, len)) = op {
+            tokens.push((Token::Op(op), column));
+            i += len;
+            continue;
+        }
+
+        match c {
+            '(' => tokens.push((Token::LParen, column)),
+            ')' => tokens.push((Token::RParen, column)),
+            ',' => tokens.push((Token::Comma, column)),
+            '"' | '\'' => {
+                let (text, next) = read_quoted(&chars, i, c)?;
+                tokens.push((Token::Text(text), column));
+                i = next;
+                continue;
+            }
+            '`' => {
+                let (name, next) = read_quoted(&chars, i, '`')?;
+                tokens.push((Token::Quoted(name.trim().to_lowercase()), column));
+                i = next;
+                continue;
+            }
+            c if c.is_ascii_digit()
+                || (c == '-' && chars.get(i + 1).is_some_and(char::is_ascii_digit)) =>
+            {
+                let start = i;
+                i += 1;
+                while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                    i += 1;
+                }
+                tokens.push((Token::Number(chars[start..i].iter().collect()), column));
+                continue;
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let start = i;
+                while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                    i += 1;
+                }
+                let word: String = chars[start..i].iter().collect::<String>().to_lowercase();
+                let token = match word.as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    "true" => Token::True,
+                    "false" => Token::False,
+                    "contains" => Token::Op(CompareOp::Contains),
+                    "startswith" => Token::Op(CompareOp::StartsWith),
+                    "endswith" => Token::Op(CompareOp::EndsWith),
+                    _ => Token::Ident(word),
+                };
+                tokens.push((token, column));
+                continue;
+            }
+            other => {
+                return Err(FilterError::new(
+                    column,
+                    format!("Unexpected character '{}'", other),
+                ))
+            }
+        }
+        i += 1;
+    }
+
+    Ok(tokens)
+}
+
+/// Read a literal opened by `quote` at `start`, returning its contents and
+/// the index just past the closing quote. A backslash escapes the next
+/// character.
+fn read_quoted(chars: &[char], start: usize, quote: char) -> Result<(String, usize), FilterError> {
+    let mut text = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                text.push(chars[i + 1]);
+                i += 2;
+            }
+            c if c == quote => return Ok((text, i + 1)),
+            c => {
+                text.push(c);
+                i += 1;
+            }
+        }
+    }
+    Err(FilterError::new(
+        start + 1,
+        format!("Missing closing {}", quote),
+    ))
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+    /// Column just past the end of the source, for "unexpected end" errors.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<(&Token, usize)> {
+        self.tokens
+            .get(self.pos)
+            .map(|(token, column)| (token, *column))
+    }
+
+    fn next(&mut self) -> Option<Spanned> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, expected: &Token) -> bool {
+        if self.peek().is_some_and(|(token, _)| token == expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.and()?;
+        while self.eat(&Token::Or) {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, FilterError> {
+        let mut expr = self.not()?;
+        while self.eat(&Token::And) {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, FilterError> {
+        if self.eat(&Token::Not) {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, FilterError> {
+        let lhs = self.primary()?;
+        if let Some((Token::Op(op), _)) = self.peek() {
+            let op = *op;
+            self.pos += 1;
+            let rhs = self.primary()?;
+            return Ok(Expr::Compare(op, Box::new(lhs), Box::new(rhs)));
+        }
+        Ok(lhs)
+    }
+
+    fn primary(&mut self) -> Result<Expr, FilterError> {
+        let Some((token, column)) = self.next() else {
+            return Err(FilterError::new(
+                self.end,
+                "Expected a value, found the end of the filter",
+            ));
+        };
+
+        match token {
+            Token::Text(text) | Token::Number(text) => Ok(Expr::Text(text)),
+            Token::True => Ok(Expr::Bool(true)),
+            Token::False => Ok(Expr::Bool(false)),
+            Token::Quoted(name) => Ok(Expr::Column(name, column)),
+            Token::LParen => {
+                let expr = self.or()?;
+                self.expect_close(column)?;
+                Ok(expr)
+            }
+            Token::Ident(name) if self.eat(&Token::LParen) => {
+                let function = Function::named(&name).ok_or_else(|| {
+                    FilterError::new(column, format!("Unknown function '{}'", name))
+                })?;
+                let arg = self.or()?;
+                if let Some((Token::Comma, comma)) = self.peek() {
+                    return Err(FilterError::new(
+                        comma,
+                        format!("{}() takes one argument", name),
+                    ));
+                }
+                self.expect_close(column)?;
+                Ok(Expr::Call(function, Box::new(arg)))
+            }
+            Token::Ident(name) => Ok(Expr::Column(name, column)),
+            other => Err(FilterError::new(
+                column,
+                format!("Expected a value, found {}", other.describe()),
+            )),
+        }
+    }
+
+    fn expect_close(&mut self, open: usize) -> Result<(), FilterError> {
+        if self.eat(&Token::RParen) {
+            return Ok(());
+        }
+        let column = self.peek().map_or(self.end, |(_, column)| column);
+        Err(FilterError::new(
+            column,
+            format!("Expected ')' to close the '(' at column {}", open),
+        ))
+    }
+}
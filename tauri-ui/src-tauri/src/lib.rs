@@ -1,76 +1,71 @@
+pub mod bridge;
+mod commands;
+pub mod data;
+pub mod engine;
+pub mod filter;
+pub mod model;
+#[cfg(feature = "debug-passthrough")]
+mod passthrough;
+pub mod settings;
+pub mod template;
+
 use std::path::PathBuf;
-use std::process::Command;
-use tauri::Manager;
+use std::sync::Arc;
 
-/// Find the engine directory by looking for the engine module.
-fn find_engine_dir() -> Result<PathBuf, String> {
-    // Try multiple strategies to find the engine directory
+use tauri::{AppHandle, Manager, State};
 
-    // Strategy 1: Current directory's parent (works in dev mode from tauri-ui)
-    if let Ok(cwd) = std::env::current_dir() {
-        if let Some(parent) = cwd.parent() {
-            let engine_path = parent.join("engine");
-            if engine_path.exists() {
-                return Ok(parent.to_path_buf());
-            }
-        }
-        // Maybe we're already in the project root
-        let engine_path = cwd.join("engine");
-        if engine_path.exists() {
-            return Ok(cwd);
-        }
-    }
+use commands::Backend;
+use data::DataWatcher;
+use engine::{
+    DiagnosticsReport, EngineError, EngineLaunch, EngineWorker, JobInfo, JobManager, PythonInfo,
+    SubprocessBackend,
+};
+use settings::{Settings, SettingsStore};
 
-    // Strategy 2: Look relative to executable
-    if let Ok(exe_path) = std::env::current_exe() {
-        // In dev: target/debug/draftmate -> go up to find project root
-        let mut dir = exe_path.parent();
-        for _ in 0..5 {
-            if let Some(d) = dir {
-                let engine_path = d.join("engine");
-                if engine_path.exists() {
-                    return Ok(d.to_path_buf());
-                }
-                dir = d.parent();
-            }
-        }
-    }
+/// The bundled resources directory, where packaged builds ship the engine.
+fn resource_dir(app: &AppHandle) -> Option<PathBuf> {
+    app.path().resource_dir().ok()
+}
 
-    // Strategy 3: Hardcoded fallback for development
-    let dev_path = PathBuf::from("/Users/arinaggarwal/Documents/IB Prep Materials/Draftmate v3");
-    if dev_path.join("engine").exists() {
-        return Ok(dev_path);
-    }
+/// Engine jobs that are queued or running, oldest first.
+#[tauri::command]
+fn list_jobs(jobs: State<JobManager>) -> Vec<JobInfo> {
+    jobs.list()
+}
 
-    Err("Could not find engine directory. Make sure the 'engine' folder exists.".to_string())
+/// Report which interpreter the engine runs with, why it was chosen, and its version.
+#[tauri::command]
+async fn python_info(
+    app: AppHandle,
+    settings: State<'_, Arc<SettingsStore>>,
+) -> Result<PythonInfo, EngineError> {
+    let launch = EngineLaunch::resolve(&settings.get(), resource_dir(&app).as_deref())?;
+    let version = engine::python_version(&launch.python.path).await?;
+
+    Ok(PythonInfo {
+        path: launch.python.path,
+        source: launch.python.source,
+        version,
+    })
 }
 
-/// Run the Python engine CLI and return the JSON output.
-/// This is the bridge between Tauri frontend and Python backend.
+/// Collect a structured report on the engine setup for the support screen.
 #[tauri::command]
-fn run_engine(args: Vec<String>) -> Result<String, String> {
-    let engine_dir = find_engine_dir()?;
+async fn diagnose(
+    app: AppHandle,
+    settings: State<'_, Arc<SettingsStore>>,
+) -> Result<DiagnosticsReport, EngineError> {
+    Ok(engine::diagnose(&settings.get(), resource_dir(&app).as_deref()).await)
+}
 
-    let output = Command::new("python3")
-        .arg("-m")
-        .arg("engine")
-        .args(&args)
-        .current_dir(&engine_dir)
-        .output()
-        .map_err(|e| format!("Failed to execute Python engine: {}", e))?;
+#[tauri::command]
+fn get_settings(settings: State<Arc<SettingsStore>>) -> Settings {
+    settings.get()
+}
 
-    if output.status.success() {
-        String::from_utf8(output.stdout)
-            .map_err(|e| format!("Failed to parse stdout: {}", e))
-    } else {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        Err(format!(
-            "Engine command failed: {}{}",
-            stderr,
-            if stdout.is_empty() { String::new() } else { format!("\nOutput: {}", stdout) }
-        ))
-    }
+#[tauri::command]
+fn save_settings(settings: State<Arc<SettingsStore>>, value: Settings) -> Result<(), String> {
+    settings.set(value)
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -78,8 +73,59 @@ pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
-        .invoke_handler(tauri::generate_handler![run_engine])
+        .manage(JobManager::new())
+        .manage(DataWatcher::new())
+        .invoke_handler(tauri::generate_handler![
+            commands::load_csv,
+            commands::watch_data_file,
+            commands::unwatch_data_file,
+            commands::list_sheets,
+            commands::load_workbook,
+            commands::suggest_column_roles,
+            commands::validate_column_roles,
+            commands::join_sources,
+            commands::filter_rows,
+            commands::lint_templates,
+            commands::load_sheet,
+            commands::list_sheet_tabs,
+            commands::preview,
+            commands::render_preview,
+            commands::render_previews,
+            commands::validate_dataset,
+            commands::generate,
+            commands::read_files,
+            commands::export_templates,
+            commands::validate_license,
+            commands::cancel_job,
+            #[cfg(feature = "debug-passthrough")]
+            passthrough::engine_call,
+            #[cfg(feature = "debug-passthrough")]
+            passthrough::run_engine,
+            #[cfg(feature = "debug-passthrough")]
+            passthrough::run_engine_stdin,
+            list_jobs,
+            python_info,
+            diagnose,
+            get_settings,
+            save_settings
+        ])
         .setup(|app| {
+            let settings_path = app
+                .path()
+                .app_config_dir()
+                .ok()
+                .map(|dir| dir.join("settings.json"));
+            let settings = Arc::new(SettingsStore::load(settings_path));
+            let worker = Arc::new(EngineWorker::new(resource_dir(app.handle())));
+            let backend: Backend = Box::new(SubprocessBackend::new(
+                Arc::clone(&worker),
+                Arc::clone(&settings),
+            ));
+            app.manage(settings);
+            app.manage(backend);
+            #[cfg(feature = "debug-passthrough")]
+            app.manage(worker);
+
             #[cfg(debug_assertions)]
             {
                 let window = app.get_webview_window("main").unwrap();
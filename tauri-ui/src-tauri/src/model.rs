@@ -0,0 +1,486 @@
+//! Data shapes shared by the engine and the frontend.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{Deserialize, Serialize};
+
+/// One spreadsheet row, keyed by lowercased header.
+pub type Row = BTreeMap<String, String>;
+
+/// Recipient email (lowercased) to template id.
+pub type Overrides = HashMap<String, String>;
+
+/// Loaded rows plus their ordered, lowercased headers.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Dataset {
+    pub rows: Vec<Row>,
+    pub headers: Vec<String>,
+}
+
+/// What the data loaders hand back to the frontend.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DataLoadResult {
+    pub rows: Vec<Row>,
+    pub headers: Vec<String>,
+    pub count: usize,
+}
+
+/// One tab of a Google Sheet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SheetTab {
+    pub name: String,
+    pub gid: String,
+}
+
+/// A natively loaded CSV file, plus how it was read.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CsvLoadResult {
+    #[serde(flatten)]
+    pub data: DataLoadResult,
+    /// Encoding the file was decoded with, e.g. "UTF-8" or "windows-1252".
+    pub encoding: String,
+    pub delimiter: char,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Template {
+    pub id: String,
+    pub name: String,
+    pub text: String,
+    #[serde(default)]
+    pub manual_only: bool,
+}
+
+/// A template rendered for one row.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Rendered {
+    pub text: String,
+    /// Placeholders that came out blank and had no default, in first-use
+    /// order. Placeholders in blocks that were left out don't count.
+    pub empty: Vec<String>,
+}
+
+/// The subject and every template body rendered for one row.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RenderedRow {
+    pub subject: Rendered,
+    /// Keyed by template id.
+    pub bodies: BTreeMap<String, Rendered>,
+}
+
+/// How a piece of rendered text came about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PartKind {
+    /// Template text, as written.
+    Text,
+    /// A placeholder's value from the row.
+    Value,
+    /// A placeholder that was blank, replaced by its default.
+    Default,
+    /// A placeholder that was blank with no default; the text is empty.
+    Empty,
+}
+
+/// One piece of rendered text.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Part {
+    pub kind: PartKind,
+    pub text: String,
+    /// The placeholder the text came from; `None` for `Text`.
+    pub placeholder: Option<String>,
+}
+
+/// Rendered text split into parts, so substituted and empty values can
+/// be highlighted.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Annotated {
+    /// The parts' text joined.
+    pub text: String,
+    pub parts: Vec<Part>,
+}
+
+/// What one recipient's draft will contain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EmailPreview {
+    pub email: String,
+    /// 1-based position of the recipient's row in the request's data.
+    pub row: usize,
+    pub template_id: Option<String>,
+    pub template_name: String,
+    pub subject: Annotated,
+    /// `None` when no template applies, so no draft will be created.
+    pub body: Option<Annotated>,
+    /// The body exactly as the draft gets it.
+    pub html: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PreviewRow {
+    pub name: String,
+    pub email: String,
+    pub email_norm: String,
+    pub firm: String,
+    pub template_name: String,
+    pub template_id: Option<String>,
+    pub is_manual: bool,
+    pub is_eligible: bool,
+    /// 1-based position of the row in the data sent.
+    #[serde(default)]
+    pub row: Option<usize>,
+}
+
+/// What to do when one email address is on several recipient rows.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DedupPolicy {
+    /// Use the first row with the address.
+    #[default]
+    KeepFirst,
+    /// Use the last row with the address.
+    KeepLast,
+    /// Create no draft for the address at all.
+    SkipAll,
+    /// Refuse to preview or generate until the data is fixed.
+    Error,
+}
+
+impl DedupPolicy {
+    /// The engine's name for the policy.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DedupPolicy::KeepFirst => "keep_first",
+            DedupPolicy::KeepLast => "keep_last",
+            DedupPolicy::SkipAll => "skip_all",
+            DedupPolicy::Error => "error",
+        }
+    }
+}
+
+/// One email address found on several recipient rows, and how it was resolved.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DuplicateRecipient {
+    /// Lowercased address.
+    pub email: String,
+    /// 1-based positions of every row with the address, in the data sent.
+    pub rows: Vec<usize>,
+    /// The row whose data was used, or `None` if all were skipped.
+    pub kept: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PreviewResult {
+    pub preview_rows: Vec<PreviewRow>,
+    pub count: usize,
+    #[serde(default)]
+    pub duplicates: Vec<DuplicateRecipient>,
+}
+
+/// Input to `validate_dataset`: the same data and templates `generate`
+/// would get.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidateRequest {
+    pub data: Dataset,
+    pub templates: Vec<Template>,
+    #[serde(default)]
+    pub overrides: Overrides,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub roles: ColumnRoles,
+    #[serde(default)]
+    pub dedup: DedupPolicy,
+    /// Row filter expression; blank or absent selects every row.
+    #[serde(default)]
+    pub filter: Option<String>,
+    /// Each row's subject and bodies, rendered by `template`; filled in
+    /// by the bridge, aligned with `data.rows`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rendered: Vec<RenderedRow>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Generation should not go ahead.
+    Error,
+    /// Worth a look, but drafts will still be created.
+    Warning,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingCode {
+    DuplicateEmail,
+    InvalidEmail,
+    MissingEmail,
+    EmptyName,
+    EmptyFirm,
+    InvalidGenerate,
+    NoTemplate,
+    EmptyPlaceholder,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationFinding {
+    /// 1-based position of the row in the validated data.
+    pub row: usize,
+    /// Header the problem is in, when one applies.
+    pub column: Option<String>,
+    pub code: FindingCode,
+    pub severity: Severity,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub findings: Vec<ValidationFinding>,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+impl ValidationReport {
+    /// Whether generation should be blocked.
+    pub fn has_errors(&self) -> bool {
+        self.errors > 0
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GenerateResult {
+    pub created: usize,
+    #[serde(default)]
+    pub duplicates: Vec<DuplicateRecipient>,
+    /// Set by the bridge when the request asked for a `write_back`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_back: Option<WriteBackReport>,
+}
+
+/// Opt-in step after `generate`: mark each drafted recipient in the
+/// profile's CSV so the next run skips them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteBack {
+    /// The CSV the data was loaded from.
+    pub path: String,
+    /// Written to the `Drafted At` column as is, e.g. "2024-05-01 14:30".
+    pub drafted_at: String,
+}
+
+/// What the write-back changed in the CSV.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriteBackReport {
+    /// Copy of the file from before it was rewritten.
+    pub backup: String,
+    /// Rows marked as drafted.
+    pub updated: usize,
+    /// Drafted emails with no row in the file, e.g. because it was edited
+    /// during the run.
+    pub missing: Vec<String>,
+    /// Why the file was left alone, if it was; the drafts still exist.
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TemplateFile {
+    pub name: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReadFilesResult {
+    pub files: Vec<TemplateFile>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportResult {
+    /// File name of the ZIP written to the Downloads folder.
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LicenseResult {
+    pub valid: bool,
+    pub message: String,
+}
+
+/// Which header holds each column the engine needs to know about.
+///
+/// An unset role is guessed from the headers, as is a role naming a header
+/// the loaded data does not have.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColumnRoles {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub firm: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub school: Option<String>,
+}
+
+impl ColumnRoles {
+    /// Each role and the header it is mapped to, unset roles included.
+    pub fn entries(&self) -> [(ColumnRole, Option<&str>); 4] {
+        [
+            (ColumnRole::Email, self.email.as_deref()),
+            (ColumnRole::Name, self.name.as_deref()),
+            (ColumnRole::Firm, self.firm.as_deref()),
+            (ColumnRole::School, self.school.as_deref()),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColumnRole {
+    Email,
+    Name,
+    Firm,
+    School,
+}
+
+impl ColumnRole {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ColumnRole::Email => "email",
+            ColumnRole::Name => "name",
+            ColumnRole::Firm => "firm",
+            ColumnRole::School => "school",
+        }
+    }
+}
+
+/// Rows a profile's filter selects; see `filter`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterMatches {
+    /// 0-based indices of the matching rows, in order.
+    pub rows: Vec<usize>,
+    /// Number of rows the filter was tested against.
+    pub total: usize,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewRequest {
+    pub data: Dataset,
+    pub templates: Vec<Template>,
+    #[serde(default)]
+    pub overrides: Overrides,
+    /// Only list rows that will get a draft; otherwise list every row.
+    #[serde(default = "default_true")]
+    pub only_recipients: bool,
+    #[serde(default)]
+    pub roles: ColumnRoles,
+    #[serde(default)]
+    pub dedup: DedupPolicy,
+    /// Row filter expression; blank or absent selects every row.
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateRequest {
+    pub data: Dataset,
+    pub templates: Vec<Template>,
+    #[serde(default)]
+    pub overrides: Overrides,
+    pub subject: String,
+    #[serde(default)]
+    pub resume_path: Option<String>,
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub roles: ColumnRoles,
+    #[serde(default)]
+    pub dedup: DedupPolicy,
+    /// Row filter expression; blank or absent selects every row.
+    #[serde(default)]
+    pub filter: Option<String>,
+    /// Ignored for dry runs.
+    #[serde(default)]
+    pub write_back: Option<WriteBack>,
+    /// Each row's subject and bodies, rendered by `template`; filled in
+    /// by the bridge, aligned with `data.rows`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rendered: Vec<RenderedRow>,
+}
+
+/// How `join_sources` names the columns of the sources after the first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeaderMode {
+    /// Columns with the same name are one column; the first source with a
+    /// non-blank value wins.
+    #[default]
+    Merge,
+    /// Each column is renamed `<source>.<header>`, so nothing collides.
+    Prefix,
+}
+
+/// One loaded source to join, named for prefixes and reports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JoinSource {
+    pub name: String,
+    pub data: Dataset,
+}
+
+/// Input to `join_sources`. The first source is the primary one: the
+/// result has exactly its rows, in its order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JoinRequest {
+    pub sources: Vec<JoinSource>,
+    /// Column to join on: a header, or a column role such as `email`.
+    #[serde(default = "default_join_key")]
+    pub key: String,
+    #[serde(default)]
+    pub headers: HeaderMode,
+}
+
+fn default_join_key() -> String {
+    "email".into()
+}
+
+/// Input to `lint_templates`: the subject, templates and data the user is
+/// editing against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintRequest {
+    /// The loaded data, if any; placeholders are checked against its
+    /// headers and empty ones counted over its rows.
+    #[serde(default)]
+    pub data: Dataset,
+    pub templates: Vec<Template>,
+    #[serde(default)]
+    pub overrides: Overrides,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub roles: ColumnRoles,
+    /// Row filter expression; blank or absent selects every row.
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+/// Input to `render_preview` and `render_previews`: what `generate`
+/// would get, less its run options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderRequest {
+    pub data: Dataset,
+    pub templates: Vec<Template>,
+    #[serde(default)]
+    pub overrides: Overrides,
+    #[serde(default)]
+    pub subject: String,
+    #[serde(default)]
+    pub roles: ColumnRoles,
+    #[serde(default)]
+    pub dedup: DedupPolicy,
+    /// Row filter expression; blank or absent selects every row.
+    #[serde(default)]
+    pub filter: Option<String>,
+}
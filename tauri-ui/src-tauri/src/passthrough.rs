@@ -0,0 +1,48 @@
+//! Raw engine access for debugging, compiled only with the
+//! `debug-passthrough` feature. These commands run arbitrary engine
+//! arguments, so release builds expose the typed commands instead.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+use tauri::{AppHandle, State};
+
+use crate::engine::{self, EngineError, EngineWorker, JobManager};
+use crate::resource_dir;
+use crate::settings::SettingsStore;
+
+/// Run an engine subcommand on the persistent worker and return its JSON response.
+#[tauri::command]
+pub async fn engine_call(
+    worker: State<'_, Arc<EngineWorker>>,
+    jobs: State<'_, JobManager>,
+    settings: State<'_, Arc<SettingsStore>>,
+    command: String,
+    args: Vec<String>,
+) -> Result<Value, EngineError> {
+    let guard = jobs.start(&command);
+    worker
+        .call(&settings.get(), guard.job(), &command, &args)
+        .await
+}
+
+/// Run the Python engine CLI in a fresh process and return the JSON output.
+#[tauri::command]
+pub async fn run_engine(
+    app: AppHandle,
+    settings: State<'_, Arc<SettingsStore>>,
+    args: Vec<String>,
+) -> Result<String, EngineError> {
+    engine::run_once(&settings.get(), resource_dir(&app).as_deref(), &args).await
+}
+
+/// Like `run_engine`, but hands the arguments to the engine over stdin so
+/// large `--data`/`--templates` payloads don't hit the OS argv limit.
+#[tauri::command]
+pub async fn run_engine_stdin(
+    app: AppHandle,
+    settings: State<'_, Arc<SettingsStore>>,
+    args: Vec<String>,
+) -> Result<String, EngineError> {
+    engine::run_once_stdin(&settings.get(), resource_dir(&app).as_deref(), &args).await
+}
@@ -0,0 +1,105 @@
+//! App settings persisted as JSON in the app config directory.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Timeout for engine subcommands that have no entry in `Settings::timeouts`.
+pub const DEFAULT_TIMEOUT_SECS: u64 = 120;
+
+/// Built-in per-subcommand timeouts, in seconds. `generate` drives Outlook
+/// one draft at a time, so it gets far longer than the data commands.
+const BUILTIN_TIMEOUTS: &[(&str, u64)] = &[
+    ("load-csv", 30),
+    ("load-sheet", 60),
+    ("preview", 60),
+    ("generate", 3600),
+    ("read-files", 30),
+    ("export-templates", 60),
+    ("validate-license", 60),
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Per-subcommand timeout overrides in seconds, keyed by engine subcommand.
+    pub timeouts: HashMap<String, u64>,
+    /// Interpreter to run the engine with. Takes precedence over
+    /// `DRAFTMATE_PYTHON`, a project `.venv` and `python3` on PATH.
+    pub python_path: Option<PathBuf>,
+    /// Directory containing the `engine` package. Takes precedence over
+    /// `DRAFTMATE_ENGINE_DIR` and the bundled resources.
+    pub engine_dir: Option<PathBuf>,
+}
+
+impl Settings {
+    /// How long `command` may run before the engine is killed.
+    pub fn timeout_for(&self, command: &str) -> Duration {
+        let secs = self
+            .timeouts
+            .get(command)
+            .copied()
+            .or_else(|| {
+                BUILTIN_TIMEOUTS
+                    .iter()
+                    .find(|(name, _)| *name == command)
+                    .map(|(_, secs)| *secs)
+            })
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+        Duration::from_secs(secs)
+    }
+}
+
+/// Settings shared as Tauri state, backed by a JSON file.
+pub struct SettingsStore {
+    path: Option<PathBuf>,
+    settings: RwLock<Settings>,
+}
+
+impl SettingsStore {
+    /// Load settings from `path`, falling back to defaults if the file is
+    /// missing or unreadable. With no path, settings live in memory only.
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let settings = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(|text| serde_json::from_str(&text).ok())
+            .unwrap_or_default();
+
+        Self {
+            path,
+            settings: RwLock::new(settings),
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.read().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    /// Replace the settings and write them to disk.
+    pub fn set(&self, settings: Settings) -> Result<(), String> {
+        if let Some(path) = &self.path {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir)
+                    .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+            }
+            let text = serde_json::to_string_pretty(&settings).map_err(|e| e.to_string())?;
+            fs::write(path, text).map_err(|e| format!("Failed to save settings: {}", e))?;
+        }
+
+        let mut current = self
+            .settings
+            .write()
+            .map_err(|_| "Settings lock poisoned".to_string())?;
+        *current = settings;
+        Ok(())
+    }
+
+    pub fn timeout_for(&self, command: &str) -> Duration {
+        self.get().timeout_for(command)
+    }
+}
@@ -0,0 +1,26 @@
+//! The HTML body drafts are created with.
+
+/// Wrap a rendered body in HTML as the engine's `_wrap_in_html` does,
+/// quirks included: double spaces become paragraph breaks, and leading and
+/// trailing `<`, `b`, `r` and `>` characters are trimmed off.
+pub fn wrap_in_html(text: &str) -> String {
+    let content = text
+        .trim_matches(is_python_space)
+        .replace("  ", "<br><br>")
+        .replace('\n', "<br>");
+    let is_break_char = |c: char| matches!(c, '<' | 'b' | 'r' | '>');
+    let content = content
+        .trim_matches(is_python_space)
+        .trim_start_matches(is_break_char)
+        .trim_end_matches(is_break_char);
+    format!(
+        "<html><body style='margin:0;padding:0;font-family:Arial,sans-serif;'>{}</body></html>",
+        content
+    )
+}
+
+/// What Python's `str.strip()` removes: Unicode whitespace plus the ASCII
+/// separators 0x1C to 0x1F.
+fn is_python_space(c: char) -> bool {
+    c.is_whitespace() || ('\x1c'..='\x1f').contains(&c)
+}
@@ -0,0 +1,292 @@
+//! Checks on the subject and templates against the loaded headers, run as
+//! they are edited so mistakes show up before anything is generated.
+
+use serde::Serialize;
+
+use super::{transforms, Position, Resolver, TemplateText, DERIVED_PLACEHOLDERS};
+use crate::data;
+use crate::model::{Dataset, LintRequest, Severity, Template};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LintCode {
+    /// The text doesn't parse; nothing else is checked in it.
+    Syntax,
+    /// A `}` outside any tag, usually half of a mistyped tag.
+    UnbalancedBrace,
+    /// A `{{name}}` tag, which works but is read as `{name}`.
+    DoubleBraces,
+    /// A placeholder that is neither a header nor a derived name.
+    UnknownPlaceholder,
+    /// A bare word after `|` that names no transform but is close to one.
+    /// Other words are defaults, as the resolver treats them.
+    UnknownTransform,
+    EmptyTemplate,
+    /// A manual-only template that no override picks, so nobody gets it.
+    UnusedTemplate,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LintIssue {
+    /// Template name; `None` for the subject line.
+    pub template: Option<String>,
+    pub template_id: Option<String>,
+    pub code: LintCode,
+    pub severity: Severity,
+    pub message: String,
+    /// 1-based position of the problem, when it has one.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// A close known name to use instead, for unknown names.
+    pub suggestion: Option<String>,
+}
+
+/// How many rows leave one placeholder blank.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlaceholderCount {
+    /// Template name; `None` for the subject line.
+    pub template: Option<String>,
+    pub template_id: Option<String>,
+    pub placeholder: String,
+    /// Rows that render the placeholder blank with no default. Every row
+    /// is counted, whichever template it ends up getting.
+    pub empty: usize,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct LintReport {
+    /// In template order, the subject line first.
+    pub issues: Vec<LintIssue>,
+    /// Each placeholder of each template that parsed, in first-use order.
+    pub placeholders: Vec<PlaceholderCount>,
+    /// Rows the counts are over, after the filter.
+    pub rows: usize,
+    pub errors: usize,
+    pub warnings: usize,
+}
+
+/// The subject line or one template, as issues name it.
+struct Source<'a> {
+    template: Option<&'a Template>,
+    text: &'a str,
+}
+
+impl Source<'_> {
+    fn issue(
+        &self,
+        code: LintCode,
+        severity: Severity,
+        at: Option<Position>,
+        message: String,
+    ) -> LintIssue {
+        LintIssue {
+            template: self.template.map(|t| t.name.clone()),
+            template_id: self.template.map(|t| t.id.clone()),
+            code,
+            severity,
+            message,
+            line: at.map(|at| at.line),
+            column: at.map(|at| at.column),
+            suggestion: None,
+        }
+    }
+}
+
+/// Lint `request`'s subject and templates against the rows of `data`,
+/// which the caller has already narrowed to the request's filter.
+///
+/// Placeholder names are only checked when `data` has headers, so
+/// templates written before anything is loaded aren't all flagged.
+pub fn lint(request: &LintRequest, data: &Dataset) -> LintReport {
+    let resolved = data::validate_roles(&request.roles, &data.headers).resolved;
+    let known: Vec<&str> = DERIVED_PLACEHOLDERS
+        .iter()
+        .copied()
+        .chain(data.headers.iter().map(String::as_str))
+        .collect();
+
+    let sources = std::iter::once(Source {
+        template: None,
+        text: &request.subject,
+    })
+    .chain(request.templates.iter().map(|template| Source {
+        template: Some(template),
+        text: &template.text,
+    }));
+
+    let mut report = LintReport {
+        rows: data.rows.len(),
+        ..LintReport::default()
+    };
+    for source in sources {
+        if source.text.trim().is_empty() {
+            // A blank body is a mistake; a blank subject is allowed, though
+            // rarely meant.
+            let (severity, message) = match source.template {
+                Some(template) => (
+                    Severity::Error,
+                    format!("Template '{}' is empty", template.name),
+                ),
+                None => (Severity::Warning, "The subject line is empty".to_string()),
+            };
+            report
+                .issues
+                .push(source.issue(LintCode::EmptyTemplate, severity, None, message));
+            continue;
+        }
+
+        let parsed = match TemplateText::parse(source.text) {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                let at = Position {
+                    line: e.line,
+                    column: e.column,
+                };
+                report.issues.push(source.issue(
+                    LintCode::Syntax,
+                    Severity::Error,
+                    Some(at),
+                    e.message,
+                ));
+                continue;
+            }
+        };
+
+        for &at in parsed.stray_braces() {
+            report.issues.push(source.issue(
+                LintCode::UnbalancedBrace,
+                Severity::Warning,
+                Some(at),
+                "} without a matching {; it will appear as written".into(),
+            ));
+        }
+
+        for &at in parsed.double_braces() {
+            report.issues.push(source.issue(
+                LintCode::DoubleBraces,
+                Severity::Warning,
+                Some(at),
+                "{{...}} is read as {...}; use single braces".into(),
+            ));
+        }
+
+        let mut names: Vec<String> = Vec::new();
+        for (name, at) in parsed.placeholders() {
+            if !data.headers.is_empty() && !known.contains(&name.as_str()) {
+                let mut issue = source.issue(
+                    LintCode::UnknownPlaceholder,
+                    Severity::Error,
+                    Some(at),
+                    format!("No column or placeholder named '{}'", name),
+                );
+                issue.suggestion = closest(&name, known.iter().copied()).map(str::to_string);
+                report.issues.push(issue);
+            }
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+
+        for (name, at) in parsed.unknown_transforms() {
+            // Only a near miss is likely a typo; `{first name|there}` is a default.
+            let Some(suggestion) = closest(&name, transforms::TRANSFORMS.iter().map(|t| t.name))
+            else {
+                continue;
+            };
+            let mut issue = source.issue(
+                LintCode::UnknownTransform,
+                Severity::Warning,
+                Some(at),
+                format!(
+                    "'{}' is not a transform, so it is used as default text; quote it if that is meant",
+                    name
+                ),
+            );
+            issue.suggestion = Some(suggestion.to_string());
+            report.issues.push(issue);
+        }
+
+        let mut empty = vec![0; names.len()];
+        for row in &data.rows {
+            let resolver = Resolver::with_resolved(&data.headers, row, &resolved);
+            for name in parsed.render(&resolver).empty {
+                if let Some(index) = names.iter().position(|n| *n == name) {
+                    empty[index] += 1;
+                }
+            }
+        }
+        report
+            .placeholders
+            .extend(
+                names
+                    .into_iter()
+                    .zip(empty)
+                    .map(|(placeholder, empty)| PlaceholderCount {
+                        template: source.template.map(|t| t.name.clone()),
+                        template_id: source.template.map(|t| t.id.clone()),
+                        placeholder,
+                        empty,
+                    }),
+            );
+
+        if let Some(template) = source.template {
+            if template.manual_only && !request.overrides.values().any(|id| *id == template.id) {
+                report.issues.push(source.issue(
+                    LintCode::UnusedTemplate,
+                    Severity::Warning,
+                    None,
+                    format!(
+                        "Template '{}' is manual-only and no override uses it, so no one will get it",
+                        template.name
+                    ),
+                ));
+            }
+        }
+    }
+
+    report.errors = report
+        .issues
+        .iter()
+        .filter(|issue| issue.severity == Severity::Error)
+        .count();
+    report.warnings = report.issues.len() - report.errors;
+    report
+}
+
+/// The candidate nearest to `name` by edit distance, if it is near enough
+/// to be a likely typo.
+fn closest<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let limit = (name.chars().count() / 3).max(2);
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance, counting an adjacent swap as one edit.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // rows[i][j] is the distance between a[..i] and b[..j].
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
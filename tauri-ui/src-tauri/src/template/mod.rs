@@ -0,0 +1,305 @@
+//! The template language shared by subjects and bodies, e.g.
+//! `Hi {first name}, I saw your work at {firm|your firm}.`
+//!
+//! `{name}` is replaced by the placeholder's value; see `Resolver` for
+//! which names exist. `{name|text}` uses `text` when the value is blank.
+//! A bare word after `|` names a transform from `transforms` instead, as
+//! in `{first name|title}` or `{notes|truncate:80}`; quote a word to use
+//! it as a default, as in `{school|"Duke"}`. Segments apply left to right.
+//! `{#if name}...{/if}` keeps its contents only when `name` is not blank,
+//! and `{#unless name}...{/unless}` only when it is. Blocks nest. A `}`
+//! outside a tag is plain text.
+//!
+//! Templates are rendered here rather than in the engine so preview and
+//! generate produce the same text.
+
+mod html;
+pub mod lint;
+mod parse;
+mod resolver;
+pub mod transforms;
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+
+pub use self::html::wrap_in_html;
+pub use self::parse::Position;
+pub use self::resolver::{Resolver, DERIVED_PLACEHOLDERS};
+
+use self::parse::{BlockKind, Node, Pipe};
+use crate::data;
+use crate::engine::EngineError;
+use crate::model::{
+    Annotated, ColumnRoles, Dataset, Part, PartKind, Rendered, RenderedRow, Template,
+};
+
+/// A template that failed to parse.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TemplateError {
+    pub message: String,
+    /// 1-based line of the problem.
+    pub line: usize,
+    /// 1-based character position in that line.
+    pub column: usize,
+}
+
+impl TemplateError {
+    fn new(at: Position, message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            line: at.line,
+            column: at.column,
+        }
+    }
+
+    /// This error as an `EngineError` naming the template it came from;
+    /// `None` stands for the subject line.
+    pub fn in_template(self, template: Option<&str>) -> EngineError {
+        EngineError::InvalidTemplate {
+            template: template.map(str::to_string),
+            message: self.message,
+            line: self.line,
+            column: self.column,
+        }
+    }
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// A parsed template, ready to render rows.
+#[derive(Debug, Clone)]
+pub struct TemplateText {
+    nodes: Vec<Node>,
+    stray_braces: Vec<Position>,
+    double_braces: Vec<Position>,
+}
+
+impl TemplateText {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let parsed = parse::parse(source)?;
+        Ok(Self {
+            nodes: parsed.nodes,
+            stray_braces: parsed.stray_braces,
+            double_braces: parsed.double_braces,
+        })
+    }
+
+    /// Where a `}` appears outside any tag; see `parse::Parsed`.
+    pub fn stray_braces(&self) -> &[Position] {
+        &self.stray_braces
+    }
+
+    /// Where a tag is written `{{...}}`; see `parse::Parsed`.
+    pub fn double_braces(&self) -> &[Position] {
+        &self.double_braces
+    }
+
+    /// Every placeholder the template reads, block conditions included,
+    /// with where each use is.
+    pub fn placeholders(&self) -> Vec<(String, Position)> {
+        let mut found = Vec::new();
+        collect_placeholders(&self.nodes, &mut found);
+        found
+    }
+
+    pub fn render(&self, resolver: &Resolver) -> Rendered {
+        let mut rendered = Rendered::default();
+        render_nodes(&self.nodes, resolver, &mut rendered);
+        rendered
+    }
+
+    /// Like `render`, keeping which text came from which placeholder.
+    pub fn annotate(&self, resolver: &Resolver) -> Annotated {
+        let mut annotated = Annotated::default();
+        render_nodes(&self.nodes, resolver, &mut annotated);
+        annotated
+    }
+
+    /// Bare words after `|` that name no transform, with where each is.
+    pub fn unknown_transforms(&self) -> Vec<(String, Position)> {
+        let mut found = Vec::new();
+        collect_unknown(&self.nodes, &mut found);
+        found
+    }
+}
+
+fn collect_placeholders(nodes: &[Node], found: &mut Vec<(String, Position)>) {
+    for node in nodes {
+        match node {
+            Node::Placeholder { name, at, .. } => found.push((name.clone(), *at)),
+            Node::Block { name, body, at, .. } => {
+                found.push((name.clone(), *at));
+                collect_placeholders(body, found);
+            }
+            Node::Text(_) => {}
+        }
+    }
+}
+
+fn collect_unknown(nodes: &[Node], found: &mut Vec<(String, Position)>) {
+    for node in nodes {
+        match node {
+            Node::Placeholder { pipes, .. } => {
+                for pipe in pipes {
+                    if let Pipe::Unknown { name, at, .. } = pipe {
+                        found.push((name.clone(), *at));
+                    }
+                }
+            }
+            Node::Block { body, .. } => collect_unknown(body, found),
+            Node::Text(_) => {}
+        }
+    }
+}
+
+/// Apply `pipes` to `value`, saying whether a default was used.
+/// `PartKind::Empty` if the result is blank with no default to fall back
+/// on.
+fn apply_pipes(mut value: String, pipes: &[Pipe]) -> (String, PartKind) {
+    let mut has_default = false;
+    let mut defaulted = false;
+    for pipe in pipes {
+        match pipe {
+            Pipe::Transform { transform, count } => {
+                if !value.is_empty() {
+                    value = transform.apply(&value, *count);
+                }
+            }
+            Pipe::Default(text) | Pipe::Unknown { text, .. } => {
+                has_default = true;
+                if value.trim().is_empty() {
+                    value = text.clone();
+                    defaulted = true;
+                }
+            }
+        }
+    }
+    let kind = if defaulted {
+        PartKind::Default
+    } else if has_default || !value.is_empty() {
+        PartKind::Value
+    } else {
+        PartKind::Empty
+    };
+    (value, kind)
+}
+
+/// Where `render_nodes` writes.
+trait Output {
+    fn text(&mut self, text: &str);
+    fn placeholder(&mut self, name: &str, value: String, kind: PartKind);
+}
+
+impl Output for Rendered {
+    fn text(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    fn placeholder(&mut self, name: &str, value: String, kind: PartKind) {
+        if kind != PartKind::Empty {
+            self.text.push_str(&value);
+        } else if !self.empty.iter().any(|empty| empty == name) {
+            self.empty.push(name.to_string());
+        }
+    }
+}
+
+impl Output for Annotated {
+    fn text(&mut self, text: &str) {
+        self.text.push_str(text);
+        match self.parts.last_mut() {
+            // Text on both sides of a skipped block is one part.
+            Some(last) if last.kind == PartKind::Text => last.text.push_str(text),
+            _ => self.parts.push(Part {
+                kind: PartKind::Text,
+                text: text.to_string(),
+                placeholder: None,
+            }),
+        }
+    }
+
+    fn placeholder(&mut self, name: &str, value: String, kind: PartKind) {
+        self.text.push_str(&value);
+        self.parts.push(Part {
+            kind,
+            text: value,
+            placeholder: Some(name.to_string()),
+        });
+    }
+}
+
+fn render_nodes(nodes: &[Node], resolver: &Resolver, out: &mut impl Output) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.text(text),
+            Node::Placeholder { name, pipes, .. } => {
+                let (value, kind) = apply_pipes(resolver.value(name), pipes);
+                out.placeholder(name, value, kind);
+            }
+            Node::Block {
+                kind, name, body, ..
+            } => {
+                let present = !resolver.value(name).is_empty();
+                if present == (*kind == BlockKind::If) {
+                    render_nodes(body, resolver, out);
+                }
+            }
+        }
+    }
+}
+
+/// Render `subject` and every template for each row of `data`.
+///
+/// Fails on the first template that doesn't parse, before any row is
+/// rendered.
+pub fn render_rows(
+    data: &Dataset,
+    subject: &str,
+    templates: &[Template],
+    roles: &ColumnRoles,
+) -> Result<Vec<RenderedRow>, EngineError> {
+    let subject = TemplateText::parse(subject).map_err(|e| e.in_template(None))?;
+    let bodies = parse_templates(templates)?;
+    let resolved = data::validate_roles(roles, &data.headers).resolved;
+
+    Ok(data
+        .rows
+        .iter()
+        .map(|row| {
+            let resolver = Resolver::with_resolved(&data.headers, row, &resolved);
+            RenderedRow {
+                subject: subject.render(&resolver),
+                bodies: bodies
+                    .iter()
+                    .map(|(id, body)| (id.to_string(), body.render(&resolver)))
+                    .collect(),
+            }
+        })
+        .collect())
+}
+
+/// Parse every template's text, keyed by template id.
+pub fn parse_templates(
+    templates: &[Template],
+) -> Result<BTreeMap<&str, TemplateText>, EngineError> {
+    templates
+        .iter()
+        .map(|template| {
+            TemplateText::parse(&template.text)
+                .map(|text| (template.id.as_str(), text))
+                .map_err(|e| e.in_template(Some(&template.name)))
+        })
+        .collect()
+}
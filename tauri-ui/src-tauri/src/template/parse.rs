@@ -0,0 +1,341 @@
+//! Parser for template text: literal text, `{placeholder|pipe|...}` tags,
+//! and `{#if}`/`{#unless}` blocks. The older `{{placeholder}}` form is read
+//! as `{placeholder}`.
+
+use super::transforms::{self, Arg, Transform};
+use super::TemplateError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockKind {
+    If,
+    Unless,
+}
+
+impl BlockKind {
+    fn named(name: &str) -> Option<Self> {
+        match name {
+            "if" => Some(Self::If),
+            "unless" => Some(Self::Unless),
+            _ => None,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            BlockKind::If => "if",
+            BlockKind::Unless => "unless",
+        }
+    }
+}
+
+/// A 1-based line and column in the template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+/// One `|` segment of a placeholder, applied left to right.
+#[derive(Debug, Clone)]
+pub enum Pipe {
+    /// Text to use if the value so far is blank.
+    Default(String),
+    Transform {
+        transform: &'static Transform,
+        /// The `:` argument, or 0 if the transform takes none.
+        count: usize,
+    },
+    /// A bare word that names no transform. `text`, the segment as
+    /// written, renders as a default, but the word is probably a typo.
+    Unknown {
+        name: String,
+        text: String,
+        at: Position,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum Node {
+    Text(String),
+    Placeholder {
+        /// Lowercased and trimmed.
+        name: String,
+        pipes: Vec<Pipe>,
+        at: Position,
+    },
+    Block {
+        kind: BlockKind,
+        /// The placeholder tested, lowercased and trimmed.
+        name: String,
+        body: Vec<Node>,
+        at: Position,
+    },
+}
+
+/// A block that has been opened but not yet closed.
+struct Open {
+    kind: BlockKind,
+    name: String,
+    at: Position,
+    /// Nodes before the block, to return to when it closes.
+    outer: Vec<Node>,
+}
+
+/// A parsed template.
+#[derive(Debug, Clone)]
+pub struct Parsed {
+    pub nodes: Vec<Node>,
+    /// Where a `}` appears outside any tag. It is kept as text, but is
+    /// usually half of a mistyped tag.
+    pub stray_braces: Vec<Position>,
+    /// Where a tag is written `{{...}}`. It is read as `{...}`.
+    pub double_braces: Vec<Position>,
+}
+
+pub fn parse(source: &str) -> Result<Parsed, TemplateError> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut stray_braces = Vec::new();
+    let mut double_braces = Vec::new();
+    let mut nodes = Vec::new();
+    let mut open: Vec<Open> = Vec::new();
+    let mut text = String::new();
+    let mut here = Position { line: 1, column: 1 };
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] != '{' {
+            if chars[i] == '}' {
+                stray_braces.push(here);
+            }
+            advance(&mut here, chars[i]);
+            text.push(chars[i]);
+            i += 1;
+            continue;
+        }
+
+        let at = here;
+        let double = chars.get(i + 1) == Some(&'{');
+        let start = i + 1 + usize::from(double);
+        let Some(len) = chars[start..].iter().position(|&c| c == '{' || c == '}') else {
+            return Err(TemplateError::new(at, "Missing closing }"));
+        };
+        let end = start + len;
+        if chars[end] == '{' {
+            return Err(TemplateError::new(at, "Missing closing }"));
+        }
+        let close = if double {
+            if chars.get(end + 1) != Some(&'}') {
+                let tag: String = chars[start..end].iter().collect();
+                return Err(TemplateError::new(
+                    at,
+                    format!("Double braces are not supported; use {{{}}}", tag.trim()),
+                ));
+            }
+            double_braces.push(at);
+            2
+        } else {
+            1
+        };
+        let raw = &chars[start..end];
+        let tag: String = raw.iter().collect();
+        for &c in &chars[i..end + close] {
+            advance(&mut here, c);
+        }
+        i = end + close;
+
+        if !text.is_empty() {
+            nodes.push(Node::Text(std::mem::take(&mut text)));
+        }
+
+        let tag = tag.trim();
+        if let Some(directive) = tag.strip_prefix('#') {
+            let (word, name) = split_word(directive);
+            let kind = BlockKind::named(&word.to_lowercase()).ok_or_else(|| {
+                TemplateError::new(at, format!("Unknown block '#{}'; use #if or #unless", word))
+            })?;
+            if name.is_empty() {
+                return Err(TemplateError::new(
+                    at,
+                    format!("Missing placeholder after #{}", kind.as_str()),
+                ));
+            }
+            open.push(Open {
+                kind,
+                name: name.to_lowercase(),
+                at,
+                outer: std::mem::take(&mut nodes),
+            });
+        } else if let Some(closing) = tag.strip_prefix('/') {
+            let closing = closing.trim().to_lowercase();
+            let Some(block) = open.pop() else {
+                return Err(TemplateError::new(
+                    at,
+                    format!("{{/{}}} has no matching block", closing),
+                ));
+            };
+            if closing != block.kind.as_str() {
+                return Err(TemplateError::new(
+                    at,
+                    format!(
+                        "{{/{}}} closes {{#{} {}}} from line {}, column {}",
+                        closing,
+                        block.kind.as_str(),
+                        block.name,
+                        block.at.line,
+                        block.at.column
+                    ),
+                ));
+            }
+            let body = std::mem::replace(&mut nodes, block.outer);
+            nodes.push(Node::Block {
+                kind: block.kind,
+                name: block.name,
+                body,
+                at: block.at,
+            });
+        } else {
+            let mut inner = at;
+            // Past the opening brace, or both of them.
+            for _ in 0..close {
+                advance(&mut inner, '{');
+            }
+            nodes.push(placeholder(raw, inner, at)?);
+        }
+    }
+
+    if let Some(block) = open.pop() {
+        return Err(TemplateError::new(
+            block.at,
+            format!(
+                "{{#{} {}}} is never closed; add {{/{}}}",
+                block.kind.as_str(),
+                block.name,
+                block.kind.as_str()
+            ),
+        ));
+    }
+    if !text.is_empty() {
+        nodes.push(Node::Text(text));
+    }
+    Ok(Parsed {
+        nodes,
+        stray_braces,
+        double_braces,
+    })
+}
+
+/// Parse the inside of a `{name|pipe|...}` tag that starts at `inner`.
+fn placeholder(raw: &[char], inner: Position, at: Position) -> Result<Node, TemplateError> {
+    let mut segments = split_pipes(raw, inner).into_iter();
+    let name = segments.next().map(|(name, _)| name).unwrap_or_default();
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(TemplateError::new(at, "Missing placeholder name"));
+    }
+
+    let mut pipes = Vec::new();
+    for (segment, position) in segments {
+        let segment = segment.trim();
+        if let Some(quoted) = segment
+            .strip_prefix('"')
+            .and_then(|rest| rest.strip_suffix('"'))
+        {
+            pipes.push(Pipe::Default(quoted.to_string()));
+            continue;
+        }
+        let Some((word, arg)) = transform_call(segment) else {
+            pipes.push(Pipe::Default(segment.to_string()));
+            continue;
+        };
+        let Some(transform) = transforms::lookup(word) else {
+            pipes.push(Pipe::Unknown {
+                name: word.to_lowercase(),
+                text: segment.to_string(),
+                at: position,
+            });
+            continue;
+        };
+        let count = match (transform.arg, arg) {
+            (Arg::None, None) => 0,
+            (Arg::None, Some(_)) => {
+                return Err(TemplateError::new(
+                    position,
+                    format!("{} takes no argument", transform.name),
+                ))
+            }
+            (Arg::Count, arg) => arg
+                .and_then(|arg| arg.trim().parse::<usize>().ok())
+                .filter(|count| *count > 0)
+                .ok_or_else(|| {
+                    TemplateError::new(
+                        position,
+                        format!(
+                            "{} needs a length, e.g. {}:80",
+                            transform.name, transform.name
+                        ),
+                    )
+                })?,
+        };
+        pipes.push(Pipe::Transform { transform, count });
+    }
+
+    Ok(Node::Placeholder {
+        name: name.to_lowercase(),
+        pipes,
+        at,
+    })
+}
+
+/// Split a tag on `|` outside double quotes, with where each piece starts.
+fn split_pipes(raw: &[char], mut here: Position) -> Vec<(String, Position)> {
+    let mut segments = vec![(String::new(), here)];
+    let mut quoted = false;
+    for &c in raw {
+        advance(&mut here, c);
+        if c == '|' && !quoted {
+            segments.push((String::new(), here));
+            continue;
+        }
+        if c == '"' {
+            quoted = !quoted;
+        }
+        if let Some((segment, start)) = segments.last_mut() {
+            // Point at the first non-blank character of the segment.
+            if segment.trim().is_empty() && c.is_whitespace() {
+                *start = here;
+            }
+            segment.push(c);
+        }
+    }
+    segments
+}
+
+/// `name` or `name:arg`, where `name` is a bare word such as `truncate`.
+fn transform_call(segment: &str) -> Option<(&str, Option<&str>)> {
+    let (word, arg) = match segment.split_once(':') {
+        Some((word, arg)) => (word, Some(arg)),
+        None => (segment, None),
+    };
+    let mut chars = word.chars();
+    let bare = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    bare.then_some((word, arg))
+}
+
+fn advance(position: &mut Position, c: char) {
+    if c == '\n' {
+        position.line += 1;
+        position.column = 1;
+    } else {
+        position.column += 1;
+    }
+}
+
+/// The first word of `text` and the trimmed rest.
+fn split_word(text: &str) -> (&str, &str) {
+    let text = text.trim_start();
+    match text.find(char::is_whitespace) {
+        Some(end) => (&text[..end], text[end..].trim()),
+        None => (text, ""),
+    }
+}
@@ -0,0 +1,116 @@
+//! Placeholder values for one row, as the engine's `PlaceholderResolver`
+//! computes them.
+
+use std::collections::HashMap;
+
+use crate::data;
+use crate::model::{ColumnRoles, Row};
+
+/// Placeholders computed from the role columns rather than read from a
+/// header of the same name.
+pub const DERIVED_PLACEHOLDERS: &[&str] = &[
+    "first name",
+    "last name",
+    "full name",
+    "firm",
+    "firm name",
+    "school",
+];
+
+/// Looks up placeholders in one row.
+///
+/// `first name`, `last name`, `full name`, `firm`, `firm name` and
+/// `school` are derived from the columns the roles resolve to; any other
+/// name is looked up as a header. Unknown names are blank.
+pub struct Resolver<'a> {
+    headers: &'a [String],
+    row: &'a Row,
+    derived: HashMap<&'static str, String>,
+}
+
+impl<'a> Resolver<'a> {
+    /// `roles` are resolved against `headers` here; pass the same roles
+    /// the engine gets.
+    pub fn new(headers: &'a [String], row: &'a Row, roles: &ColumnRoles) -> Self {
+        let resolved = data::validate_roles(roles, headers).resolved;
+        Self::with_resolved(headers, row, &resolved)
+    }
+
+    /// Like `new`, with roles already resolved by `data::validate_roles`.
+    pub fn with_resolved(headers: &'a [String], row: &'a Row, resolved: &ColumnRoles) -> Self {
+        let cell = |header: &Option<String>| {
+            header
+                .as_ref()
+                .and_then(|header| row.get(header))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+
+        let mut full_name = cell(&resolved.name);
+        let (mut first, mut last) = parse_name(&full_name);
+        if full_name.is_empty() && headers.iter().any(|h| h == "name") {
+            let fallback = cell(&Some("name".into()));
+            if !fallback.is_empty() {
+                if first.is_empty() && last.is_empty() {
+                    (first, last) = parse_name(&fallback);
+                }
+                full_name = fallback;
+            }
+        }
+
+        if headers.iter().any(|h| h == "prefix") {
+            let prefix = cell(&Some("prefix".into()));
+            if !prefix.is_empty() {
+                first = if last.is_empty() {
+                    format!("{}.", prefix)
+                } else {
+                    format!("{}. {}", prefix, last)
+                };
+            }
+        }
+
+        let firm = cell(&resolved.firm);
+        let derived = HashMap::from([
+            ("first name", first),
+            ("last name", last),
+            ("full name", full_name),
+            ("firm name", firm.clone()),
+            ("firm", firm),
+            ("school", cell(&resolved.school)),
+        ]);
+        Self {
+            headers,
+            row,
+            derived,
+        }
+    }
+
+    /// The trimmed value of placeholder `name`, which must be lowercased.
+    pub fn value(&self, name: &str) -> String {
+        if let Some(value) = self.derived.get(name) {
+            return value.clone();
+        }
+        if self.headers.iter().any(|h| h == name) {
+            return self
+                .row
+                .get(name)
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default();
+        }
+        String::new()
+    }
+}
+
+/// Split a full name into first and last, reading "Last, First" too.
+fn parse_name(full_name: &str) -> (String, String) {
+    let full_name = full_name.trim();
+    if let Some((last, first)) = full_name.split_once(',') {
+        return (first.trim().to_string(), last.trim().to_string());
+    }
+    let parts: Vec<&str> = full_name.split_whitespace().collect();
+    match parts.as_slice() {
+        [] => (String::new(), String::new()),
+        [only] => (only.to_string(), String::new()),
+        [first, .., last] => (first.to_string(), last.to_string()),
+    }
+}
@@ -0,0 +1,149 @@
+//! The registry of transforms placeholders can be piped through, as in
+//! `{first name|title}` or `{notes|truncate:80}`.
+
+/// What follows a transform's name after a `:`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arg {
+    None,
+    /// A whole number of at least 1.
+    Count,
+}
+
+#[derive(Debug)]
+pub struct Transform {
+    pub name: &'static str,
+    pub arg: Arg,
+    /// Applied to non-blank values only; the count is 0 for `Arg::None`.
+    apply: fn(&str, usize) -> String,
+}
+
+impl Transform {
+    pub fn apply(&self, value: &str, count: usize) -> String {
+        (self.apply)(value, count)
+    }
+}
+
+pub const TRANSFORMS: &[Transform] = &[
+    Transform {
+        name: "title",
+        arg: Arg::None,
+        apply: title,
+    },
+    Transform {
+        name: "upper",
+        arg: Arg::None,
+        apply: |value, _| value.to_uppercase(),
+    },
+    Transform {
+        name: "lower",
+        arg: Arg::None,
+        apply: |value, _| value.to_lowercase(),
+    },
+    Transform {
+        name: "strip_suffix",
+        arg: Arg::None,
+        apply: strip_suffix,
+    },
+    Transform {
+        name: "truncate",
+        arg: Arg::Count,
+        apply: truncate,
+    },
+    Transform {
+        name: "initials",
+        arg: Arg::None,
+        apply: initials,
+    },
+];
+
+/// The transform called `name`, ignoring case.
+pub fn lookup(name: &str) -> Option<&'static Transform> {
+    TRANSFORMS
+        .iter()
+        .find(|transform| transform.name.eq_ignore_ascii_case(name))
+}
+
+/// Company suffixes `strip_suffix` removes, compared without dots and
+/// case.
+const COMPANY_SUFFIXES: &[&str] = &[
+    "llc",
+    "llp",
+    "lp",
+    "inc",
+    "incorporated",
+    "corp",
+    "corporation",
+    "co",
+    "ltd",
+    "limited",
+    "plc",
+    "gmbh",
+    "ag",
+    "sa",
+    "nv",
+    "bv",
+    "pty",
+];
+
+fn title(value: &str, _: usize) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut word_start = true;
+    for c in value.chars() {
+        if word_start {
+            out.extend(c.to_uppercase());
+        } else {
+            out.extend(c.to_lowercase());
+        }
+        word_start = !c.is_alphanumeric();
+    }
+    out
+}
+
+fn strip_suffix(value: &str, _: usize) -> String {
+    let mut value = value.trim();
+    loop {
+        let trimmed = value.trim_end_matches([',', '&', ' ']);
+        let (rest, last) = match trimmed.rfind(' ') {
+            Some(space) => (&trimmed[..space], &trimmed[space + 1..]),
+            None => break,
+        };
+        let bare: String = last
+            .chars()
+            .filter(|c| *c != '.')
+            .collect::<String>()
+            .to_lowercase();
+        if !COMPANY_SUFFIXES.contains(&bare.as_str()) {
+            value = trimmed;
+            break;
+        }
+        value = rest;
+    }
+    value.trim_end_matches([',', '&', ' ']).to_string()
+}
+
+fn truncate(value: &str, max: usize) -> String {
+    if value.chars().count() <= max {
+        return value.to_string();
+    }
+    let kept: String = value.chars().take(max.saturating_sub(1)).collect();
+    let next = value.chars().nth(kept.chars().count());
+    let cut = match kept.rfind(char::is_whitespace) {
+        // Drop the partial word at the end, unless it is a whole word.
+        Some(space) if space > 0 && !next.is_some_and(char::is_whitespace) => &kept[..space],
+        _ => &kept,
+    };
+    format!("{}…", cut.trim_end())
+}
+
+fn initials(value: &str, _: usize) -> String {
+    // "Lovelace, Ada" is read as "Ada Lovelace", as for first/last names.
+    let ordered = match value.split_once(',') {
+        Some((last, first)) => format!("{} {}", first, last),
+        None => value.to_string(),
+    };
+    ordered
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter_map(|word| word.chars().find(|c| c.is_alphanumeric()))
+        .flat_map(|c| c.to_uppercase().chain(['.']))
+        .collect()
+}
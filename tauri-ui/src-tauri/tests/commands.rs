@@ -0,0 +1,693 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use draftmate_lib::bridge::{self, CancelReport, GenerateEvent};
+use draftmate_lib::engine::{
+    EngineError, GenerateProgress, GenerateSummary, JobManager, MockBackend,
+};
+use draftmate_lib::model::{
+    DataLoadResult, Dataset, DedupPolicy, DuplicateRecipient, FindingCode, GenerateRequest,
+    GenerateResult, LicenseResult, PartKind, RenderRequest, Severity, SheetTab, Template,
+    ValidateRequest, WriteBack,
+};
+use serde_json::json;
+
+fn template() -> Template {
+    Template {
+        id: "tpl_1".into(),
+        name: "Default".into(),
+        text: "Dear {first name},".into(),
+        manual_only: false,
+    }
+}
+
+fn generate_request() -> GenerateRequest {
+    GenerateRequest {
+        data: Dataset::default(),
+        templates: vec![template()],
+        overrides: Default::default(),
+        subject: "{first name} - Networking Request".into(),
+        resume_path: None,
+        dry_run: true,
+        roles: Default::default(),
+        dedup: Default::default(),
+        filter: None,
+        write_back: None,
+        rendered: Vec::new(),
+    }
+}
+
+/// Owned copies of the events `bridge::generate` reports.
+#[derive(Debug)]
+enum Recorded {
+    Progress(GenerateProgress),
+    Complete(GenerateSummary),
+}
+
+fn record(events: &mut Vec<Recorded>, event: GenerateEvent<'_>) {
+    events.push(match event {
+        GenerateEvent::Progress(progress) => Recorded::Progress(progress.clone()),
+        GenerateEvent::Complete(summary) => Recorded::Complete(summary.clone()),
+    });
+}
+
+#[tokio::test]
+async fn load_sheet_returns_scripted_rows_and_releases_the_job() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+    let url = "https://docs.google.com/spreadsheets/d/abc123/edit#gid=0";
+
+    let loaded = DataLoadResult {
+        rows: vec![[("email".to_string(), "a@example.com".to_string())].into()],
+        headers: vec!["email".into()],
+        count: 1,
+    };
+    backend.reply("load-sheet", &loaded);
+
+    let result = bridge::load_sheet(&backend, &jobs, url, &[]).await.unwrap();
+    assert_eq!(result, loaded);
+
+    let calls = backend.calls();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].command, "load-sheet");
+    assert_eq!(calls[0].input, json!({ "url": url, "tabs": [] }));
+    assert!(jobs.list().is_empty());
+}
+
+#[tokio::test]
+async fn sheet_tabs_are_listed_and_loaded_by_name() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+    let url = "https://docs.google.com/spreadsheets/d/abc123/edit";
+
+    let tabs = vec![
+        SheetTab {
+            name: "Contacts".into(),
+            gid: "0".into(),
+        },
+        SheetTab {
+            name: "Alumni".into(),
+            gid: "1432".into(),
+        },
+    ];
+    backend.reply("list-sheet-tabs", &tabs);
+    backend.reply(
+        "load-sheet",
+        json!({
+            "rows": [
+                { "email": "a@example.com", "source tab": "Contacts" },
+                { "email": "b@example.com", "source tab": "Alumni" },
+            ],
+            "headers": ["email", "source tab"],
+            "count": 2,
+        }),
+    );
+
+    assert_eq!(
+        bridge::list_sheet_tabs(&backend, &jobs, url).await.unwrap(),
+        tabs
+    );
+    let requested = ["Contacts".to_string(), "1432".to_string()];
+    let loaded = bridge::load_sheet(&backend, &jobs, url, &requested)
+        .await
+        .unwrap();
+    assert_eq!(loaded.rows[1]["source tab"], "Alumni");
+
+    let calls = backend.calls();
+    assert_eq!(calls[0].command, "list-sheet-tabs");
+    assert_eq!(calls[1].input["tabs"], json!(["Contacts", "1432"]));
+    assert!(jobs.list().is_empty());
+}
+
+#[tokio::test]
+async fn invalid_parameters_never_reach_the_backend() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+
+    let missing = bridge::read_files(&backend, &jobs, &["/no/such/template.txt".into()]).await;
+    assert!(matches!(missing, Err(EngineError::InvalidArgument { .. })));
+
+    let sheet = bridge::load_sheet(&backend, &jobs, "https://example.com/sheet", &[]).await;
+    assert!(matches!(sheet, Err(EngineError::InvalidArgument { .. })));
+
+    let sheets = "https://docs.google.com/spreadsheets/d/abc123/edit";
+    let tab = bridge::load_sheet(&backend, &jobs, sheets, &["  ".into()]).await;
+    assert!(matches!(tab, Err(EngineError::InvalidArgument { .. })));
+
+    let license = bridge::validate_license(&backend, &jobs, "   ").await;
+    assert!(matches!(license, Err(EngineError::InvalidArgument { .. })));
+
+    let export = bridge::export_templates(&backend, &jobs, &[]).await;
+    assert!(matches!(export, Err(EngineError::InvalidArgument { .. })));
+
+    assert!(backend.calls().is_empty());
+}
+
+#[tokio::test]
+async fn engine_errors_are_passed_through() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+    backend.fail(
+        "validate-license",
+        EngineError::Timeout {
+            command: "validate-license".into(),
+            seconds: 60,
+        },
+    );
+
+    let result = bridge::validate_license(&backend, &jobs, "KEY-123").await;
+    assert_eq!(
+        result,
+        Err(EngineError::Timeout {
+            command: "validate-license".into(),
+            seconds: 60,
+        })
+    );
+
+    // Nothing else was scripted.
+    let result = bridge::validate_license(&backend, &jobs, "KEY-123").await;
+    assert!(matches!(result, Err(EngineError::Engine { .. })));
+}
+
+#[tokio::test]
+async fn validate_license_returns_the_engine_verdict() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+    backend.reply(
+        "validate-license",
+        json!({ "valid": true, "message": "License active" }),
+    );
+
+    let result = bridge::validate_license(&backend, &jobs, "KEY-123")
+        .await
+        .unwrap();
+    assert_eq!(
+        result,
+        LicenseResult {
+            valid: true,
+            message: "License active".into(),
+        }
+    );
+}
+
+#[tokio::test]
+async fn validate_dataset_returns_findings_with_row_and_column() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+    backend.reply(
+        "validate",
+        json!({
+            "findings": [
+                {
+                    "row": 3,
+                    "column": "email",
+                    "code": "duplicate_email",
+                    "severity": "error",
+                    "message": "a@example.com already appears on row 1",
+                },
+                {
+                    "row": 4,
+                    "column": "firm",
+                    "code": "empty_firm",
+                    "severity": "warning",
+                    "message": "Firm is empty",
+                },
+            ],
+            "errors": 1,
+            "warnings": 1,
+        }),
+    );
+    let request = ValidateRequest {
+        data: Dataset::default(),
+        templates: vec![template()],
+        overrides: Default::default(),
+        subject: "Hello {first name}".into(),
+        roles: Default::default(),
+        dedup: Default::default(),
+        filter: None,
+        rendered: Vec::new(),
+    };
+
+    let report = bridge::validate_dataset(&backend, &jobs, &request)
+        .await
+        .unwrap();
+    assert!(report.has_errors());
+    assert_eq!(report.findings[0].row, 3);
+    assert_eq!(report.findings[0].column.as_deref(), Some("email"));
+    assert_eq!(report.findings[0].code, FindingCode::DuplicateEmail);
+    assert_eq!(report.findings[1].severity, Severity::Warning);
+
+    let calls = backend.calls();
+    assert_eq!(calls[0].command, "validate");
+    assert_eq!(calls[0].input["subject"], "Hello {first name}");
+    assert!(jobs.list().is_empty());
+}
+
+#[tokio::test]
+async fn validate_dataset_sends_only_filtered_rows_and_renumbers_findings() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+    backend.reply(
+        "validate",
+        json!({
+            "findings": [{
+                "row": 2,
+                "column": "firm",
+                "code": "empty_firm",
+                "severity": "warning",
+                "message": "Firm is empty",
+            }],
+            "errors": 0,
+            "warnings": 1,
+        }),
+    );
+    let row = |email: &str, school: &str| {
+        [("email", email), ("school", school)]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    };
+    let request = ValidateRequest {
+        data: Dataset {
+            headers: vec!["email".into(), "school".into()],
+            rows: vec![
+                row("a@example.com", "Wharton"),
+                row("b@example.com", "Stern"),
+                row("c@example.com", "Wharton"),
+            ],
+        },
+        templates: vec![template()],
+        overrides: Default::default(),
+        subject: "Hello".into(),
+        roles: Default::default(),
+        dedup: Default::default(),
+        filter: Some("school == 'wharton'".into()),
+        rendered: Vec::new(),
+    };
+
+    let report = bridge::validate_dataset(&backend, &jobs, &request)
+        .await
+        .unwrap();
+    assert_eq!(report.findings[0].row, 3);
+
+    let calls = backend.calls();
+    let sent = calls[0].input["data"]["rows"].as_array().unwrap();
+    assert_eq!(sent.len(), 2);
+    assert_eq!(sent[1]["email"], "c@example.com");
+}
+
+#[tokio::test]
+async fn an_invalid_filter_fails_before_the_engine_runs() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+    let request = GenerateRequest {
+        filter: Some("firm ==".into()),
+        ..generate_request()
+    };
+
+    let err = bridge::generate(&backend, &jobs, &request, &mut |_| {})
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::InvalidFilter { column: 8, .. }));
+    assert!(backend.calls().is_empty());
+}
+
+#[tokio::test]
+async fn drafted_recipients_are_written_back_to_the_csv() {
+    let path = std::env::temp_dir().join(format!("draftmate-writeback-{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "Email,Generate\na@example.com,Yes\nb@example.com,Yes\n",
+    )
+    .unwrap();
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+    backend.reply_with_templates(
+        "generate",
+        &[
+            ("a@example.com", "created", "Default"),
+            ("b@example.com", "failed", "Default"),
+            ("z@example.com", "created", "Follow-up"),
+        ],
+        json!({ "created": 2 }),
+    );
+    let request = GenerateRequest {
+        dry_run: false,
+        write_back: Some(WriteBack {
+            path: path.display().to_string(),
+            drafted_at: "2024-05-01 14:30".into(),
+        }),
+        ..generate_request()
+    };
+
+    let result = bridge::generate(&backend, &jobs, &request, &mut |_| {})
+        .await
+        .unwrap();
+    let report = result.write_back.unwrap();
+    assert_eq!(report.updated, 1);
+    assert_eq!(report.missing, vec!["z@example.com".to_string()]);
+    assert_eq!(report.error, None);
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "Email,Generate,Drafted At,Template\n\
+         a@example.com,No,2024-05-01 14:30,Default\n\
+         b@example.com,Yes,,\n"
+    );
+    assert_eq!(
+        std::fs::read_to_string(&report.backup).unwrap(),
+        "Email,Generate\na@example.com,Yes\nb@example.com,Yes\n"
+    );
+    let _ = std::fs::remove_file(&path);
+    let _ = std::fs::remove_file(&report.backup);
+}
+
+#[tokio::test]
+async fn write_back_failures_are_reported_without_failing_the_run() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+    backend.reply_with_progress(
+        "generate",
+        &[("a@example.com", "created")],
+        json!({ "created": 1 }),
+    );
+    let request = GenerateRequest {
+        dry_run: false,
+        write_back: Some(WriteBack {
+            path: "/no/such/contacts.csv".into(),
+            drafted_at: "2024-05-01".into(),
+        }),
+        ..generate_request()
+    };
+
+    let result = bridge::generate(&backend, &jobs, &request, &mut |_| {})
+        .await
+        .unwrap();
+    assert_eq!(result.created, 1);
+    let report = result.write_back.unwrap();
+    assert_eq!(report.updated, 0);
+    assert!(report.error.is_some());
+}
+
+#[tokio::test]
+async fn generate_sends_each_row_rendered_and_rejects_broken_templates() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+    backend.reply("generate", json!({ "created": 1 }));
+    let request = GenerateRequest {
+        data: Dataset {
+            headers: vec!["email".into(), "name".into(), "company".into()],
+            rows: vec![[
+                ("email", "a@example.com"),
+                ("name", "Ada King"),
+                ("company", ""),
+            ]
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()],
+        },
+        subject: "{first name} - {firm|your firm}".into(),
+        ..generate_request()
+    };
+
+    bridge::generate(&backend, &jobs, &request, &mut |_| {})
+        .await
+        .unwrap();
+    let rendered = &backend.calls()[0].input["rendered"][0];
+    assert_eq!(rendered["subject"]["text"], "Ada - your firm");
+    assert_eq!(rendered["bodies"]["tpl_1"]["text"], "Dear Ada,");
+
+    let request = GenerateRequest {
+        subject: "{#unless firm}Hi".into(),
+        ..request
+    };
+    let err = bridge::generate(&backend, &jobs, &request, &mut |_| {})
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        EngineError::InvalidTemplate {
+            line: 1,
+            column: 1,
+            ..
+        }
+    ));
+    assert_eq!(backend.calls().len(), 1);
+}
+
+fn preview_row(email: &str, template: &str, row: usize) -> serde_json::Value {
+    json!({
+        "name": "",
+        "email": email,
+        "email_norm": email.to_lowercase(),
+        "firm": "",
+        "template_name": template.to_uppercase(),
+        "template_id": template,
+        "is_manual": false,
+        "is_eligible": true,
+        "row": row,
+    })
+}
+
+#[tokio::test]
+async fn render_previews_render_each_recipients_chosen_template() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+    let rows = [
+        ("a@example.com", "Ada King", "Lazard"),
+        ("b@example.com", "", "Evercore"),
+        ("C@example.com", "Cy Young", ""),
+    ];
+    let request = RenderRequest {
+        data: Dataset {
+            headers: vec!["email".into(), "name".into(), "firm".into()],
+            rows: rows
+                .iter()
+                .map(|(email, name, firm)| {
+                    [("email", email), ("name", name), ("firm", firm)]
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect()
+                })
+                .collect(),
+        },
+        templates: vec![
+            template(),
+            Template {
+                id: "tpl_2".into(),
+                name: "Follow-up".into(),
+                text: "Hi {first name}  at {firm}\n".into(),
+                manual_only: false,
+            },
+        ],
+        overrides: Default::default(),
+        subject: "For {firm|you}".into(),
+        roles: Default::default(),
+        dedup: DedupPolicy::KeepFirst,
+        filter: Some("name".into()),
+    };
+    // Row numbers are in the filtered data the engine is sent. One reply
+    // per render call below.
+    for _ in 0..4 {
+        backend.reply(
+            "preview",
+            json!({
+                "preview_rows": [
+                    preview_row("a@example.com", "tpl_1", 1),
+                    preview_row("C@example.com", "tpl_2", 2),
+                ],
+                "count": 2,
+                "duplicates": [],
+            }),
+        );
+    }
+
+    let previews = bridge::render_previews(&backend, &jobs, &request, &["c@EXAMPLE.com ".into()])
+        .await
+        .unwrap();
+    assert_eq!(previews.len(), 1);
+    let preview = &previews[0];
+    assert_eq!((preview.email.as_str(), preview.row), ("C@example.com", 3));
+    assert_eq!(preview.template_id.as_deref(), Some("tpl_2"));
+    assert_eq!(preview.subject.text, "For you");
+    assert_eq!(preview.subject.parts[1].kind, PartKind::Default);
+    let body = preview.body.as_ref().unwrap();
+    assert_eq!(body.text, "Hi Cy  at \n");
+    assert_eq!(body.parts[3].kind, PartKind::Empty);
+    assert_eq!(
+        preview.html.as_deref(),
+        Some("<html><body style='margin:0;padding:0;font-family:Arial,sans-serif;'>Hi Cy<br><br>at</body></html>")
+    );
+
+    let call = &backend.calls()[0];
+    assert_eq!(call.command, "preview");
+    assert_eq!(call.input["only_recipients"], true);
+    assert_eq!(call.input["data"]["rows"].as_array().unwrap().len(), 2);
+
+    let all = bridge::render_previews(&backend, &jobs, &request, &[])
+        .await
+        .unwrap();
+    let rendered: Vec<(usize, &str)> = all
+        .iter()
+        .map(|preview| (preview.row, preview.subject.text.as_str()))
+        .collect();
+    assert_eq!(rendered, vec![(1, "For Lazard"), (3, "For you")]);
+    assert_eq!(all[0].body.as_ref().unwrap().text, "Dear Ada,");
+
+    let one = bridge::render_preview(&backend, &jobs, &request, "A@example.com")
+        .await
+        .unwrap();
+    assert_eq!(one.row, 1);
+    let err = bridge::render_preview(&backend, &jobs, &request, "b@example.com")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, EngineError::InvalidArgument { .. }));
+    assert!(err.to_string().contains("b@example.com"));
+}
+
+#[tokio::test]
+async fn generate_reports_progress_then_a_summary() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+    backend.reply_with_progress(
+        "generate",
+        &[
+            ("a@example.com", "dry_run"),
+            ("b@example.com", "skipped"),
+            ("c@example.com", "dry_run"),
+        ],
+        json!({
+            "created": 2,
+            "duplicates": [{ "email": "a@example.com", "rows": [1, 4], "kept": 4 }],
+        }),
+    );
+    let request = GenerateRequest {
+        dedup: DedupPolicy::KeepLast,
+        ..generate_request()
+    };
+
+    let mut events = Vec::new();
+    let result = bridge::generate(&backend, &jobs, &request, &mut |event| {
+        record(&mut events, event)
+    })
+    .await
+    .unwrap();
+    assert_eq!(
+        result,
+        GenerateResult {
+            created: 2,
+            duplicates: vec![DuplicateRecipient {
+                email: "a@example.com".into(),
+                rows: vec![1, 4],
+                kept: Some(4),
+            }],
+            write_back: None,
+        }
+    );
+    assert_eq!(backend.calls()[0].input["dedup"], "keep_last");
+
+    assert_eq!(events.len(), 4);
+    for (index, event) in events[..3].iter().enumerate() {
+        match event {
+            Recorded::Progress(progress) => {
+                assert_eq!(progress.index, index);
+                assert_eq!(progress.total, 3);
+            }
+            other => panic!("expected progress, got {:?}", other),
+        }
+    }
+    match &events[3] {
+        Recorded::Complete(summary) => {
+            assert!(summary.success);
+            assert!(!summary.cancelled);
+            assert_eq!(summary.total, 3);
+            assert_eq!(summary.created, 2);
+            assert_eq!(summary.skipped, 1);
+            assert_eq!(summary.error, None);
+        }
+        other => panic!("expected summary, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn failed_generate_still_sends_a_summary() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+    backend.fail(
+        "generate",
+        EngineError::Engine {
+            message: "Outlook is not running".into(),
+        },
+    );
+
+    let mut events = Vec::new();
+    let result = bridge::generate(&backend, &jobs, &generate_request(), &mut |event| {
+        record(&mut events, event)
+    })
+    .await;
+    assert!(matches!(result, Err(EngineError::Engine { .. })));
+
+    match events.as_slice() {
+        [Recorded::Complete(summary)] => {
+            assert!(!summary.success);
+            assert_eq!(summary.error.as_deref(), Some("Outlook is not running"));
+        }
+        other => panic!("expected only a summary, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn cancel_job_stops_a_running_generate() {
+    let backend = Arc::new(MockBackend::new());
+    let jobs = Arc::new(JobManager::new());
+    backend.hang("generate", &[("a@example.com", "created")]);
+
+    let task = {
+        let backend = Arc::clone(&backend);
+        let jobs = Arc::clone(&jobs);
+        tokio::spawn(async move {
+            let mut summary = None;
+            let result =
+                bridge::generate(backend.as_ref(), &jobs, &generate_request(), &mut |event| {
+                    if let GenerateEvent::Complete(s) = event {
+                        summary = Some(s.clone());
+                    }
+                })
+                .await;
+            (result, summary)
+        })
+    };
+
+    let id = loop {
+        if let Some(job) = jobs.list().into_iter().find(|job| job.processed == 1) {
+            break job.id;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    };
+
+    let report = bridge::cancel_job(backend.as_ref(), &jobs, id).unwrap();
+    assert_eq!(report, CancelReport { id, processed: 1 });
+    assert_eq!(backend.interrupted(), vec![id]);
+
+    let (result, summary) = task.await.unwrap();
+    assert_eq!(
+        result,
+        Err(EngineError::Cancelled {
+            job_id: id,
+            processed: 1,
+        })
+    );
+    assert!(summary.unwrap().cancelled);
+    assert!(jobs.list().is_empty());
+}
+
+#[tokio::test]
+async fn cancelling_an_unknown_job_fails() {
+    let backend = MockBackend::new();
+    let jobs = JobManager::new();
+
+    assert_eq!(
+        bridge::cancel_job(&backend, &jobs, 42),
+        Err(EngineError::NoSuchJob { id: 42 })
+    );
+    assert!(backend.interrupted().is_empty());
+}
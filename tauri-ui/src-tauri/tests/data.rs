@@ -0,0 +1,602 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use calamine::{ExcelDateTime, ExcelDateTimeType};
+use draftmate_lib::bridge;
+use draftmate_lib::data::roles::{RoleIssue, RoleProblem};
+use draftmate_lib::data::workbook::format_serial_date;
+use draftmate_lib::data::{
+    decode, diff_rows, join_sources, mark_drafted, parse_csv, sniff_delimiter, suggest_roles,
+    validate_roles, write_back, DataDiff, DataWatcher, Drafted, JoinConflict, UnmatchedRow,
+};
+use draftmate_lib::engine::EngineError;
+use draftmate_lib::model::{
+    ColumnRole, ColumnRoles, Dataset, HeaderMode, JoinRequest, JoinSource, Row,
+};
+
+fn temp_file(name: &str, contents: &[u8]) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("draftmate-test-{}-{}", std::process::id(), name));
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+fn utf16le(text: &str, bom: bool) -> Vec<u8> {
+    let mut bytes = if bom { vec![0xFF, 0xFE] } else { Vec::new() };
+    for unit in text.encode_utf16() {
+        bytes.extend_from_slice(&unit.to_le_bytes());
+    }
+    bytes
+}
+
+#[test]
+fn csv_headers_are_lowercased_and_values_trimmed() {
+    let loaded = parse_csv(b" Email , First Name\na@example.com ,  Ada \n").unwrap();
+
+    assert_eq!(loaded.data.headers, vec!["email", "first name"]);
+    assert_eq!(loaded.data.count, 1);
+    assert_eq!(loaded.data.rows[0]["email"], "a@example.com");
+    assert_eq!(loaded.data.rows[0]["first name"], "Ada");
+    assert_eq!(loaded.encoding, "UTF-8");
+    assert_eq!(loaded.delimiter, ',');
+}
+
+#[test]
+fn short_records_are_padded_and_blank_lines_skipped() {
+    let loaded =
+        parse_csv(b"email,firm,title\na@example.com,Acme\n\nb@example.com,Initech,VP\n").unwrap();
+
+    assert_eq!(loaded.data.count, 2);
+    assert_eq!(loaded.data.rows[0]["title"], "");
+    assert_eq!(loaded.data.rows[1]["title"], "VP");
+}
+
+#[test]
+fn semicolon_tab_and_pipe_delimiters_are_detected() {
+    assert_eq!(
+        sniff_delimiter("email;firm\na@example.com;Acme, Inc.\n"),
+        b';'
+    );
+    assert_eq!(sniff_delimiter("email\tfirm\na@example.com\tAcme\n"), b'\t');
+    assert_eq!(sniff_delimiter("email|firm\na@example.com|Acme\n"), b'|');
+    assert_eq!(sniff_delimiter("email\na@example.com\n"), b',');
+}
+
+#[test]
+fn quoted_delimiters_do_not_count() {
+    let text = "name;firm\n\"Lovelace; Ada\";Acme\n\"Hopper; Grace\";Navy\n";
+    assert_eq!(sniff_delimiter(text), b';');
+
+    let loaded = parse_csv(text.as_bytes()).unwrap();
+    assert_eq!(loaded.data.rows[0]["name"], "Lovelace; Ada");
+}
+
+#[test]
+fn utf16_with_and_without_bom_is_decoded() {
+    let text = "email\tname\na@example.com\tJosé\n";
+
+    let loaded = parse_csv(&utf16le(text, true)).unwrap();
+    assert_eq!(loaded.encoding, "UTF-16LE");
+    assert_eq!(loaded.delimiter, '\t');
+    assert_eq!(loaded.data.rows[0]["name"], "José");
+
+    let (decoded, encoding) = decode(&utf16le(text, false));
+    assert_eq!(encoding.name(), "UTF-16LE");
+    assert_eq!(decoded, text);
+}
+
+#[test]
+fn utf8_bom_is_stripped_from_the_first_header() {
+    let loaded = parse_csv(b"\xEF\xBB\xBFEmail,Name\na@example.com,Ada\n").unwrap();
+    assert_eq!(loaded.data.headers, vec!["email", "name"]);
+    assert_eq!(loaded.encoding, "UTF-8");
+}
+
+#[test]
+fn invalid_utf8_falls_back_to_windows_1252() {
+    // "Société" with é as 0xE9, as Excel on Windows writes it.
+    let loaded = parse_csv(b"email;firm\na@example.com;Soci\xE9t\xE9\n").unwrap();
+    assert_eq!(loaded.encoding, "windows-1252");
+    assert_eq!(loaded.data.rows[0]["firm"], "Société");
+}
+
+#[test]
+fn load_csv_reads_from_disk_and_rejects_missing_files() {
+    let path = temp_file("contacts.csv", b"Email;Firm\na@example.com;Acme\n");
+
+    let loaded = bridge::load_csv(&path.to_string_lossy()).unwrap();
+    assert_eq!(loaded.delimiter, ';');
+    assert_eq!(loaded.data.rows[0]["firm"], "Acme");
+
+    let missing = bridge::load_csv("/no/such/contacts.csv");
+    assert!(matches!(missing, Err(EngineError::InvalidArgument { .. })));
+
+    let _ = fs::remove_file(path);
+}
+
+#[test]
+fn empty_files_load_as_no_rows() {
+    let loaded = parse_csv(b"").unwrap();
+    assert!(loaded.data.headers.is_empty());
+    assert_eq!(loaded.data.count, 0);
+}
+
+fn fixture(name: &str) -> String {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[test]
+fn workbook_sheets_are_listed_in_order() {
+    let sheets = bridge::list_sheets(&fixture("contacts.xlsx")).unwrap();
+    assert_eq!(sheets, vec!["Contacts", "Archive"]);
+}
+
+#[test]
+fn workbook_cells_are_normalized_like_csv() {
+    let loaded = bridge::load_workbook(&fixture("contacts.xlsx"), None).unwrap();
+
+    assert_eq!(
+        loaded.headers,
+        vec!["email", "first name", "phone", "start date", "active"]
+    );
+    // The blank row between the two contacts is skipped.
+    assert_eq!(loaded.count, 2);
+
+    let ada = &loaded.rows[0];
+    assert_eq!(ada["first name"], "Ada");
+    assert_eq!(ada["phone"], "5551234");
+    assert_eq!(ada["start date"], "2023-03-15");
+    assert_eq!(ada["active"], "TRUE");
+
+    let grace = &loaded.rows[1];
+    assert_eq!(grace["phone"], "2.5");
+    assert_eq!(grace["start date"], "2023-03-15 12:00:00");
+    assert_eq!(grace["active"], "FALSE");
+}
+
+#[test]
+fn workbook_sheets_can_be_chosen_by_name() {
+    let path = fixture("contacts.xlsx");
+
+    let archive = bridge::load_workbook(&path, Some("Archive")).unwrap();
+    assert_eq!(archive.rows[0]["email"], "old@example.com");
+
+    let missing = bridge::load_workbook(&path, Some("Nope"));
+    assert!(matches!(missing, Err(EngineError::InvalidArgument { .. })));
+}
+
+fn serial_date(serial: f64, is_1904: bool) -> String {
+    format_serial_date(&ExcelDateTime::new(
+        serial,
+        ExcelDateTimeType::DateTime,
+        is_1904,
+    ))
+}
+
+#[test]
+fn excel_serial_dates_follow_the_1900_date_system() {
+    assert_eq!(serial_date(1.0, false), "1900-01-01");
+    assert_eq!(serial_date(59.0, false), "1900-02-28");
+    assert_eq!(serial_date(61.0, false), "1900-03-01");
+    assert_eq!(serial_date(36526.25, false), "2000-01-01 06:00:00");
+}
+
+#[test]
+fn excel_serial_dates_follow_the_1904_date_system() {
+    assert_eq!(serial_date(0.0, true), "1904-01-01");
+    assert_eq!(serial_date(43538.0, true), "2023-03-15");
+
+    // Saved on a Mac, where workbooks count from 1904.
+    let loaded = bridge::load_workbook(&fixture("contacts-1904.xlsx"), None).unwrap();
+    assert_eq!(loaded.rows[0]["start date"], "2023-03-15");
+    assert_eq!(loaded.rows[1]["start date"], "2023-03-15 12:00:00");
+}
+
+#[test]
+fn non_workbook_files_are_rejected() {
+    let path = temp_file("contacts.txt", b"email\n");
+    let result = bridge::load_workbook(&path.to_string_lossy(), None);
+    assert!(matches!(result, Err(EngineError::InvalidArgument { .. })));
+    let _ = fs::remove_file(path);
+}
+
+fn row(cells: &[(&str, &str)]) -> Row {
+    cells
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+#[test]
+fn diff_reports_added_removed_and_changed_emails() {
+    let old = vec![
+        row(&[("email", "a@example.com"), ("firm", "Acme")]),
+        row(&[("email", "b@example.com"), ("firm", "Initech")]),
+        row(&[("email", "c@example.com"), ("firm", "Globex")]),
+    ];
+    let new = vec![
+        row(&[("email", "A@Example.com"), ("firm", "Acme")]),
+        row(&[("email", "c@example.com"), ("firm", "Globex Corp")]),
+        row(&[("email", "d@example.com"), ("firm", "Hooli")]),
+    ];
+
+    let diff = diff_rows(&old, &new, "email");
+    assert_eq!(diff.added, vec!["d@example.com"]);
+    assert_eq!(diff.removed, vec!["b@example.com"]);
+    assert_eq!(diff.changed, vec!["a@example.com", "c@example.com"]);
+}
+
+#[test]
+fn diff_ignores_rows_without_an_email() {
+    let old = vec![row(&[("email", ""), ("firm", "Acme")])];
+    let new = vec![row(&[("email", ""), ("firm", "Initech")])];
+    assert!(diff_rows(&old, &new, "email").is_empty());
+}
+
+#[test]
+fn diff_keys_rows_on_the_given_email_column() {
+    let old = vec![
+        row(&[("email", "home@example.com"), ("work email", "a@acme.com")]),
+        row(&[("email", "home@example.com"), ("work email", "b@acme.com")]),
+    ];
+    let new = vec![row(&[
+        ("email", "home@example.com"),
+        ("work email", "a@acme.com"),
+    ])];
+
+    let diff = diff_rows(&old, &new, "work email");
+    assert_eq!(diff.removed, vec!["b@acme.com"]);
+    assert!(diff.added.is_empty());
+    assert!(diff.changed.is_empty());
+}
+
+#[test]
+fn the_watcher_reports_a_rewrite_with_its_diff() {
+    let dir = std::env::temp_dir().join(format!("draftmate-watch-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("contacts.csv");
+    fs::write(
+        &path,
+        "email,work email,firm\nhome@example.com,a@acme.com,Acme\nhome@example.com,b@acme.com,Acme\n",
+    )
+    .unwrap();
+
+    let roles = ColumnRoles {
+        email: Some("work email".into()),
+        ..ColumnRoles::default()
+    };
+    let (tx, rx) = mpsc::channel();
+    let watcher = DataWatcher::new();
+    watcher
+        .watch(
+            &path,
+            roles,
+            Box::new(move |change| {
+                let _ = tx.send(change);
+            }),
+        )
+        .unwrap();
+
+    fs::write(
+        &path,
+        "email,work email,firm\nhome@example.com,a@acme.com,Acme Corp\nhome@example.com,c@acme.com,Acme\n",
+    )
+    .unwrap();
+    let change = rx.recv_timeout(Duration::from_secs(10)).unwrap();
+    watcher.unwatch();
+    let _ = fs::remove_dir_all(&dir);
+
+    assert_eq!(change.path, path);
+    assert_eq!(change.data.data.rows.len(), 2);
+    assert_eq!(
+        change.diff,
+        DataDiff {
+            added: vec!["c@acme.com".into()],
+            removed: vec!["b@acme.com".into()],
+            changed: vec!["a@acme.com".into()],
+        }
+    );
+}
+
+fn headers(names: &[&str]) -> Vec<String> {
+    names.iter().map(|h| h.to_string()).collect()
+}
+
+#[test]
+fn roles_are_suggested_from_headers() {
+    let suggested = suggest_roles(&headers(&[
+        "firm email",
+        "full name",
+        "law firm",
+        "company",
+        "university",
+    ]));
+
+    assert_eq!(suggested.email.as_deref(), Some("firm email"));
+    assert_eq!(suggested.name.as_deref(), Some("full name"));
+    // An exact name beats an earlier substring match.
+    assert_eq!(suggested.firm.as_deref(), Some("company"));
+    assert_eq!(suggested.school.as_deref(), Some("university"));
+}
+
+#[test]
+fn firm_suggestion_skips_email_columns() {
+    let suggested = suggest_roles(&headers(&["firm email", "employer firm"]));
+    assert_eq!(suggested.firm.as_deref(), Some("employer firm"));
+}
+
+#[test]
+fn mapped_roles_override_the_guess() {
+    let roles = ColumnRoles {
+        email: Some("Work Address".into()),
+        firm: Some("employer".into()),
+        ..Default::default()
+    };
+    let report = validate_roles(
+        &roles,
+        &headers(&["email", "work address", "company", "employer"]),
+    );
+
+    assert!(report.is_ok());
+    assert_eq!(report.resolved.email.as_deref(), Some("work address"));
+    assert_eq!(report.resolved.firm.as_deref(), Some("employer"));
+}
+
+#[test]
+fn role_validation_reports_missing_and_shared_headers() {
+    let roles = ColumnRoles {
+        email: Some("e-mail".into()),
+        name: Some("contact".into()),
+        firm: Some("contact".into()),
+        ..Default::default()
+    };
+    let report = validate_roles(&roles, &headers(&["email", "contact"]));
+
+    assert_eq!(
+        report.issues,
+        vec![
+            RoleIssue {
+                role: ColumnRole::Email,
+                header: Some("e-mail".into()),
+                problem: RoleProblem::MissingHeader,
+            },
+            RoleIssue {
+                role: ColumnRole::Name,
+                header: Some("contact".into()),
+                problem: RoleProblem::SharedHeader,
+            },
+            RoleIssue {
+                role: ColumnRole::Firm,
+                header: Some("contact".into()),
+                problem: RoleProblem::SharedHeader,
+            },
+        ]
+    );
+    // A missing header falls back to the guess.
+    assert_eq!(report.resolved.email.as_deref(), Some("email"));
+}
+
+#[test]
+fn role_validation_requires_an_email_column() {
+    let report = validate_roles(&ColumnRoles::default(), &headers(&["name", "firm"]));
+    assert_eq!(
+        report.issues,
+        vec![RoleIssue {
+            role: ColumnRole::Email,
+            header: None,
+            problem: RoleProblem::Unresolved,
+        }]
+    );
+}
+
+fn source(name: &str, csv: &str) -> JoinSource {
+    let loaded = parse_csv(csv.as_bytes()).unwrap().data;
+    JoinSource {
+        name: name.into(),
+        data: Dataset {
+            rows: loaded.rows,
+            headers: loaded.headers,
+        },
+    }
+}
+
+fn join_request(headers: HeaderMode) -> JoinRequest {
+    JoinRequest {
+        sources: vec![
+            source(
+                "contacts",
+                "Email,Name,Firm\nada@x.com,Ada,Evercore\nbo@y.com,Bo,\ncy@z.com,Cy,Lazard\n",
+            ),
+            source(
+                "notes",
+                "Email Address,Met At,Firm\nBO@y.com,Career fair,Centerview\nada@x.com,Referral,Moelis\ndee@w.com,Alumni call,\n",
+            ),
+        ],
+        key: "email".into(),
+        headers,
+    }
+}
+
+#[test]
+fn joining_merges_columns_onto_the_primary_rows() {
+    let joined = join_sources(&join_request(HeaderMode::Merge)).unwrap();
+
+    assert_eq!(
+        joined.data.headers,
+        headers(&["email", "name", "firm", "met at"])
+    );
+    assert_eq!(joined.data.count, 3);
+    assert_eq!(joined.data.rows[0]["met at"], "Referral");
+    // A blank primary cell takes the other source's value.
+    assert_eq!(joined.data.rows[1]["firm"], "Centerview");
+    assert_eq!(joined.data.rows[1]["met at"], "Career fair");
+    assert_eq!(joined.data.rows[2]["met at"], "");
+
+    assert_eq!(
+        joined.conflicts,
+        vec![JoinConflict {
+            row: 1,
+            key: "ada@x.com".into(),
+            column: "firm".into(),
+            kept: "Evercore".into(),
+            kept_from: "contacts".into(),
+            ignored: "Moelis".into(),
+            ignored_from: "notes".into(),
+        }]
+    );
+    assert_eq!(
+        joined.unmatched,
+        vec![
+            UnmatchedRow {
+                source: "contacts".into(),
+                row: 3,
+                key: "cy@z.com".into(),
+                missing_from: vec!["notes".into()],
+            },
+            UnmatchedRow {
+                source: "notes".into(),
+                row: 3,
+                key: "dee@w.com".into(),
+                missing_from: vec!["contacts".into()],
+            },
+        ]
+    );
+}
+
+#[test]
+fn joining_with_prefixes_keeps_every_column() {
+    let joined = join_sources(&join_request(HeaderMode::Prefix)).unwrap();
+
+    assert_eq!(
+        joined.data.headers,
+        headers(&["email", "name", "firm", "notes.met at", "notes.firm"])
+    );
+    assert_eq!(joined.data.rows[0]["firm"], "Evercore");
+    assert_eq!(joined.data.rows[0]["notes.firm"], "Moelis");
+    assert_eq!(joined.data.rows[1]["firm"], "");
+    assert!(joined.conflicts.is_empty());
+}
+
+#[test]
+fn joining_needs_a_key_column_in_every_source() {
+    let mut request = join_request(HeaderMode::Merge);
+    request
+        .sources
+        .push(source("extra", "Name,School\nAda,Duke\n"));
+
+    let err = join_sources(&request).unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::InvalidArgument {
+            message: "Source 'extra' has no 'email' column to join on".into(),
+        }
+    );
+
+    request.sources[2] = source("Notes", "Email\n");
+    let err = join_sources(&request).unwrap_err();
+    assert_eq!(err.to_string(), "Two sources are named 'Notes'");
+}
+
+fn drafted(pairs: &[(&str, &str)]) -> Vec<Drafted> {
+    pairs
+        .iter()
+        .map(|(email, template)| Drafted {
+            email: email.to_string(),
+            template: template.to_string(),
+        })
+        .collect()
+}
+
+fn marked(text: &str, recipients: &[(&str, &str)]) -> String {
+    let delimiter = sniff_delimiter(text);
+    let roles = ColumnRoles::default();
+    mark_drafted(text, delimiter, &drafted(recipients), "2024-05-01", &roles)
+        .unwrap()
+        .0
+}
+
+#[test]
+fn write_back_only_touches_the_drafted_cells() {
+    let text = "\"Email\";Note;GEN;Drafted At;Template\r\n\
+                \"ada@x.com\";\"likes; semicolons\";TRUE;;\r\n\
+                bo@x.com;\"multi\nline\";1;;\r\n";
+    assert_eq!(
+        marked(text, &[("ADA@x.com", "Intro; short")]),
+        "\"Email\";Note;GEN;Drafted At;Template\r\n\
+         \"ada@x.com\";\"likes; semicolons\";FALSE;2024-05-01;\"Intro; short\"\r\n\
+         bo@x.com;\"multi\nline\";1;;\r\n"
+    );
+}
+
+#[test]
+fn write_back_appends_missing_columns() {
+    let text = "\"email\",\"name\"\n\"ada@x.com\",\"Ada\"\nbo@x.com\n";
+    assert_eq!(
+        marked(text, &[("bo@x.com", "Default")]),
+        "\"email\",\"name\",\"Generate\",\"Drafted At\",\"Template\"\n\
+         \"ada@x.com\",\"Ada\",\"TRUE\",\"\",\"\"\n\
+         bo@x.com,,FALSE,2024-05-01,Default\n"
+    );
+}
+
+#[test]
+fn write_back_keeps_the_flag_spelling() {
+    let text = "email,generate\na@x.com,yes\nb@x.com,Y\nc@x.com,True\nd@x.com,x\n";
+    let all = [
+        ("a@x.com", "T"),
+        ("b@x.com", "T"),
+        ("c@x.com", "T"),
+        ("d@x.com", "T"),
+    ];
+    assert_eq!(
+        marked(text, &all),
+        "email,generate,Drafted At,Template\n\
+         a@x.com,no,2024-05-01,T\n\
+         b@x.com,N,2024-05-01,T\n\
+         c@x.com,False,2024-05-01,T\n\
+         d@x.com,FALSE,2024-05-01,T\n"
+    );
+}
+
+#[test]
+fn write_back_reports_emails_not_in_the_file() {
+    let (_, report) = mark_drafted(
+        "email\na@x.com\na@x.com\n",
+        b',',
+        &drafted(&[("a@x.com", "T"), ("gone@x.com", "T")]),
+        "today",
+        &ColumnRoles::default(),
+    )
+    .unwrap();
+    assert_eq!(report.updated, 2);
+    assert_eq!(report.missing, vec!["gone@x.com".to_string()]);
+}
+
+#[test]
+fn write_back_keeps_the_encoding_and_writes_a_backup() {
+    let original = utf16le("email\tgenerate\r\nada@x.com\ttrue\r\n", true);
+    let path = temp_file("writeback-utf16.csv", &original);
+
+    let report = write_back(
+        &path,
+        &drafted(&[("ada@x.com", "Intro")]),
+        "today",
+        &ColumnRoles::default(),
+    )
+    .unwrap();
+    assert_eq!(report.updated, 1);
+    assert_eq!(fs::read(&report.backup).unwrap(), original);
+    assert_eq!(
+        fs::read(&path).unwrap(),
+        utf16le(
+            "email\tgenerate\tDrafted At\tTemplate\r\nada@x.com\tfalse\ttoday\tIntro\r\n",
+            true
+        )
+    );
+    let _ = fs::remove_file(&report.backup);
+}
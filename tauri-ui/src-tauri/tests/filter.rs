@@ -0,0 +1,146 @@
+use draftmate_lib::bridge;
+use draftmate_lib::engine::EngineError;
+use draftmate_lib::filter::{Filter, FilterError};
+use draftmate_lib::model::{ColumnRoles, Dataset, Row};
+
+fn row(cells: &[(&str, &str)]) -> Row {
+    cells
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn contacts() -> Dataset {
+    Dataset {
+        headers: ["email", "first name", "company", "school", "class"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect(),
+        rows: vec![
+            row(&[
+                ("email", "ada@evercore.com"),
+                ("first name", "Ada"),
+                ("company", "Evercore"),
+                ("school", "Wharton School"),
+                ("class", "2024"),
+            ]),
+            row(&[
+                ("email", ""),
+                ("first name", "Bo"),
+                ("company", "Evercore"),
+                ("school", "The Wharton School"),
+                ("class", "2019"),
+            ]),
+            row(&[
+                ("email", "cy@lazard.com"),
+                ("first name", "Cy"),
+                ("company", "Lazard"),
+                ("school", "Stern"),
+                ("class", "2021"),
+            ]),
+        ],
+    }
+}
+
+fn matching(source: &str) -> Vec<usize> {
+    let data = contacts();
+    Filter::compile(source, &data.headers, &ColumnRoles::default())
+        .unwrap()
+        .expect("filter is not blank")
+        .matching_rows(&data.rows)
+}
+
+fn error(source: &str) -> FilterError {
+    let data = contacts();
+    Filter::compile(source, &data.headers, &ColumnRoles::default()).unwrap_err()
+}
+
+#[test]
+fn blank_filters_select_everything() {
+    let data = contacts();
+    assert!(
+        Filter::compile("  ", &data.headers, &ColumnRoles::default())
+            .unwrap()
+            .is_none()
+    );
+
+    let matches = bridge::filter_rows("", &data, &ColumnRoles::default()).unwrap();
+    assert_eq!(matches.rows, vec![0, 1, 2]);
+    assert_eq!(matches.total, 3);
+}
+
+#[test]
+fn comparisons_combine_with_and_or_not() {
+    // `firm` is not a header; it resolves to the guessed firm column.
+    assert_eq!(
+        matching(r#"firm == "Evercore" and school contains "Wharton" and not empty(email)"#),
+        vec![0]
+    );
+    assert_eq!(
+        matching(r#"company = 'lazard' or `first name` == "Bo""#),
+        vec![1, 2]
+    );
+    assert_eq!(matching(r#"not (company == "Evercore")"#), vec![2]);
+}
+
+#[test]
+fn and_binds_tighter_than_or() {
+    assert_eq!(
+        matching(r#"company == "Lazard" or company == "Evercore" and class == 2019"#),
+        vec![1, 2]
+    );
+}
+
+#[test]
+fn text_comparisons_ignore_case_and_numbers_compare_numerically() {
+    assert_eq!(matching(r#"school startswith "the""#), vec![1]);
+    assert_eq!(matching(r#"upper(email) endswith "@LAZARD.COM""#), vec![2]);
+    assert_eq!(matching("class >= 2021"), vec![0, 2]);
+    assert_eq!(matching("class < 2020.5"), vec![1]);
+}
+
+#[test]
+fn a_bare_column_is_true_when_not_blank() {
+    assert_eq!(matching("email"), vec![0, 2]);
+}
+
+#[test]
+fn parse_errors_point_at_the_column() {
+    assert_eq!(
+        error(r#"company == "Evercore"#),
+        FilterError {
+            message: "Missing closing \"".into(),
+            column: 12,
+        }
+    );
+    assert_eq!(error("company == ").column, 12);
+    assert_eq!(error("company == 'x' and (email").column, 26);
+    assert_eq!(error("company ~ 'x'").column, 9);
+    assert_eq!(error("company 'x'").column, 9);
+}
+
+#[test]
+fn unknown_columns_and_functions_are_reported() {
+    let unknown = error("email and region == 'West'");
+    assert_eq!(unknown.column, 11);
+    assert_eq!(unknown.message, "No column named 'region'");
+
+    let function = error("not blank(email)");
+    assert_eq!(function.column, 5);
+    assert_eq!(function.message, "Unknown function 'blank'");
+}
+
+#[test]
+fn filter_errors_reach_the_frontend_with_their_column() {
+    let err = bridge::filter_rows("region == 1", &contacts(), &ColumnRoles::default()).unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::InvalidFilter {
+            message: "No column named 'region'".into(),
+            column: 1,
+        }
+    );
+    let json = serde_json::to_value(&err).unwrap();
+    assert_eq!(json["kind"], "InvalidFilter");
+    assert_eq!(json["column"], 1);
+}
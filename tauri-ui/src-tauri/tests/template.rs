@@ -0,0 +1,529 @@
+use draftmate_lib::bridge;
+use draftmate_lib::engine::EngineError;
+use draftmate_lib::model::{
+    ColumnRoles, Dataset, LintRequest, Part, PartKind, Rendered, Row, Severity, Template,
+};
+use draftmate_lib::template::lint::{LintCode, LintIssue};
+use draftmate_lib::template::{render_rows, wrap_in_html, Resolver, TemplateError, TemplateText};
+
+fn row(cells: &[(&str, &str)]) -> Row {
+    cells
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+fn headers(row: &Row) -> Vec<String> {
+    row.keys().cloned().collect()
+}
+
+fn render(source: &str, cells: &[(&str, &str)]) -> Rendered {
+    let row = row(cells);
+    let headers = headers(&row);
+    let resolver = Resolver::new(&headers, &row, &ColumnRoles::default());
+    TemplateText::parse(source).unwrap().render(&resolver)
+}
+
+fn text(source: &str, cells: &[(&str, &str)]) -> String {
+    render(source, cells).text
+}
+
+fn error(source: &str) -> TemplateError {
+    TemplateText::parse(source).unwrap_err()
+}
+
+#[test]
+fn placeholders_use_derived_values_then_headers() {
+    let cells = [
+        ("full name", "Lovelace, Ada"),
+        ("company", "Evercore"),
+        ("met at", "the Duke fair"),
+    ];
+    assert_eq!(
+        text(
+            "Hi {First Name} {last name}, we met at {met at} ({firm}).",
+            &cells
+        ),
+        "Hi Ada Lovelace, we met at the Duke fair (Evercore)."
+    );
+    assert_eq!(
+        text(
+            "Dear {first name}",
+            &[("name", "Grace Hopper"), ("prefix", "Dr")]
+        ),
+        "Dear Dr. Hopper"
+    );
+}
+
+#[test]
+fn blank_placeholders_fall_back_to_their_default() {
+    let cells = [("name", "Ada"), ("company", "  ")];
+    assert_eq!(
+        text("I saw your work at {firm|your firm}.", &cells),
+        "I saw your work at your firm."
+    );
+    assert_eq!(text("{name|there}", &cells), "Ada");
+
+    let rendered = render("{firm|x} {school} {school} {nope}", &cells);
+    assert_eq!(rendered.empty, vec!["school", "nope"]);
+}
+
+#[test]
+fn blocks_keep_their_contents_only_when_the_condition_holds() {
+    let source = "Hi {name}.{#if school} Fellow {school} alum!{/if}\
+                  {#unless firm} Where are you now?{/unless}";
+    assert_eq!(
+        text(source, &[("name", "Ada"), ("school", "Duke"), ("firm", "")]),
+        "Hi Ada. Fellow Duke alum! Where are you now?"
+    );
+    assert_eq!(
+        text(
+            source,
+            &[("name", "Bo"), ("school", ""), ("firm", "Lazard")]
+        ),
+        "Hi Bo."
+    );
+
+    let nested = "{#if firm}At {firm}{#unless school}, no school{/unless}.{/if}";
+    assert_eq!(text(nested, &[("firm", "Lazard")]), "At Lazard, no school.");
+
+    // Placeholders in a block that was left out aren't reported as empty.
+    let rendered = render("{#if school}{school} {firm}{/if}", &[("firm", "")]);
+    assert_eq!(rendered.text, "");
+    assert!(rendered.empty.is_empty());
+}
+
+#[test]
+fn text_outside_tags_is_kept_as_is() {
+    assert_eq!(text("a } b\n\nc", &[]), "a } b\n\nc");
+}
+
+#[test]
+fn double_braced_tags_are_read_as_single_braced() {
+    let cells = [("firstname", "Ada"), ("firm", "")];
+    assert_eq!(
+        text("Hi {{firstname}}, {{ firm | \"your firm\" }}", &cells),
+        "Hi Ada, your firm"
+    );
+    assert_eq!(text("{{#if firm}}at {firm}{{/if}}!", &cells), "!");
+
+    let parsed = TemplateText::parse("Hi {{firstname}}\n{{firm}} {firm}").unwrap();
+    let at: Vec<(usize, usize)> = parsed
+        .double_braces()
+        .iter()
+        .map(|at| (at.line, at.column))
+        .collect();
+    assert_eq!(at, vec![(1, 4), (2, 1)]);
+
+    assert_eq!(
+        error("Hi {{firstname} there"),
+        TemplateError {
+            message: "Double braces are not supported; use {firstname}".into(),
+            line: 1,
+            column: 4,
+        }
+    );
+}
+
+#[test]
+fn malformed_tags_report_their_line_and_column() {
+    assert_eq!(
+        error("Hi {first name,\nthanks"),
+        TemplateError {
+            message: "Missing closing }".into(),
+            line: 1,
+            column: 4,
+        }
+    );
+
+    let unclosed = error("Hi\n  {#if school}\nalum");
+    assert_eq!((unclosed.line, unclosed.column), (2, 3));
+    assert_eq!(unclosed.message, "{#if school} is never closed; add {/if}");
+
+    let mismatched = error("{#if firm}\n{#unless school}x{/if}{/unless}");
+    assert_eq!((mismatched.line, mismatched.column), (2, 18));
+    assert_eq!(
+        mismatched.message,
+        "{/if} closes {#unless school} from line 2, column 1"
+    );
+
+    assert_eq!(error("x {/if}").message, "{/if} has no matching block");
+    assert_eq!(
+        error("{#each rows}").message,
+        "Unknown block '#each'; use #if or #unless"
+    );
+    assert_eq!(
+        error("{#if }{/if}").message,
+        "Missing placeholder after #if"
+    );
+    assert_eq!(error("a {|default}").column, 3);
+    assert_eq!(error("a {x {y}").column, 3);
+}
+
+#[test]
+fn rows_are_rendered_for_the_subject_and_every_template() {
+    let data = Dataset {
+        headers: vec!["email".into(), "name".into()],
+        rows: vec![row(&[("email", "a@x.com"), ("name", "Ada")])],
+    };
+    let templates = vec![
+        Template {
+            id: "t1".into(),
+            name: "Intro".into(),
+            text: "Dear {name}".into(),
+            manual_only: false,
+        },
+        Template {
+            id: "t2".into(),
+            name: "Follow-up".into(),
+            text: "Hello again{#if name}, {name}{/if}".into(),
+            manual_only: true,
+        },
+    ];
+
+    let rendered =
+        render_rows(&data, "For {name|you}", &templates, &ColumnRoles::default()).unwrap();
+    assert_eq!(rendered.len(), 1);
+    assert_eq!(rendered[0].subject.text, "For Ada");
+    assert_eq!(rendered[0].bodies["t1"].text, "Dear Ada");
+    assert_eq!(rendered[0].bodies["t2"].text, "Hello again, Ada");
+
+    let err = render_rows(&data, "{#if name}", &templates, &ColumnRoles::default()).unwrap_err();
+    assert!(matches!(
+        err,
+        EngineError::InvalidTemplate {
+            template: None,
+            line: 1,
+            column: 1,
+            ..
+        }
+    ));
+
+    let mut broken = templates.clone();
+    broken[1].text = "Hi\n{name".into();
+    let err = render_rows(&data, "", &broken, &ColumnRoles::default()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "Template 'Follow-up', line 2, column 1: Missing closing }"
+    );
+    let json = serde_json::to_value(&err).unwrap();
+    assert_eq!(json["kind"], "InvalidTemplate");
+    assert_eq!(json["template"], "Follow-up");
+    assert_eq!(json["line"], 2);
+}
+
+#[test]
+fn transforms_clean_up_values() {
+    let cells = [
+        ("full name", "ADA KING LOVELACE"),
+        ("firm", "Goldman Sachs & Co. LLC"),
+        ("school", "Duke"),
+        (
+            "notes",
+            "Met at the fair and talked about restructuring deals",
+        ),
+    ];
+    assert_eq!(text("{first name|title}", &cells), "Ada");
+    assert_eq!(text("{full name|TITLE}", &cells), "Ada King Lovelace");
+    assert_eq!(text("{full name|initials}", &cells), "A.K.L.");
+    assert_eq!(text("{firm|strip_suffix}", &cells), "Goldman Sachs");
+    assert_eq!(text("{school|upper}", &cells), "DUKE");
+    assert_eq!(text("{notes|truncate:20}", &cells), "Met at the fair and…");
+    assert_eq!(text("{notes|truncate:200}", &cells), cells[3].1);
+
+    assert_eq!(
+        text("{firm|strip_suffix}", &[("firm", "Evercore, Inc.")]),
+        "Evercore"
+    );
+    assert_eq!(
+        text("{name|initials}", &[("name", "Lovelace, Ada")]),
+        "A.L."
+    );
+    assert_eq!(
+        text("{name|title}", &[("name", "o'neil-SMITH")]),
+        "O'Neil-Smith"
+    );
+}
+
+#[test]
+fn transforms_and_defaults_apply_left_to_right() {
+    let cells = [("firm", ""), ("school", "duke")];
+    assert_eq!(text("{firm|\"your firm\"|upper}", &cells), "YOUR FIRM");
+    assert_eq!(text("{firm|upper|your firm}", &cells), "your firm");
+    assert_eq!(text("{school|title|\"title\"}", &cells), "Duke");
+    assert_eq!(text("{firm|\"a|b\"}", &cells), "a|b");
+
+    let rendered = render("{firm|upper}", &cells);
+    assert_eq!(rendered.text, "");
+    assert_eq!(rendered.empty, vec!["firm"]);
+}
+
+#[test]
+fn unknown_transforms_render_as_defaults_and_are_reported() {
+    let parsed = TemplateText::parse("Hi {firm|Lazard}\n{school| uper}").unwrap();
+    let found: Vec<(String, usize, usize)> = parsed
+        .unknown_transforms()
+        .into_iter()
+        .map(|(name, at)| (name, at.line, at.column))
+        .collect();
+    assert_eq!(
+        found,
+        vec![("lazard".into(), 1, 10), ("uper".into(), 2, 10)]
+    );
+
+    let row = row(&[("firm", ""), ("school", "Duke")]);
+    let headers = headers(&row);
+    let rendered = parsed.render(&Resolver::new(&headers, &row, &ColumnRoles::default()));
+    assert_eq!(rendered.text, "Hi Lazard\nDuke");
+}
+
+#[test]
+fn transform_arguments_are_checked() {
+    let err = error("{notes|truncate}");
+    assert_eq!(err.message, "truncate needs a length, e.g. truncate:80");
+    assert_eq!(err.column, 8);
+    assert_eq!(error("{notes|truncate:0}").column, 8);
+    assert_eq!(error("{name|  upper:2}").message, "upper takes no argument");
+    assert_eq!(error("{name|  upper:2}").column, 9);
+}
+
+fn lint_request(subject: &str, templates: &[(&str, &str, bool)]) -> LintRequest {
+    LintRequest {
+        data: Dataset {
+            headers: vec![
+                "email".into(),
+                "name".into(),
+                "firm".into(),
+                "school".into(),
+            ],
+            rows: vec![
+                row(&[("email", "a@x.com"), ("name", "Ada"), ("firm", "Lazard")]),
+                row(&[("email", "b@x.com"), ("name", "Bo"), ("school", "Duke")]),
+                row(&[("email", "c@x.com"), ("name", ""), ("firm", "Evercore")]),
+            ],
+        },
+        templates: templates
+            .iter()
+            .map(|(id, text, manual_only)| Template {
+                id: id.to_string(),
+                name: id.to_uppercase(),
+                text: text.to_string(),
+                manual_only: *manual_only,
+            })
+            .collect(),
+        overrides: Default::default(),
+        subject: subject.into(),
+        roles: ColumnRoles::default(),
+        filter: None,
+    }
+}
+
+fn codes(issues: &[LintIssue]) -> Vec<(Option<&str>, LintCode)> {
+    issues
+        .iter()
+        .map(|issue| (issue.template_id.as_deref(), issue.code))
+        .collect()
+}
+
+#[test]
+fn lint_flags_unknown_placeholders_with_suggestions() {
+    let request = lint_request(
+        "For {frist name}",
+        &[("t1", "Hi {name},\n{#if shcool}alum{/if} {xyz|uper}", false)],
+    );
+    let report = bridge::lint_templates(&request).unwrap();
+    assert_eq!(
+        codes(&report.issues),
+        vec![
+            (None, LintCode::UnknownPlaceholder),
+            (Some("t1"), LintCode::UnknownPlaceholder),
+            (Some("t1"), LintCode::UnknownPlaceholder),
+            (Some("t1"), LintCode::UnknownTransform),
+        ]
+    );
+    let suggestions: Vec<Option<&str>> = report
+        .issues
+        .iter()
+        .map(|issue| issue.suggestion.as_deref())
+        .collect();
+    assert_eq!(
+        suggestions,
+        vec![Some("first name"), Some("school"), None, Some("upper")]
+    );
+    assert_eq!(report.issues[1].template.as_deref(), Some("T1"));
+    assert_eq!(
+        (report.issues[1].line, report.issues[1].column),
+        (Some(2), Some(1))
+    );
+    assert_eq!((report.errors, report.warnings), (3, 1));
+
+    // Without loaded headers, names can't be checked.
+    let mut unloaded = request.clone();
+    unloaded.data = Dataset::default();
+    let report = bridge::lint_templates(&unloaded).unwrap();
+    assert_eq!(
+        codes(&report.issues),
+        vec![(Some("t1"), LintCode::UnknownTransform)]
+    );
+}
+
+#[test]
+fn lint_flags_broken_empty_and_unused_templates() {
+    let mut request = lint_request(
+        "",
+        &[
+            ("t1", "Hi {name", false),
+            ("t2", "  \n", false),
+            ("t3", "Thanks} {name}", true),
+            ("t4", "Hello {name}", true),
+        ],
+    );
+    request.overrides.insert("a@x.com".into(), "t4".into());
+    let report = bridge::lint_templates(&request).unwrap();
+    assert_eq!(
+        codes(&report.issues),
+        vec![
+            (None, LintCode::EmptyTemplate),
+            (Some("t1"), LintCode::Syntax),
+            (Some("t2"), LintCode::EmptyTemplate),
+            (Some("t3"), LintCode::UnbalancedBrace),
+            (Some("t3"), LintCode::UnusedTemplate),
+        ]
+    );
+    let severities: Vec<Severity> = report.issues.iter().map(|issue| issue.severity).collect();
+    assert_eq!(
+        severities,
+        vec![
+            Severity::Warning,
+            Severity::Error,
+            Severity::Error,
+            Severity::Warning,
+            Severity::Warning,
+        ]
+    );
+    assert_eq!(report.issues[1].message, "Missing closing }");
+    assert_eq!(report.issues[3].column, Some(7));
+
+    let json = serde_json::to_value(&report.issues[4]).unwrap();
+    assert_eq!(json["code"], "unused_template");
+    assert_eq!(json["severity"], "warning");
+}
+
+#[test]
+fn lint_takes_a_single_word_that_is_no_transform_as_a_default() {
+    let request = lint_request(
+        "{first name|there}",
+        &[("t1", "Hi {first name|there|title}, {name|uppr}", false)],
+    );
+    let report = bridge::lint_templates(&request).unwrap();
+    assert_eq!(
+        codes(&report.issues),
+        vec![(Some("t1"), LintCode::UnknownTransform)]
+    );
+    assert_eq!(report.issues[0].suggestion.as_deref(), Some("upper"));
+}
+
+#[test]
+fn lint_warns_about_double_braces() {
+    let request = lint_request("{{first name}}", &[("t1", "Hi {{name}}", false)]);
+    let report = bridge::lint_templates(&request).unwrap();
+    assert_eq!(
+        codes(&report.issues),
+        vec![
+            (None, LintCode::DoubleBraces),
+            (Some("t1"), LintCode::DoubleBraces)
+        ]
+    );
+    assert_eq!((report.errors, report.warnings), (0, 2));
+    assert_eq!(
+        (report.issues[1].line, report.issues[1].column),
+        (Some(1), Some(4))
+    );
+}
+
+#[test]
+fn lint_counts_rows_rendering_each_placeholder_empty() {
+    let mut request = lint_request(
+        "{firm|your firm}",
+        &[("t1", "{first name} {firm} {#if firm}{school}{/if}", false)],
+    );
+    let report = bridge::lint_templates(&request).unwrap();
+    assert_eq!(report.rows, 3);
+    let counts: Vec<(Option<&str>, &str, usize)> = report
+        .placeholders
+        .iter()
+        .map(|count| {
+            (
+                count.template_id.as_deref(),
+                count.placeholder.as_str(),
+                count.empty,
+            )
+        })
+        .collect();
+    assert_eq!(
+        counts,
+        vec![
+            (None, "firm", 0),
+            (Some("t1"), "first name", 1),
+            (Some("t1"), "firm", 1),
+            (Some("t1"), "school", 2),
+        ]
+    );
+
+    request.filter = Some("firm".into());
+    let report = bridge::lint_templates(&request).unwrap();
+    assert_eq!(report.rows, 2);
+    assert_eq!(report.placeholders[3].empty, 2);
+
+    request.filter = Some("nope == 1".into());
+    assert!(matches!(
+        bridge::lint_templates(&request),
+        Err(EngineError::InvalidFilter { .. })
+    ));
+}
+
+#[test]
+fn annotated_text_marks_each_substituted_value() {
+    let row = row(&[("name", "ada"), ("firm", ""), ("school", "")]);
+    let headers = headers(&row);
+    let resolver = Resolver::new(&headers, &row, &ColumnRoles::default());
+    let source = "Hi {name|title},{#if school} alum{/if} at {firm|your firm}{school}.";
+    let parsed = TemplateText::parse(source).unwrap();
+    let annotated = parsed.annotate(&resolver);
+    assert_eq!(annotated.text, parsed.render(&resolver).text);
+    assert_eq!(annotated.text, "Hi Ada, at your firm.");
+
+    let part = |kind, text: &str, placeholder: Option<&str>| Part {
+        kind,
+        text: text.into(),
+        placeholder: placeholder.map(str::to_string),
+    };
+    assert_eq!(
+        annotated.parts,
+        vec![
+            part(PartKind::Text, "Hi ", None),
+            part(PartKind::Value, "Ada", Some("name")),
+            part(PartKind::Text, ", at ", None),
+            part(PartKind::Default, "your firm", Some("firm")),
+            part(PartKind::Empty, "", Some("school")),
+            part(PartKind::Text, ".", None),
+        ]
+    );
+}
+
+#[test]
+fn html_matches_the_engines_wrapping() {
+    let wrap = |body: &str| {
+        format!(
+            "<html><body style='margin:0;padding:0;font-family:Arial,sans-serif;'>{}</body></html>",
+            body
+        )
+    };
+    assert_eq!(
+        wrap_in_html("\n Dear Ada,\n\nThanks.  Best,\nBo\n"),
+        wrap("Dear Ada,<br><br>Thanks.<br><br>Best,<br>Bo")
+    );
+    // The engine strips these characters, not whole <br> tags.
+    assert_eq!(wrap_in_html("bring <b>"), wrap("ing "));
+    assert_eq!(wrap_in_html("\x1f x \u{a0}"), wrap("x"));
+}
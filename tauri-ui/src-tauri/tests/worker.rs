@@ -0,0 +1,206 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use draftmate_lib::engine::{EngineError, EngineWorker, JobManager};
+use draftmate_lib::settings::Settings;
+use serde_json::Value;
+
+/// Stands in for `python -m engine serve`. Every request is logged as
+/// `<pid> <method>` before it is handled, so tests can see which child got
+/// it and whether it was sent twice.
+///
+/// - `pid` answers with the child's pid
+/// - `sleep <secs>` answers after sleeping, logging `<pid> woke` first
+/// - `quit` answers, then exits
+/// - `crash` exits without answering
+const STUB_SERVE: &str = r#"
+import json, os, sys, time
+
+LOG = os.path.join(os.path.dirname(os.path.abspath(__file__)), "requests.log")
+
+def log(entry):
+    with open(LOG, "a") as f:
+        f.write(f"{os.getpid()} {entry}\n")
+
+assert sys.argv[1:] == ["serve"], sys.argv
+for line in sys.stdin:
+    request = json.loads(line)
+    method = request["method"]
+    log(method)
+    if method == "crash":
+        sys.exit(1)
+    if method == "sleep":
+        time.sleep(float(request["params"][0]))
+        log("woke")
+    print(json.dumps({"jsonrpc": "2.0", "id": request["id"], "result": {"pid": os.getpid()}}), flush=True)
+    if method == "quit":
+        sys.exit(0)
+"#;
+
+/// A throwaway engine directory holding the stub, removed on drop.
+struct StubEngine {
+    dir: PathBuf,
+}
+
+impl StubEngine {
+    fn new(name: &str) -> Self {
+        let dir =
+            std::env::temp_dir().join(format!("draftmate-worker-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("engine")).unwrap();
+        fs::write(dir.join("engine").join("__init__.py"), "").unwrap();
+        fs::write(dir.join("engine").join("__main__.py"), STUB_SERVE).unwrap();
+        Self { dir }
+    }
+
+    fn settings(&self) -> Settings {
+        Settings {
+            python_path: Some(PathBuf::from("python3")),
+            engine_dir: Some(self.dir.clone()),
+            ..Settings::default()
+        }
+    }
+
+    /// `(pid, method)` for each request the stub saw, oldest first.
+    fn requests(&self) -> Vec<(u32, String)> {
+        log_entries(&self.dir.join("engine").join("requests.log"))
+    }
+}
+
+impl Drop for StubEngine {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn log_entries(path: &Path) -> Vec<(u32, String)> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(|line| {
+            let (pid, entry) = line.split_once(' ').unwrap();
+            (pid.parse().unwrap(), entry.to_string())
+        })
+        .collect()
+}
+
+async fn call(
+    worker: &EngineWorker,
+    settings: &Settings,
+    command: &str,
+    args: &[&str],
+) -> Result<Value, EngineError> {
+    let jobs = JobManager::new();
+    let guard = jobs.start(command);
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    worker.call(settings, guard.job(), command, &args).await
+}
+
+async fn pid(worker: &EngineWorker, settings: &Settings) -> u64 {
+    let result = call(worker, settings, "pid", &[]).await.unwrap();
+    result["pid"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn the_child_is_reused_across_calls() {
+    let engine = StubEngine::new("reuse");
+    let settings = engine.settings();
+    let worker = EngineWorker::new(None);
+
+    let first = pid(&worker, &settings).await;
+    let second = pid(&worker, &settings).await;
+
+    assert_eq!(first, second);
+    assert_eq!(
+        engine.requests(),
+        vec![(first as u32, "pid".into()), (first as u32, "pid".into())]
+    );
+}
+
+#[tokio::test]
+async fn a_child_that_died_between_calls_is_respawned() {
+    let engine = StubEngine::new("respawn");
+    let settings = engine.settings();
+    let worker = EngineWorker::new(None);
+
+    let first = call(&worker, &settings, "quit", &[]).await.unwrap()["pid"]
+        .as_u64()
+        .unwrap();
+    // Give the child time to exit after answering.
+    tokio::time::sleep(Duration::from_millis(500)).await;
+    let second = pid(&worker, &settings).await;
+
+    assert_ne!(first, second);
+}
+
+#[tokio::test]
+async fn a_launch_change_respawns_the_child() {
+    let before = StubEngine::new("launch-before");
+    let after = StubEngine::new("launch-after");
+    let worker = EngineWorker::new(None);
+
+    let first = pid(&worker, &before.settings()).await;
+    let second = pid(&worker, &after.settings()).await;
+    let third = pid(&worker, &after.settings()).await;
+
+    assert_ne!(first, second);
+    assert_eq!(second, third);
+    assert_eq!(before.requests().len(), 1);
+    assert_eq!(after.requests().len(), 2);
+}
+
+#[tokio::test]
+async fn a_timeout_kills_the_child() {
+    let engine = StubEngine::new("timeout");
+    let mut settings = engine.settings();
+    settings.timeouts.insert("sleep".into(), 1);
+    let worker = EngineWorker::new(None);
+
+    let first = pid(&worker, &settings).await;
+    let err = call(&worker, &settings, "sleep", &["2"]).await.unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::Timeout {
+            command: "sleep".into(),
+            seconds: 1,
+        }
+    );
+
+    let second = pid(&worker, &settings).await;
+    assert_ne!(first, second);
+
+    // Had the child survived, it would have woken by now.
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    let entries: Vec<String> = engine
+        .requests()
+        .into_iter()
+        .map(|(_, entry)| entry)
+        .collect();
+    assert_eq!(entries, vec!["pid", "sleep", "pid"]);
+}
+
+#[tokio::test]
+async fn a_request_in_flight_when_the_child_dies_fails_and_is_not_replayed() {
+    let engine = StubEngine::new("in-flight");
+    let settings = engine.settings();
+    let worker = EngineWorker::new(None);
+
+    let first = pid(&worker, &settings).await;
+    let err = call(&worker, &settings, "crash", &[]).await.unwrap_err();
+    assert_eq!(
+        err,
+        EngineError::io("Engine worker exited before responding")
+    );
+
+    let second = pid(&worker, &settings).await;
+    assert_ne!(first, second);
+    assert_eq!(
+        engine.requests(),
+        vec![
+            (first as u32, "pid".into()),
+            (first as u32, "crash".into()),
+            (second as u32, "pid".into()),
+        ]
+    );
+}
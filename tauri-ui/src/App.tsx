@@ -2,9 +2,19 @@ import { useState, useCallback, useEffect, useMemo, useRef } from "react";
 import {
   loadCsv,
   loadGoogleSheet,
+  listSheetTabs,
+  watchDataFile,
+  unwatchDataFile,
+  onDataChanged,
   buildPreview,
   generateEmails,
+  onGenerateProgress,
+  cancelJob,
+  listJobs,
   pickCsvFile,
+  pickWorkbookFile,
+  listSheets,
+  loadWorkbook,
   pickResumeFile,
   pickTemplateFiles,
   exportTemplates,
@@ -18,12 +28,221 @@ import {
   saveLicenseKey,
   loadLicenseKey,
   generateTemplateId,
+  suggestColumnRoles,
+  validateColumnRoles,
+  validateDataset,
+  filterRows,
+  joinSources,
+  lintTemplates,
+  renderPreview,
   type PreviewRow,
   type Profile,
   type DataLoadResult,
   type Template,
+  type ColumnRole,
+  type ColumnRoles,
+  type RolesReport,
+  type ValidationReport,
+  type DedupPolicy,
+  type GenerateProgress,
+  type GenerateResult,
+  type DuplicateRecipient,
+  type PreviewResult,
+  type EngineError,
+  type FilterMatches,
+  type EngineResponse,
+  type HeaderMode,
+  type JoinResult,
+  type JoinSourceConfig,
+  type SheetTab,
+  type DataSourceKind,
+  type WriteBackReport,
+  type LintIssue,
+  type LintReport,
+  type Annotated,
+  type EmailPreview,
 } from "./engine";
 
+// ============================================================
+// Column Roles
+// ============================================================
+
+const COLUMN_ROLE_LABELS: Record<ColumnRole, string> = {
+  email: "Email",
+  name: "Name",
+  firm: "Firm",
+  school: "School",
+};
+
+const ROLE_PROBLEM_MESSAGES: Record<string, string> = {
+  missing_header: "is not in the loaded data; auto-detecting instead",
+  shared_header: "is also mapped to another role",
+  unresolved: "no column found",
+};
+
+const DEDUP_POLICY_LABELS: Record<DedupPolicy, string> = {
+  keep_first: "Use the first row",
+  keep_last: "Use the last row",
+  skip_all: "Skip the recipient",
+  error: "Stop with an error",
+};
+
+const HEADER_MODE_LABELS: Record<HeaderMode, string> = {
+  merge: "Merge columns with the same name",
+  prefix: "Prefix columns with the source name",
+};
+
+/** Name the main source goes by in join reports. */
+const MAIN_SOURCE_NAME = "main";
+
+/**
+ * Load the profile's main source, then join its extra sources onto it.
+ */
+async function loadProfileData(profile: Profile): Promise<EngineResponse<DataLoadResult | JoinResult>> {
+  const main =
+    profile.dataSource === "csv"
+      ? await loadCsv(profile.csvPath)
+      : profile.dataSource === "workbook"
+        ? await loadWorkbook(profile.workbookPath ?? "", profile.workbookSheet || undefined)
+        : await loadGoogleSheet(profile.sheetUrl, profile.sheetTabs ?? []);
+  const extras = (profile.joinSources ?? []).filter((source) => source.path.trim());
+  if (!main.success || !main.data || extras.length === 0) return main;
+
+  const sources = [{ name: MAIN_SOURCE_NAME, data: main.data }];
+  for (const extra of extras) {
+    const loaded = extra.dataSource === "csv" ? await loadCsv(extra.path) : await loadGoogleSheet(extra.path);
+    if (!loaded.success || !loaded.data) {
+      return { ...loaded, error: `${extra.name}: ${loaded.error || "Failed to load data"}` };
+    }
+    sources.push({ name: extra.name, data: loaded.data });
+  }
+  return joinSources(sources, profile.joinKey || "email", profile.joinHeaders ?? "merge");
+}
+
+/** Local time as "YYYY-MM-DD HH:MM", for the CSV's Drafted At column. */
+function draftedAtNow(): string {
+  const now = new Date();
+  const pad = (n: number) => String(n).padStart(2, "0");
+  return (
+    `${now.getFullYear()}-${pad(now.getMonth() + 1)}-${pad(now.getDate())} ` +
+    `${pad(now.getHours())}:${pad(now.getMinutes())}`
+  );
+}
+
+/** Toast text for what marking drafted recipients in the CSV did. */
+function describeWriteBack(report: WriteBackReport): string {
+  if (report.error) return `; could not update the CSV: ${report.error}`;
+  let text = `; marked ${report.updated} row${report.updated === 1 ? "" : "s"} in the CSV`;
+  if (report.missing.length > 0) text += ` (${report.missing.length} not found)`;
+  return text;
+}
+
+/** One lint issue as a line of text under the editor. */
+function describeLintIssue(issue: LintIssue): string {
+  let text = issue.message;
+  if (issue.line !== null) text = `Line ${issue.line}, column ${issue.column}: ${text}`;
+  if (issue.suggestion) text += ` (did you mean ${issue.code === "unknown_transform" ? issue.suggestion : `{${issue.suggestion}}`}?)`;
+  return text;
+}
+
+/**
+ * Line preview rows up with the rows that were sent, leaving null where the
+ * duplicate policy dropped a row. Only valid when every sent row comes back,
+ * i.e. the preview was asked for all rows.
+ */
+function alignWithSentRows(result: PreviewResult, sentCount: number): (PreviewRow | null)[] {
+  const dropped = new Set<number>();
+  for (const duplicate of result.duplicates) {
+    for (const row of duplicate.rows) {
+      if (row !== duplicate.kept) dropped.add(row - 1);
+    }
+  }
+
+  const aligned: (PreviewRow | null)[] = [];
+  let next = 0;
+  for (let i = 0; i < sentCount; i++) {
+    aligned.push(dropped.has(i) ? null : result.preview_rows[next++] ?? null);
+  }
+  return aligned;
+}
+
+/**
+ * Renumber duplicates from positions in the rows sent to positions in the
+ * loaded data, so they match what the user sees in their spreadsheet.
+ */
+function toDataRows(duplicates: DuplicateRecipient[], originalIndices: number[]): DuplicateRecipient[] {
+  const toData = (row: number) => (originalIndices[row - 1] ?? row - 1) + 1;
+  return duplicates.map((duplicate) => ({
+    ...duplicate,
+    rows: duplicate.rows.map(toData),
+    kept: duplicate.kept === null ? null : toData(duplicate.kept),
+  }));
+}
+
+/**
+ * Overrides with empty choices dropped and email keys normalized.
+ */
+function cleanedOverrides(overrides: Record<string, string>): Record<string, string> {
+  const clean: Record<string, string> = {};
+  for (const [email, templateId] of Object.entries(overrides)) {
+    if (templateId && templateId.trim() !== "") {
+      clean[email.toLowerCase().trim()] = templateId;
+    }
+  }
+  return clean;
+}
+
+/**
+ * The header the engine will read each recipient's address from.
+ */
+function emailHeader(roles: ColumnRoles | undefined, headers: string[]): string {
+  const mapped = roles?.email?.trim().toLowerCase();
+  if (mapped && headers.includes(mapped)) return mapped;
+  return headers.find((h) => h.includes("email")) ?? "email";
+}
+
+/**
+ * Indices of the rows the profile's filter selects, or null when every row
+ * counts (no filter, or one that does not parse; generate reports that).
+ */
+async function rowsMatchingFilter(
+  filter: string | undefined,
+  data: { rows: Record<string, string>[]; headers: string[] },
+  roles: ColumnRoles | undefined
+): Promise<Set<number> | null> {
+  if (!filter?.trim()) return null;
+  const result = await filterRows(filter, data, roles);
+  return result.success && result.data ? new Set(result.data.rows) : null;
+}
+
+// ============================================================
+// Lint Panel
+// ============================================================
+
+/**
+ * The open template's lint issues, and how many rows leave each of its
+ * placeholders blank.
+ */
+function LintPanel({ report, templateId }: { report: LintReport; templateId: string }) {
+  const issues = report.issues.filter((issue) => issue.template_id === templateId);
+  const blanks = report.placeholders.filter((count) => count.template_id === templateId && count.empty > 0);
+  if (issues.length === 0 && blanks.length === 0) return null;
+
+  return (
+    <div className="lint-panel">
+      {issues.map((issue, i) => (
+        <div key={i} className={`lint-issue ${issue.severity}`}>{describeLintIssue(issue)}</div>
+      ))}
+      {blanks.length > 0 && (
+        <div className="lint-blanks">
+          Blank in some of {report.rows} rows:{" "}
+          {blanks.map((count) => `{${count.placeholder}} ${count.empty}`).join(", ")}
+        </div>
+      )}
+    </div>
+  );
+}
+
 // ============================================================
 // Toast Component
 // ============================================================
@@ -80,6 +299,23 @@ function LicenseModal({ isOpen, onClose, licenseKey, setLicenseKey, isLicensed,
 // Preview Popup Modal Component
 // ============================================================
 
+/** Rendered text with substituted values highlighted and empty ones marked. */
+function AnnotatedText({ value }: { value: Annotated }) {
+  return (
+    <>
+      {value.parts.map((part, i) =>
+        part.kind === "text" ? (
+          <span key={i}>{part.text}</span>
+        ) : (
+          <span key={i} className={`part-${part.kind}`} title={`{${part.placeholder}}`}>
+            {part.kind === "empty" ? `{${part.placeholder}}` : part.text}
+          </span>
+        )
+      )}
+    </>
+  );
+}
+
 interface PreviewPopupModalProps {
   isOpen: boolean;
   onClose: () => void;
@@ -94,6 +330,8 @@ interface PreviewPopupModalProps {
   onOverride: (email: string, templateId: string) => void;
   eligibleCount: number;
   onGenerate: () => void;
+  duplicates: DuplicateRecipient[];
+  renderEmail: (email: string) => Promise<EngineResponse<EmailPreview>>;
 }
 
 function PreviewPopupModal({
@@ -110,9 +348,32 @@ function PreviewPopupModal({
   onOverride,
   eligibleCount,
   onGenerate,
+  duplicates,
+  renderEmail,
 }: PreviewPopupModalProps) {
   const [selectedRowIndex, setSelectedRowIndex] = useState<number | null>(null);
   const [selectedEmail, setSelectedEmail] = useState<string | null>(null);
+  const [rendered, setRendered] = useState<EmailPreview | null>(null);
+  const [renderError, setRenderError] = useState<string | null>(null);
+  const [showHtml, setShowHtml] = useState<boolean>(false);
+
+  // Render the selected recipient's draft, again whenever the rows change
+  useEffect(() => {
+    setRendered(null);
+    setRenderError(null);
+    if (!isOpen || !selectedEmail) return;
+
+    let cancelled = false;
+    renderEmail(selectedEmail).then((result) => {
+      if (cancelled) return;
+      if (result.success && result.data) setRendered(result.data);
+      else setRenderError(result.error ?? "Could not render this draft");
+    });
+
+    return () => {
+      cancelled = true;
+    };
+  }, [isOpen, selectedEmail, previewRows, renderEmail]);
 
   // Preserve selection by email when rows change (e.g. after override refresh)
   useEffect(() => {
@@ -151,6 +412,17 @@ function PreviewPopupModal({
           </div>
         </div>
 
+        {duplicates.length > 0 && (
+          <div className="preview-duplicates">
+            {duplicates.map((duplicate) => (
+              <div key={duplicate.email}>
+                <strong>{duplicate.email}</strong> is on rows {duplicate.rows.join(", ")}
+                {duplicate.kept === null ? " — skipped" : ` — using row ${duplicate.kept}`}
+              </div>
+            ))}
+          </div>
+        )}
+
         <div className="preview-popup-body">
           <div className="preview-popup-table-container">
             {loading ? (
@@ -216,6 +488,23 @@ function PreviewPopupModal({
                   <div><strong>Firm:</strong> {selectedRow.firm}</div>
                   <div><strong>Template:</strong> {displayTemplate}</div>
                   <div><strong>Source:</strong> {overrideId ? "Manual Override" : "Auto-Assigned"}</div>
+                  {renderError && <div className="field-warning">{renderError}</div>}
+                  {rendered && (
+                    <>
+                      <div><strong>Subject:</strong> <AnnotatedText value={rendered.subject} /></div>
+                      {rendered.body && rendered.html !== null ? (
+                        <div className="rendered-body">
+                          <label className="checkbox-label">
+                            <input type="checkbox" checked={showHtml} onChange={(e) => setShowHtml(e.target.checked)} />
+                            Show HTML
+                          </label>
+                          {showHtml ? <pre>{rendered.html}</pre> : <pre><AnnotatedText value={rendered.body} /></pre>}
+                        </div>
+                      ) : (
+                        <div className="field-hint">No template applies, so no draft will be created</div>
+                      )}
+                    </>
+                  )}
                 </div>
               );
             })() : (
@@ -274,6 +563,56 @@ function ConfirmModal({ isOpen, onClose, title, message, onConfirm }: ConfirmMod
   );
 }
 
+// ============================================================
+// Validation Modal Component
+// ============================================================
+
+interface ValidationModalProps {
+  report: ValidationReport | null;
+  onClose: () => void;
+}
+
+function ValidationModal({ report, onClose }: ValidationModalProps) {
+  if (!report) return null;
+
+  return (
+    <div className="modal-overlay" onClick={onClose}>
+      <div className="modal" onClick={(e) => e.stopPropagation()} style={{ maxWidth: "640px" }}>
+        <h2>Data Check</h2>
+        <p style={{ marginBottom: "1rem" }}>
+          {report.errors} error{report.errors === 1 ? "" : "s"}, {report.warnings} warning{report.warnings === 1 ? "" : "s"}
+          {report.errors > 0 ? " — fix the errors before generating." : ""}
+        </p>
+        {report.findings.length > 0 && (
+          <div className="preview-table validation-table">
+            <table>
+              <thead>
+                <tr>
+                  <th>Row</th>
+                  <th>Column</th>
+                  <th>Problem</th>
+                </tr>
+              </thead>
+              <tbody>
+                {report.findings.map((finding, idx) => (
+                  <tr key={idx} className={`finding-${finding.severity}`}>
+                    <td>{finding.row}</td>
+                    <td>{finding.column ?? "–"}</td>
+                    <td title={finding.message}>{finding.message}</td>
+                  </tr>
+                ))}
+              </tbody>
+            </table>
+          </div>
+        )}
+        <div className="modal-buttons">
+          <button onClick={onClose} className="btn-secondary" autoFocus>Close</button>
+        </div>
+      </div>
+    </div>
+  );
+}
+
 // ============================================================
 // Prompt Modal Component
 // ============================================================
@@ -358,6 +697,19 @@ function App() {
   // Data State (derived from profile)
   // ----------------------------------------
   const [loadedData, setLoadedData] = useState<DataLoadResult | null>(null);
+  const [generating, setGenerating] = useState(false);
+  // The running generate call's latest progress; null until its first recipient
+  const [generateProgress, setGenerateProgress] = useState<GenerateProgress | null>(null);
+  const [cancelling, setCancelling] = useState(false);
+  const [suggestedRoles, setSuggestedRoles] = useState<ColumnRoles>({});
+  const [rolesReport, setRolesReport] = useState<RolesReport | null>(null);
+  const [validationReport, setValidationReport] = useState<ValidationReport | null>(null);
+  const [filterMatches, setFilterMatches] = useState<FilterMatches | null>(null);
+  const [joinReport, setJoinReport] = useState<JoinResult | null>(null);
+  const [availableTabs, setAvailableTabs] = useState<SheetTab[] | null>(null);
+  const [workbookSheets, setWorkbookSheets] = useState<string[] | null>(null);
+  const [filterError, setFilterError] = useState<EngineError | null>(null);
+  const [lintReport, setLintReport] = useState<LintReport | null>(null);
 
   // ----------------------------------------
   // Template Editor State
@@ -372,6 +724,7 @@ function App() {
   // Preview State
   // ----------------------------------------
   const [previewRows, setPreviewRows] = useState<PreviewRow[]>([]);
+  const [duplicates, setDuplicates] = useState<DuplicateRecipient[]>([]);
   const [onlyRecipients, setOnlyRecipients] = useState<boolean>(true);
 
 
@@ -603,7 +956,9 @@ function App() {
     // Validate that we have a data source configured
     const dataSourcePath = activeProfile.dataSource === "csv"
       ? activeProfile.csvPath
-      : activeProfile.sheetUrl;
+      : activeProfile.dataSource === "workbook"
+        ? activeProfile.workbookPath
+        : activeProfile.sheetUrl;
 
     if (!dataSourcePath || dataSourcePath.trim() === "") {
       if (!silent) showToast("No data source configured for this profile", "warning");
@@ -614,10 +969,8 @@ function App() {
     setLoading(true);
 
     try {
-      const result =
-        activeProfile.dataSource === "csv"
-          ? await loadCsv(activeProfile.csvPath)
-          : await loadGoogleSheet(activeProfile.sheetUrl);
+      const result = await loadProfileData(activeProfile);
+      setJoinReport(result.data && "unmatched" in result.data ? result.data : null);
 
       if (result.success && result.data) {
         // Filter out rows where ALL columns are empty (completely blank rows)
@@ -638,7 +991,13 @@ function App() {
 
         setLoadedData(filteredData);
         if (!silent) {
-          showToast(`Loaded ${filteredData.count} rows (${result.data.count - filteredData.count} empty rows filtered)`, "success");
+          const joined = "unmatched" in result.data
+            ? `, joined with ${result.data.unmatched.length} unmatched rows and ${result.data.conflicts.length} conflicts`
+            : "";
+          showToast(
+            `Loaded ${filteredData.count} rows (${result.data.count - filteredData.count} empty rows filtered${joined})`,
+            "success"
+          );
         }
 
         // SMART PREVIEW GENERATION:
@@ -647,6 +1006,8 @@ function App() {
         // 3. Merge results back: Ineligible rows get blank template
 
         const allRows = filteredData.rows;
+        const emailColumn = emailHeader(activeProfile.columnRoles, filteredData.headers);
+        const matching = await rowsMatchingFilter(activeProfile.filter, filteredData, activeProfile.columnRoles);
         const eligibleRows: Record<string, string>[] = [];
         const originalIndices: number[] = [];
 
@@ -657,7 +1018,8 @@ function App() {
           // So we filter by: has 'email', and if 'generate' col exists, it must be true-ish.
 
           let isEligible = true;
-          if (!row["email"] && !row["Email"]) isEligible = false;
+          if (!row[emailColumn]) isEligible = false;
+          if (matching && !matching.has(idx)) isEligible = false;
 
           // Check Generate column if it exists (case insensitive)
           const genKey = Object.keys(row).find(k => k.toLowerCase() === "generate");
@@ -676,7 +1038,9 @@ function App() {
           { rows: eligibleRows, headers: filteredData.headers },
           activeProfile.templates,
           activeProfile.overrides,
-          onlyRecipients
+          onlyRecipients,
+          activeProfile.columnRoles,
+          activeProfile.dedupPolicy
         );
 
         if (previewResult.success && previewResult.data) {
@@ -693,15 +1057,18 @@ function App() {
           // So if onlyRecipients is TRUE, we just show eligible rows (as returned).
           // If onlyRecipients is FALSE (Show All), we need to merge.
 
+          setDuplicates(toDataRows(previewResult.data.duplicates, originalIndices));
+
           if (onlyRecipients) {
             setPreviewRows(previewResult.data.preview_rows);
           } else {
             // We need to merge eligible results with ineligible placeholders
+            const aligned = alignWithSentRows(previewResult.data, eligibleRows.length);
             const fullPreview: PreviewRow[] = allRows.map((row, idx) => {
               const eligibleIdx = originalIndices.indexOf(idx);
-              if (eligibleIdx !== -1) {
+              if (eligibleIdx !== -1 && aligned[eligibleIdx]) {
                 // This was an eligible row, grab from result
-                return previewResult.data!.preview_rows[eligibleIdx];
+                return aligned[eligibleIdx]!;
               } else {
                 // Ineligible - return dummy with robust column search
                 return {
@@ -746,6 +1113,129 @@ function App() {
     }
   }, [handleLoadData]);
 
+  // Reload the preview whenever the profile's CSV changes on disk
+  const reloadDataRef = useRef(handleLoadData);
+  reloadDataRef.current = handleLoadData;
+
+  useEffect(() => {
+    if (activeProfile.dataSource !== "csv" || !activeProfile.csvPath) {
+      return;
+    }
+
+    let unlisten: (() => void) | null = null;
+    let cancelled = false;
+
+    onDataChanged((change) => {
+      const { added, removed, changed } = change.diff;
+      reloadDataRef.current(true);
+      showToast(
+        `Data file changed: ${added.length} added, ${removed.length} removed, ${changed.length} changed`,
+        "success"
+      );
+    }).then((fn) => {
+      if (cancelled) fn();
+      else unlisten = fn;
+    });
+    watchDataFile(activeProfile.csvPath, activeProfile.columnRoles);
+
+    return () => {
+      cancelled = true;
+      unlisten?.();
+      unwatchDataFile();
+    };
+  }, [activeProfile.dataSource, activeProfile.csvPath, activeProfile.columnRoles, showToast]);
+
+  // Suggest column roles for freshly loaded data and check the profile's mapping
+  useEffect(() => {
+    if (!loadedData) {
+      setSuggestedRoles({});
+      setRolesReport(null);
+      return;
+    }
+
+    let cancelled = false;
+    Promise.all([
+      suggestColumnRoles(loadedData.headers),
+      validateColumnRoles(activeProfile.columnRoles ?? {}, loadedData.headers),
+    ]).then(([suggested, report]) => {
+      if (cancelled) return;
+      setSuggestedRoles(suggested);
+      setRolesReport(report);
+    });
+
+    return () => {
+      cancelled = true;
+    };
+  }, [loadedData, activeProfile.columnRoles]);
+
+  // Count the rows the profile's filter selects, or show where it fails to parse
+  useEffect(() => {
+    setFilterMatches(null);
+    setFilterError(null);
+    if (!loadedData || !activeProfile.filter?.trim()) return;
+
+    let cancelled = false;
+    filterRows(activeProfile.filter, loadedData, activeProfile.columnRoles).then((result) => {
+      if (cancelled) return;
+      if (result.success && result.data) setFilterMatches(result.data);
+      else setFilterError(result.errorDetail ?? null);
+    });
+
+    return () => {
+      cancelled = true;
+    };
+  }, [loadedData, activeProfile.filter, activeProfile.columnRoles]);
+
+  // Lint the subject and templates as they are edited, the open template
+  // with its unsaved text, after a pause in typing
+  useEffect(() => {
+    const templates = activeProfile.templates.map((t) =>
+      t.id === selectedTemplate?.id ? { ...t, text: editorText, manual_only: editorManualOnly } : t
+    );
+    let cancelled = false;
+    const timer = setTimeout(() => {
+      lintTemplates(
+        loadedData ?? { rows: [], headers: [] },
+        templates,
+        cleanedOverrides(activeProfile.overrides),
+        activeProfile.subjectTemplate,
+        activeProfile.columnRoles,
+        activeProfile.filter
+      ).then((result) => {
+        if (!cancelled) setLintReport(result.success && result.data ? result.data : null);
+      });
+    }, 300);
+
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [loadedData, activeProfile.templates, activeProfile.overrides, activeProfile.subjectTemplate, activeProfile.columnRoles, activeProfile.filter, selectedTemplate, editorText, editorManualOnly]);
+
+  const handleRenderEmail = useCallback(
+    async (email: string): Promise<EngineResponse<EmailPreview>> => {
+      if (!loadedData) return { success: false, data: null, error: "Please load data first" };
+      return renderPreview(
+        loadedData,
+        activeProfile.templates,
+        cleanedOverrides(activeProfile.overrides),
+        activeProfile.subjectTemplate,
+        email,
+        activeProfile.columnRoles,
+        activeProfile.dedupPolicy,
+        activeProfile.filter
+      );
+    },
+    [loadedData, activeProfile.templates, activeProfile.overrides, activeProfile.subjectTemplate, activeProfile.columnRoles, activeProfile.dedupPolicy, activeProfile.filter]
+  );
+
+  const handleSetColumnRole = useCallback((role: ColumnRole, header: string) => {
+    const columnRoles = { ...activeProfile.columnRoles };
+    if (header) columnRoles[role] = header;
+    else delete columnRoles[role];
+    updateProfile({ columnRoles });
+  }, [activeProfile.columnRoles, updateProfile]);
+
   const handlePickCsv = useCallback(async () => {
     const path = await pickCsvFile();
     if (path) {
@@ -753,6 +1243,75 @@ function App() {
     }
   }, [updateProfile]);
 
+  const handlePickWorkbook = useCallback(async () => {
+    const path = await pickWorkbookFile();
+    if (path) {
+      updateProfile({ workbookPath: path, workbookSheet: "" });
+    }
+  }, [updateProfile]);
+
+  // List the workbook's sheets so one can be picked
+  useEffect(() => {
+    setWorkbookSheets(null);
+    if (activeProfile.dataSource !== "workbook" || !activeProfile.workbookPath) return;
+
+    let cancelled = false;
+    listSheets(activeProfile.workbookPath).then((result) => {
+      if (cancelled) return;
+      if (result.success && result.data) setWorkbookSheets(result.data);
+      else showToast(result.error || "Failed to read the workbook", "error");
+    });
+
+    return () => {
+      cancelled = true;
+    };
+  }, [activeProfile.dataSource, activeProfile.workbookPath, showToast]);
+
+  const updateJoinSource = useCallback((index: number, updates: Partial<JoinSourceConfig>) => {
+    const sources = (activeProfile.joinSources ?? []).map((source, i) =>
+      i === index ? { ...source, ...updates } : source
+    );
+    updateProfile({ joinSources: sources });
+  }, [activeProfile.joinSources, updateProfile]);
+
+  const handleAddJoinSource = useCallback((dataSource: "csv" | "sheet") => {
+    const sources = activeProfile.joinSources ?? [];
+    const name = `source ${sources.length + 2}`;
+    updateProfile({ joinSources: [...sources, { name, dataSource, path: "" }] });
+  }, [activeProfile.joinSources, updateProfile]);
+
+  const handleRemoveJoinSource = useCallback((index: number) => {
+    updateProfile({ joinSources: (activeProfile.joinSources ?? []).filter((_, i) => i !== index) });
+  }, [activeProfile.joinSources, updateProfile]);
+
+  const handlePickJoinCsv = useCallback(async (index: number) => {
+    const path = await pickCsvFile();
+    if (path) {
+      updateJoinSource(index, { path });
+    }
+  }, [updateJoinSource]);
+
+  const handleListTabs = useCallback(async () => {
+    const result = await listSheetTabs(activeProfile.sheetUrl);
+    if (result.success && result.data) {
+      setAvailableTabs(result.data);
+    } else {
+      showToast(result.error || "Failed to list tabs", "error");
+    }
+  }, [activeProfile.sheetUrl, showToast]);
+
+  const handleToggleTab = useCallback((name: string) => {
+    const selected = activeProfile.sheetTabs ?? [];
+    updateProfile({
+      sheetTabs: selected.includes(name) ? selected.filter((tab) => tab !== name) : [...selected, name],
+    });
+  }, [activeProfile.sheetTabs, updateProfile]);
+
+  // Tabs belong to one spreadsheet; forget them when the URL changes
+  useEffect(() => {
+    setAvailableTabs(null);
+  }, [activeProfile.sheetUrl]);
+
   const handlePickResume = useCallback(async () => {
     const path = await pickResumeFile();
     if (path) {
@@ -931,13 +1490,17 @@ function App() {
     try {
       // Filter eligible rows for rotation integrity
       const allRows = loadedData.rows;
+      const emailColumn = emailHeader(activeProfile.columnRoles, loadedData.headers);
+      const matching = await rowsMatchingFilter(activeProfile.filter, loadedData, activeProfile.columnRoles);
       const eligibleRows: Record<string, string>[] = [];
       const originalIndices: number[] = [];
 
       allRows.forEach((row, idx) => {
         let isEligible = true;
         // Basic email check
-        if (!row["email"] && !row["Email"]) isEligible = false;
+        if (!row[emailColumn]) isEligible = false;
+        // Row filter check
+        if (matching && !matching.has(idx)) isEligible = false;
 
         // Generate column check
         const genKey = Object.keys(row).find(k => k.toLowerCase() === "generate");
@@ -956,7 +1519,9 @@ function App() {
         { rows: eligibleRows, headers: loadedData.headers },
         activeProfile.templates,
         activeProfile.overrides,
-        false // We ask engine for "all" (which is just the filtered set here)
+        false, // We ask engine for "all" (which is just the filtered set here)
+        activeProfile.columnRoles,
+        activeProfile.dedupPolicy
       );
 
       if (result.success && result.data) {
@@ -971,6 +1536,8 @@ function App() {
         // The modal state `onlyRecipients` (passed as prop to modal, but not accessed here directly? Ah `onlyRecipients` is in scope!)
 
         let finalRows: PreviewRow[] = [];
+        const aligned = alignWithSentRows(result.data, eligibleRows.length);
+        setDuplicates(toDataRows(result.data.duplicates, originalIndices));
 
         if (onlyRecipients) {
           // If checking "Recipients Only", we just show the eligible ones we generated
@@ -979,8 +1546,8 @@ function App() {
           // Merge
           finalRows = allRows.map((row, idx) => {
             const eligibleIdx = originalIndices.indexOf(idx);
-            if (eligibleIdx !== -1) {
-              return result.data!.preview_rows[eligibleIdx];
+            if (eligibleIdx !== -1 && aligned[eligibleIdx]) {
+              return aligned[eligibleIdx]!;
             } else {
               return {
                 name: getRowValue(row, ["name", "full name", "recipient", "candidate", "first name"]),
@@ -1005,7 +1572,7 @@ function App() {
     } finally {
       setLoading(false);
     }
-  }, [loadedData, activeProfile.templates, activeProfile.overrides, showToast]);
+  }, [loadedData, activeProfile.templates, activeProfile.overrides, activeProfile.columnRoles, activeProfile.dedupPolicy, activeProfile.filter, showToast]);
 
   // ----------------------------------------
   // Manual Override Actions
@@ -1088,12 +1655,26 @@ function App() {
     }
 
     try {
-      // Clean overrides: filter empty values and normalize email keys to lowercase
-      const cleanOverrides: Record<string, string> = {};
-      for (const [email, templateId] of Object.entries(activeProfile.overrides)) {
-        if (templateId && templateId.trim() !== "") {
-          cleanOverrides[email.toLowerCase().trim()] = templateId;
-        }
+      const cleanOverrides = cleanedOverrides(activeProfile.overrides);
+
+      // Refuse to generate from data with errors in it
+      const check = await validateDataset(
+        { rows: freshData.rows, headers: freshData.headers },
+        activeProfile.templates,
+        cleanOverrides,
+        activeProfile.subjectTemplate,
+        activeProfile.columnRoles,
+        activeProfile.dedupPolicy,
+        activeProfile.filter
+      );
+      if (!check.success || !check.data) {
+        showToast(check.error || "Failed to check data", "error");
+        return;
+      }
+      if (check.data.errors > 0) {
+        setValidationReport(check.data);
+        showToast(`Found ${check.data.errors} problems in the data; no drafts were created`, "error");
+        return;
       }
 
       console.log("=== GENERATE DEBUG ===");
@@ -1104,18 +1685,44 @@ function App() {
       console.log("Clean overrides:", cleanOverrides);
       console.log("Subject:", activeProfile.subjectTemplate);
 
-      const result = await generateEmails(
-        { rows: freshData.rows, headers: freshData.headers },
-        activeProfile.templates,
-        cleanOverrides,
-        activeProfile.subjectTemplate,
-        activeProfile.resumePath || undefined
-      );
+      setGenerating(true);
+      const unlisten = await onGenerateProgress(setGenerateProgress);
+      let result: EngineResponse<GenerateResult>;
+      try {
+        result = await generateEmails(
+          { rows: freshData.rows, headers: freshData.headers },
+          activeProfile.templates,
+          cleanOverrides,
+          activeProfile.subjectTemplate,
+          activeProfile.resumePath || undefined,
+          false,
+          activeProfile.columnRoles,
+          activeProfile.dedupPolicy,
+          activeProfile.filter,
+          activeProfile.writeBack && activeProfile.dataSource === "csv"
+            ? { path: activeProfile.csvPath, drafted_at: draftedAtNow() }
+            : undefined
+        );
+      } finally {
+        unlisten();
+        setGenerating(false);
+        setGenerateProgress(null);
+        setCancelling(false);
+      }
 
       console.log("Generate result:", result);
 
-      if (result.success && result.data) {
-        showToast(`Created ${result.data.created} Outlook drafts`, "success");
+      if (result.errorDetail?.kind === "Cancelled") {
+        showToast(`Generation cancelled after ${result.errorDetail.processed ?? 0} recipients`, "warning");
+      } else if (result.success && result.data) {
+        const resolved = result.data.duplicates.length;
+        const writeBack = result.data.write_back;
+        showToast(
+          `Created ${result.data.created} Outlook drafts` +
+            (resolved > 0 ? ` (${resolved} duplicate recipient${resolved === 1 ? "" : "s"} resolved)` : "") +
+            (writeBack ? describeWriteBack(writeBack) : ""),
+          writeBack?.error ? "warning" : "success"
+        );
       } else {
         showToast(result.error || "Failed to generate emails", "error");
       }
@@ -1127,6 +1734,48 @@ function App() {
     }
   }, [licenseKey, activeProfile, handleLoadData, showToast]);
 
+  const handleCancelGenerate = useCallback(async () => {
+    // Before the first recipient is reported, find the job among the running ones
+    const jobId = generateProgress?.job_id ?? (await listJobs()).find((job) => job.command === "generate")?.id;
+    if (jobId === undefined) return;
+
+    setCancelling(true);
+    try {
+      await cancelJob(jobId);
+    } catch (error) {
+      // The run may have finished in the meantime
+      console.error("Cancel error:", error);
+      setCancelling(false);
+    }
+  }, [generateProgress]);
+
+  const handleCheckData = useCallback(async () => {
+    if (!loadedData) {
+      showToast("Please load data first", "warning");
+      return;
+    }
+
+    setLoading(true);
+    try {
+      const result = await validateDataset(
+        { rows: loadedData.rows, headers: loadedData.headers },
+        activeProfile.templates,
+        cleanedOverrides(activeProfile.overrides),
+        activeProfile.subjectTemplate,
+        activeProfile.columnRoles,
+        activeProfile.dedupPolicy,
+        activeProfile.filter
+      );
+      if (result.success && result.data) {
+        setValidationReport(result.data);
+      } else {
+        showToast(result.error || "Failed to check data", "error");
+      }
+    } finally {
+      setLoading(false);
+    }
+  }, [loadedData, activeProfile, showToast]);
+
   // ----------------------------------------
   // Render
   // ----------------------------------------
@@ -1223,10 +1872,11 @@ function App() {
             <div className="input-group">
               <select
                 value={activeProfile.dataSource}
-                onChange={(e) => updateProfile({ dataSource: e.target.value as "csv" | "sheet" })}
+                onChange={(e) => updateProfile({ dataSource: e.target.value as DataSourceKind })}
               >
                 <option value="sheet">Google Sheet</option>
                 <option value="csv">CSV File</option>
+                <option value="workbook">Excel / ODS Workbook</option>
               </select>
             </div>
 
@@ -1246,7 +1896,49 @@ function App() {
                   <button onClick={handleOpenSheetInBrowser} disabled={!activeProfile.sheetUrl} className="btn-secondary">
                     Open
                   </button>
+                  <button onClick={handleListTabs} disabled={loading || !activeProfile.sheetUrl} className="btn-secondary">
+                    Tabs
+                  </button>
                 </div>
+                {availableTabs?.map((tab) => (
+                  <label className="checkbox-label" key={tab.gid}>
+                    <input
+                      type="checkbox"
+                      checked={(activeProfile.sheetTabs ?? []).includes(tab.name)}
+                      onChange={() => handleToggleTab(tab.name)}
+                    />
+                    {tab.name}
+                  </label>
+                ))}
+                {(activeProfile.sheetTabs ?? []).length > 1 && (
+                  <div className="field-hint">Each row's tab is available as {"{source tab}"}</div>
+                )}
+              </div>
+            ) : activeProfile.dataSource === "workbook" ? (
+              <div className="input-group">
+                <label>Workbook</label>
+                <div className="file-picker">
+                  <input
+                    type="text"
+                    value={activeProfile.workbookPath ?? ""}
+                    readOnly
+                    placeholder="No file selected"
+                  />
+                  <button onClick={handlePickWorkbook} className="btn-secondary">Browse</button>
+                </div>
+                {workbookSheets && (
+                  <select
+                    value={activeProfile.workbookSheet || workbookSheets[0]}
+                    onChange={(e) => updateProfile({ workbookSheet: e.target.value })}
+                  >
+                    {workbookSheets.map((sheet) => (
+                      <option key={sheet} value={sheet}>{sheet}</option>
+                    ))}
+                  </select>
+                )}
+                <button onClick={() => handleLoadData(false)} disabled={loading || !activeProfile.workbookPath}>
+                  Load Data
+                </button>
               </div>
             ) : (
               <div className="input-group">
@@ -1265,8 +1957,124 @@ function App() {
                 </button>
               </div>
             )}
+
+            <div className="input-group">
+              <label>Row Filter</label>
+              <input
+                type="text"
+                value={activeProfile.filter ?? ""}
+                onChange={(e) => updateProfile({ filter: e.target.value })}
+                onBlur={() => loadedData && handleRefreshPreview()}
+                placeholder={'e.g. firm == "Evercore" and not empty(email)'}
+              />
+              {filterError && <div className="field-warning">{filterError.message}</div>}
+              {filterMatches && (
+                <div className="field-hint">
+                  {filterMatches.rows.length} of {filterMatches.total} rows match
+                </div>
+              )}
+            </div>
           </div>
 
+          {/* Joined Sources Section */}
+          <div className="section">
+            <div className="section-title">Joined Sources</div>
+            {(activeProfile.joinSources ?? []).map((source, index) => (
+              <div className="input-group" key={index}>
+                <div className="file-picker">
+                  <input
+                    type="text"
+                    value={source.name}
+                    onChange={(e) => updateJoinSource(index, { name: e.target.value })}
+                    placeholder="Source name"
+                  />
+                  <button onClick={() => handleRemoveJoinSource(index)} className="btn-small btn-danger" title="Remove Source">×</button>
+                </div>
+                {source.dataSource === "sheet" ? (
+                  <input
+                    type="text"
+                    value={source.path}
+                    onChange={(e) => updateJoinSource(index, { path: e.target.value })}
+                    placeholder="Paste Google Sheets URL..."
+                  />
+                ) : (
+                  <div className="file-picker">
+                    <input type="text" value={source.path} readOnly placeholder="No file selected" />
+                    <button onClick={() => handlePickJoinCsv(index)} className="btn-secondary">Browse</button>
+                  </div>
+                )}
+              </div>
+            ))}
+            <div className="btn-row">
+              <button onClick={() => handleAddJoinSource("csv")} className="btn-secondary">Add CSV</button>
+              <button onClick={() => handleAddJoinSource("sheet")} className="btn-secondary">Add Sheet</button>
+            </div>
+            {(activeProfile.joinSources ?? []).length > 0 && (
+              <>
+                <div className="input-group">
+                  <label>Join On</label>
+                  <input
+                    type="text"
+                    value={activeProfile.joinKey ?? "email"}
+                    onChange={(e) => updateProfile({ joinKey: e.target.value })}
+                    placeholder="email"
+                  />
+                </div>
+                <div className="input-group">
+                  <label>Columns</label>
+                  <select
+                    value={activeProfile.joinHeaders ?? "merge"}
+                    onChange={(e) => updateProfile({ joinHeaders: e.target.value as HeaderMode })}
+                  >
+                    {(Object.keys(HEADER_MODE_LABELS) as HeaderMode[]).map((mode) => (
+                      <option key={mode} value={mode}>{HEADER_MODE_LABELS[mode]}</option>
+                    ))}
+                  </select>
+                </div>
+              </>
+            )}
+            {joinReport?.unmatched.map((row) => (
+              <div className="field-warning" key={`unmatched-${row.source}-${row.row}`}>
+                {row.source} row {row.row}{row.key ? ` (${row.key})` : ""}: not in {row.missing_from.join(", ")}
+              </div>
+            ))}
+            {joinReport?.conflicts.map((conflict, index) => (
+              <div className="field-warning" key={`conflict-${index}`}>
+                {conflict.key} {conflict.column}: kept "{conflict.kept}" from {conflict.kept_from}, ignored
+                "{conflict.ignored}" from {conflict.ignored_from}
+              </div>
+            ))}
+          </div>
+
+          {/* Column Roles Section */}
+          {loadedData && (
+            <div className="section">
+              <div className="section-title">Columns</div>
+              {(Object.keys(COLUMN_ROLE_LABELS) as ColumnRole[]).map((role) => (
+                <div className="input-group" key={role}>
+                  <label>{COLUMN_ROLE_LABELS[role]}</label>
+                  <select
+                    value={activeProfile.columnRoles?.[role] ?? ""}
+                    onChange={(e) => handleSetColumnRole(role, e.target.value)}
+                  >
+                    <option value="">
+                      Auto-detect{suggestedRoles[role] ? ` (${suggestedRoles[role]})` : ""}
+                    </option>
+                    {loadedData.headers.map((header) => (
+                      <option key={header} value={header}>{header}</option>
+                    ))}
+                  </select>
+                </div>
+              ))}
+              {rolesReport?.issues.map((issue) => (
+                <div className="field-warning" key={`${issue.role}-${issue.problem}`}>
+                  {COLUMN_ROLE_LABELS[issue.role]}
+                  {issue.header ? ` column "${issue.header}"` : ""}: {ROLE_PROBLEM_MESSAGES[issue.problem]}
+                </div>
+              ))}
+            </div>
+          )}
+
           {/* Email Settings Section */}
           <div className="section">
             <div className="section-title">Email Settings</div>
@@ -1278,6 +2086,22 @@ function App() {
                 onChange={(e) => updateProfile({ subjectTemplate: e.target.value })}
                 placeholder="Enter subject line... (e.g. Duke Student interested in IB at {firm})"
               />
+              {lintReport?.issues
+                .filter((issue) => issue.template_id === null)
+                .map((issue, i) => (
+                  <div key={i} className="field-warning">{describeLintIssue(issue)}</div>
+                ))}
+            </div>
+            <div className="input-group">
+              <label>Duplicate Recipients</label>
+              <select
+                value={activeProfile.dedupPolicy ?? "keep_first"}
+                onChange={(e) => updateProfile({ dedupPolicy: e.target.value as DedupPolicy })}
+              >
+                {(Object.keys(DEDUP_POLICY_LABELS) as DedupPolicy[]).map((policy) => (
+                  <option key={policy} value={policy}>{DEDUP_POLICY_LABELS[policy]}</option>
+                ))}
+              </select>
             </div>
             <div className="input-group">
               <label>Resume PDF</label>
@@ -1291,6 +2115,23 @@ function App() {
                 <button onClick={handlePickResume} className="btn-secondary">Browse</button>
               </div>
             </div>
+            {activeProfile.dataSource === "csv" && (
+              <div className="input-group">
+                <label className="checkbox-label">
+                  <input
+                    type="checkbox"
+                    checked={!!activeProfile.writeBack}
+                    onChange={(e) => updateProfile({ writeBack: e.target.checked })}
+                  />
+                  Mark Drafted Rows in CSV
+                </label>
+                {activeProfile.writeBack && (
+                  <div className="field-hint">
+                    Sets Generate to false and fills Drafted At and Template; the original is kept as a .bak copy
+                  </div>
+                )}
+              </div>
+            )}
           </div>
 
           {/* Templates Section */}
@@ -1313,6 +2154,9 @@ function App() {
                 >
                   <span className="template-name">{tpl.name}</span>
                   {tpl.manual_only && <span className="badge">M</span>}
+                  {lintReport?.issues.some((issue) => issue.template_id === tpl.id && issue.severity === "error") && (
+                    <span className="badge error" title="This template has errors">!</span>
+                  )}
                 </div>
               ))}
             </div>
@@ -1365,8 +2209,13 @@ function App() {
             placeholder="Enter your email template here...&#10;&#10;Use placeholders like:&#10;{first name}, {last name}, {firm}, {school}"
             disabled={!selectedTemplate}
           />
+          {selectedTemplate && lintReport && (
+            <LintPanel report={lintReport} templateId={selectedTemplate.id} />
+          )}
           <div className="placeholder-hints">
-            Placeholders: {"{first name}"}, {"{last name}"}, {"{full name}"}, {"{firm}"}, {"{school}"}, or any column header
+            Placeholders: {"{first name}"}, {"{last name}"}, {"{full name}"}, {"{firm}"}, {"{school}"}, or any column header.
+            Defaults: {"{firm|your firm}"}. Conditions: {"{#if school}...{/if}"}, {"{#unless firm}...{/unless}"}.
+            Transforms: {"{first name|title}"}, {"{firm|strip_suffix}"}, {"{school|upper}"}, {"{notes|truncate:80}"}, {"{full name|initials}"}
           </div>
         </div>
 
@@ -1375,6 +2224,13 @@ function App() {
 
       {/* Main Screen Actions */}
       <div className="footer">
+        <button
+          onClick={handleCheckData}
+          className="btn-secondary"
+          disabled={loading || !loadedData}
+        >
+          Check Data
+        </button>
         <button
           onClick={handleGenerate}
           className="btn-primary btn-wide"
@@ -1382,10 +2238,33 @@ function App() {
         >
           Generate {eligibleCount > 0 ? `${eligibleCount} ` : ""}Outlook Drafts
         </button>
+        {generating && (
+          <div className="generate-progress">
+            <div className="progress-track">
+              <div
+                className="progress-fill"
+                style={{
+                  width: generateProgress ? `${((generateProgress.index + 1) / generateProgress.total) * 100}%` : "0%",
+                }}
+              />
+            </div>
+            <span className="progress-label">
+              {generateProgress
+                ? `${generateProgress.index + 1} of ${generateProgress.total}: ${generateProgress.email}`
+                : "Starting…"}
+            </span>
+            <button onClick={handleCancelGenerate} className="btn-secondary" disabled={cancelling}>
+              {cancelling ? "Cancelling…" : "Cancel"}
+            </button>
+          </div>
+        )}
       </div>
 
 
 
+      {/* Validation Modal */}
+      <ValidationModal report={validationReport} onClose={() => setValidationReport(null)} />
+
       {/* License Modal */}
       <LicenseModal
         isOpen={licenseModalOpen}
@@ -1412,6 +2291,8 @@ function App() {
         templates={activeProfile.templates}
         overrides={activeProfile.overrides}
         onOverride={handleOverrideTemplate}
+        duplicates={duplicates}
+        renderEmail={handleRenderEmail}
       />
 
       {/* Confirm Modal */}
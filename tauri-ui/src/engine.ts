@@ -6,6 +6,7 @@
  */
 
 import { invoke } from "@tauri-apps/api/core";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
 import { open, save } from "@tauri-apps/plugin-dialog";
 
 // ============================================================
@@ -16,6 +17,74 @@ export interface EngineResponse<T> {
   success: boolean;
   data: T | null;
   error: string | null;
+  /** Set when the bridge itself failed, as opposed to the engine reporting an error. */
+  errorDetail?: EngineError;
+}
+
+export type EngineErrorKind =
+  | "NotFound"
+  | "SpawnFailed"
+  | "Timeout"
+  | "NonZeroExit"
+  | "InvalidJson"
+  | "InvalidOutput"
+  | "Cancelled"
+  | "ArgvTooLarge"
+  | "Io"
+  | "Rpc"
+  | "NoSuchJob"
+  | "InvalidArgument"
+  | "Engine"
+  | "InvalidData"
+  | "InvalidFilter"
+  | "InvalidTemplate";
+
+/**
+ * Structured error returned by the Rust bridge commands.
+ * Variant-specific fields (code, stderr, seconds, ...) ride alongside kind and message.
+ */
+export interface EngineError {
+  kind: EngineErrorKind;
+  message: string;
+  command?: string;
+  seconds?: number;
+  code?: number | null;
+  stderr?: string;
+  stdout?: string;
+  job_id?: number;
+  processed?: number;
+  size?: number;
+  limit?: number;
+  id?: number;
+  /**
+   * For InvalidFilter: 1-based character position of the problem in the filter.
+   * For InvalidTemplate: 1-based character position in `line`.
+   */
+  column?: number;
+  /** For InvalidTemplate: 1-based line of the problem. */
+  line?: number;
+  /** For InvalidTemplate: the template's name, or null for the subject line. */
+  template?: string | null;
+  /** For NotFound: each location searched for the engine, in order. */
+  tried?: string[];
+}
+
+function isEngineError(error: unknown): error is EngineError {
+  return typeof error === "object" && error !== null && "kind" in error && "message" in error;
+}
+
+/**
+ * Turn a rejected invoke() into a failed EngineResponse.
+ */
+function failedResponse<T>(error: unknown): EngineResponse<T> {
+  if (isEngineError(error)) {
+    return { success: false, data: null, error: error.message, errorDetail: error };
+  }
+  return {
+    success: false,
+    data: null,
+    error: error instanceof Error ? error.message : String(error),
+  };
 }
 
 export interface DataLoadResult {
@@ -24,6 +93,42 @@ export interface DataLoadResult {
   count: number;
 }
 
+/** A CSV file read natively by the app, plus how it was decoded. */
+export interface CsvLoadResult extends DataLoadResult {
+  /** e.g. "UTF-8", "UTF-16LE" or "windows-1252". */
+  encoding: string;
+  delimiter: "," | ";" | "\t" | "|";
+}
+
+/** How join columns from sources after the first are named. */
+export type HeaderMode = "merge" | "prefix";
+
+/** A primary row missing from other sources, or another source's row missing from the primary. */
+export interface UnmatchedRow {
+  source: string;
+  /** 1-based position in that source. */
+  row: number;
+  key: string;
+  missing_from: string[];
+}
+
+/** Two sources disagree about a merged cell; the first non-blank value is kept. */
+export interface JoinConflict {
+  /** 1-based position of the joined row. */
+  row: number;
+  key: string;
+  column: string;
+  kept: string;
+  kept_from: string;
+  ignored: string;
+  ignored_from: string;
+}
+
+export interface JoinResult extends DataLoadResult {
+  unmatched: UnmatchedRow[];
+  conflicts: JoinConflict[];
+}
+
 export interface PreviewRow {
   name: string;
   email: string;
@@ -32,15 +137,84 @@ export interface PreviewRow {
   template_id: string | null;
   is_manual: boolean;
   is_eligible: boolean;
+  /** 1-based position of the row in the data sent. */
+  row?: number | null;
+}
+
+/** What to do when one email address is on several recipient rows. */
+export type DedupPolicy = "keep_first" | "keep_last" | "skip_all" | "error";
+
+/** An email address found on several recipient rows, and how it was resolved. */
+export interface DuplicateRecipient {
+  email: string;
+  /** 1-based positions of every row with the address, in the data sent. */
+  rows: number[];
+  /** The row whose data was used, or null if all were skipped. */
+  kept: number | null;
 }
 
 export interface PreviewResult {
   preview_rows: PreviewRow[];
   count: number;
+  duplicates: DuplicateRecipient[];
 }
 
 export interface GenerateResult {
   created: number;
+  duplicates: DuplicateRecipient[];
+  /** Present when the request asked to mark drafted recipients in the CSV. */
+  write_back?: WriteBackReport;
+}
+
+/** Where to mark drafted recipients after a run. */
+export interface WriteBack {
+  /** The CSV the data was loaded from. */
+  path: string;
+  /** Written to the "Drafted At" column as is. */
+  drafted_at: string;
+}
+
+export interface WriteBackReport {
+  /** Copy of the CSV from before it was rewritten. */
+  backup: string;
+  updated: number;
+  /** Drafted emails with no row in the file. */
+  missing: string[];
+  /** Why the file was left alone, if it was. */
+  error: string | null;
+}
+
+export interface GenerateProgress {
+  job_id: number;
+  index: number;
+  total: number;
+  email: string;
+  status: "created" | "dry_run" | "skipped" | "failed";
+  /** Name of the template used; empty if none was. */
+  template: string;
+}
+
+export interface GenerateSummary {
+  job_id: number;
+  total: number;
+  created: number;
+  skipped: number;
+  failed: number;
+  success: boolean;
+  cancelled: boolean;
+  error: string | null;
+}
+
+export interface JobInfo {
+  id: number;
+  command: string;
+  started_at_ms: number;
+  processed: number;
+}
+
+export interface CancelReport {
+  id: number;
+  processed: number;
 }
 
 export interface Template {
@@ -50,15 +224,66 @@ export interface Template {
   manual_only?: boolean;
 }
 
+/** Where a profile's main data comes from. */
+export type DataSourceKind = "csv" | "sheet" | "workbook";
+
 export interface Profile {
   name: string;
-  dataSource: "csv" | "sheet";
+  dataSource: DataSourceKind;
   sheetUrl: string;
+  /** Tabs to load by name; empty means the tab in the URL. */
+  sheetTabs?: string[];
   csvPath: string;
+  /** An .xlsx/.xls/.ods file, for the "workbook" source. */
+  workbookPath?: string;
+  /** Sheet of the workbook to load; empty means the first sheet. */
+  workbookSheet?: string;
   resumePath: string;
   subjectTemplate: string;
   templates: Template[];
   overrides: Record<string, string>;
+  /** Headers pinned to column roles; unset roles are guessed from the headers. */
+  columnRoles?: ColumnRoles;
+  /** How to handle recipients listed more than once; defaults to "keep_first". */
+  dedupPolicy?: DedupPolicy;
+  /** Row filter expression; only matching rows are previewed, checked and generated. */
+  filter?: string;
+  /** More sources joined onto the main one, e.g. a sheet of relationship notes. */
+  joinSources?: JoinSourceConfig[];
+  /** Column to join on; defaults to "email". */
+  joinKey?: string;
+  /** Defaults to "merge". */
+  joinHeaders?: HeaderMode;
+  /** After generating from a CSV, mark drafted recipients in it. */
+  writeBack?: boolean;
+}
+
+/** Where a profile's extra source is loaded from. */
+export interface JoinSourceConfig {
+  name: string;
+  dataSource: "csv" | "sheet";
+  path: string;
+}
+
+export interface ColumnRoles {
+  email?: string;
+  name?: string;
+  firm?: string;
+  school?: string;
+}
+
+export type ColumnRole = keyof ColumnRoles;
+
+export interface RoleIssue {
+  role: ColumnRole;
+  header: string | null;
+  problem: "missing_header" | "shared_header" | "unresolved";
+}
+
+export interface RolesReport {
+  /** The header each role will use: the mapping where valid, else the guess. */
+  resolved: ColumnRoles;
+  issues: RoleIssue[];
 }
 
 // ============================================================
@@ -66,19 +291,16 @@ export interface Profile {
 // ============================================================
 
 /**
- * Run a Python engine command via Tauri invoke and return parsed JSON response.
+ * Invoke a typed engine command and wrap its result in an EngineResponse.
+ * The Rust side validates arguments and unpacks the engine's envelope, so
+ * any failure arrives as a rejected EngineError.
  */
-async function runEngineCommand<T>(args: string[]): Promise<EngineResponse<T>> {
+async function invokeEngine<T>(command: string, args: Record<string, unknown>): Promise<EngineResponse<T>> {
   try {
-    const stdout = await invoke<string>("run_engine", { args });
-    const response = JSON.parse(stdout) as EngineResponse<T>;
-    return response;
+    const data = await invoke<T>(command, args);
+    return { success: true, data, error: null };
   } catch (error) {
-    return {
-      success: false,
-      data: null,
-      error: error instanceof Error ? error.message : String(error),
-    };
+    return failedResponse<T>(error);
   }
 }
 
@@ -86,12 +308,129 @@ async function runEngineCommand<T>(args: string[]): Promise<EngineResponse<T>> {
 // Data Loading
 // ============================================================
 
-export async function loadCsv(path: string): Promise<EngineResponse<DataLoadResult>> {
-  return runEngineCommand<DataLoadResult>(["load-csv", path]);
+export async function loadCsv(path: string): Promise<EngineResponse<CsvLoadResult>> {
+  return invokeEngine<CsvLoadResult>("load_csv", { path });
+}
+
+export interface DataDiff {
+  added: string[];
+  removed: string[];
+  changed: string[];
+}
+
+export interface DataChanged {
+  path: string;
+  data: CsvLoadResult;
+  diff: DataDiff;
+}
+
+/**
+ * Re-read a CSV file whenever it changes on disk; changes arrive via onDataChanged.
+ * Recipients are matched across reloads by the email column `roles` resolves to.
+ * Watching another file replaces the previous watch.
+ */
+export async function watchDataFile(path: string, roles: ColumnRoles = {}): Promise<EngineResponse<null>> {
+  return invokeEngine<null>("watch_data_file", { path, roles });
+}
+
+export async function unwatchDataFile(): Promise<void> {
+  return invoke<void>("unwatch_data_file");
+}
+
+/**
+ * Subscribe to reloads of the watched data file.
+ */
+export async function onDataChanged(
+  handler: (change: DataChanged) => void
+): Promise<UnlistenFn> {
+  return listen<DataChanged>("data-changed", (event) => handler(event.payload));
+}
+
+/**
+ * List the sheets of an .xlsx/.xls/.ods workbook, in workbook order.
+ */
+export async function listSheets(path: string): Promise<EngineResponse<string[]>> {
+  return invokeEngine<string[]>("list_sheets", { path });
+}
+
+/**
+ * Load one sheet of a workbook; the first sheet when none is named.
+ */
+export async function loadWorkbook(
+  path: string,
+  sheet?: string
+): Promise<EngineResponse<DataLoadResult>> {
+  return invokeEngine<DataLoadResult>("load_workbook", { path, sheet: sheet ?? null });
+}
+
+/** One tab of a Google Sheet. */
+export interface SheetTab {
+  name: string;
+  gid: string;
+}
+
+/**
+ * Load a Google Sheet: the tab in the URL, or the given tabs by name or gid.
+ * With several tabs, each row gets a "source tab" column naming its tab.
+ */
+export async function loadGoogleSheet(url: string, tabs: string[] = []): Promise<EngineResponse<DataLoadResult>> {
+  return invokeEngine<DataLoadResult>("load_sheet", { url, tabs });
+}
+
+export async function listSheetTabs(url: string): Promise<EngineResponse<SheetTab[]>> {
+  return invokeEngine<SheetTab[]>("list_sheet_tabs", { url });
+}
+
+/**
+ * Join loaded sources on a key column. The first source decides which rows
+ * exist; unmatched rows and conflicting cells come back alongside the data.
+ */
+export async function joinSources(
+  sources: { name: string; data: { rows: Record<string, string>[]; headers: string[] } }[],
+  key: string = "email",
+  headers: HeaderMode = "merge"
+): Promise<EngineResponse<JoinResult>> {
+  return invokeEngine<JoinResult>("join_sources", { request: { sources, key, headers } });
+}
+
+// ============================================================
+// Column Roles
+// ============================================================
+
+/**
+ * Guess which header holds each column role, the way the engine would.
+ */
+export async function suggestColumnRoles(headers: string[]): Promise<ColumnRoles> {
+  return invoke<ColumnRoles>("suggest_column_roles", { headers });
 }
 
-export async function loadGoogleSheet(url: string): Promise<EngineResponse<DataLoadResult>> {
-  return runEngineCommand<DataLoadResult>(["load-sheet", url]);
+/**
+ * Check a profile's column roles against the loaded headers.
+ */
+export async function validateColumnRoles(roles: ColumnRoles, headers: string[]): Promise<RolesReport> {
+  return invoke<RolesReport>("validate_column_roles", { roles, headers });
+}
+
+// ============================================================
+// Row Filter
+// ============================================================
+
+export interface FilterMatches {
+  /** 0-based indices of the matching rows. */
+  rows: number[];
+  total: number;
+}
+
+/**
+ * Evaluate a filter expression over the loaded rows. A blank filter matches
+ * every row; a bad one fails with an InvalidFilter error carrying its column.
+ */
+export async function filterRows(
+  filter: string,
+  data: { rows: Record<string, string>[]; headers: string[] },
+  roles: ColumnRoles = {}
+): Promise<EngineResponse<FilterMatches>> {
+  return invokeEngine<FilterMatches>("filter_rows", { filter, data, roles });
 }
 
 // ============================================================
@@ -102,23 +441,191 @@ export async function buildPreview(
   data: { rows: Record<string, string>[]; headers: string[] },
   templates: Template[],
   overrides: Record<string, string> = {},
-  onlyRecipients: boolean = true
+  onlyRecipients: boolean = true,
+  roles: ColumnRoles = {},
+  dedup: DedupPolicy = "keep_first",
+  filter?: string
 ): Promise<EngineResponse<PreviewResult>> {
-  const args = [
-    "preview",
-    "--data",
-    JSON.stringify(data),
-    "--templates",
-    JSON.stringify(templates),
-    "--overrides",
-    JSON.stringify(overrides),
-  ];
-
-  if (!onlyRecipients) {
-    args.push("--all-rows");
-  }
+  return invokeEngine<PreviewResult>("preview", {
+    request: { data, templates, overrides, only_recipients: onlyRecipients, roles, dedup, filter: filter || null },
+  });
+}
+
+// ============================================================
+// Rendered Preview
+// ============================================================
+
+/** One piece of rendered text, and where it came from. */
+export interface Part {
+  /** "default": a blank value replaced by its default; "empty": blank, with no default. */
+  kind: "text" | "value" | "default" | "empty";
+  text: string;
+  /** The placeholder the text came from; null for template text. */
+  placeholder: string | null;
+}
+
+export interface Annotated {
+  text: string;
+  parts: Part[];
+}
+
+/** What one recipient's draft will contain. */
+export interface EmailPreview {
+  email: string;
+  /** 1-based position of the recipient's row in the data sent. */
+  row: number;
+  template_id: string | null;
+  template_name: string;
+  subject: Annotated;
+  /** Null when no template applies, so no draft will be created. */
+  body: Annotated | null;
+  /** The body exactly as the draft gets it. */
+  html: string | null;
+}
+
+/**
+ * Render the subject, body and HTML one recipient will get, with templates
+ * chosen as generate chooses them. Fails with InvalidArgument if the address
+ * won't get a draft.
+ */
+export async function renderPreview(
+  data: { rows: Record<string, string>[]; headers: string[] },
+  templates: Template[],
+  overrides: Record<string, string>,
+  subjectTemplate: string,
+  email: string,
+  roles: ColumnRoles = {},
+  dedup: DedupPolicy = "keep_first",
+  filter?: string
+): Promise<EngineResponse<EmailPreview>> {
+  return invokeEngine<EmailPreview>("render_preview", {
+    request: { data, templates, overrides, subject: subjectTemplate, roles, dedup, filter: filter || null },
+    email,
+  });
+}
 
-  return runEngineCommand<PreviewResult>(args);
+/**
+ * renderPreview for several recipients at once, in preview order. Addresses
+ * that won't get a draft are left out; an empty list renders every recipient.
+ */
+export async function renderPreviews(
+  data: { rows: Record<string, string>[]; headers: string[] },
+  templates: Template[],
+  overrides: Record<string, string>,
+  subjectTemplate: string,
+  emails: string[],
+  roles: ColumnRoles = {},
+  dedup: DedupPolicy = "keep_first",
+  filter?: string
+): Promise<EngineResponse<EmailPreview[]>> {
+  return invokeEngine<EmailPreview[]>("render_previews", {
+    request: { data, templates, overrides, subject: subjectTemplate, roles, dedup, filter: filter || null },
+    emails,
+  });
+}
+
+// ============================================================
+// Validation
+// ============================================================
+
+export interface ValidationFinding {
+  /** 1-based position of the row in the validated data. */
+  row: number;
+  column: string | null;
+  code:
+    | "duplicate_email"
+    | "invalid_email"
+    | "missing_email"
+    | "empty_name"
+    | "empty_firm"
+    | "invalid_generate"
+    | "no_template"
+    | "empty_placeholder";
+  severity: "error" | "warning";
+  message: string;
+}
+
+export interface ValidationReport {
+  findings: ValidationFinding[];
+  errors: number;
+  warnings: number;
+}
+
+/**
+ * Check the data for problems before generating. Findings with severity
+ * "error" mean the run would produce wrong or missing drafts.
+ */
+export async function validateDataset(
+  data: { rows: Record<string, string>[]; headers: string[] },
+  templates: Template[],
+  overrides: Record<string, string>,
+  subjectTemplate: string,
+  roles: ColumnRoles = {},
+  dedup: DedupPolicy = "keep_first",
+  filter?: string
+): Promise<EngineResponse<ValidationReport>> {
+  return invokeEngine<ValidationReport>("validate_dataset", {
+    request: { data, templates, overrides, subject: subjectTemplate, roles, dedup, filter: filter || null },
+  });
+}
+
+// ============================================================
+// Template Lint
+// ============================================================
+
+export interface LintIssue {
+  /** Template name, or null for the subject line. */
+  template: string | null;
+  template_id: string | null;
+  code:
+    | "syntax"
+    | "unbalanced_brace"
+    | "double_braces"
+    | "unknown_placeholder"
+    | "unknown_transform"
+    | "empty_template"
+    | "unused_template";
+  severity: "error" | "warning";
+  message: string;
+  line: number | null;
+  column: number | null;
+  /** A close known name, for unknown placeholders and transforms. */
+  suggestion: string | null;
+}
+
+export interface PlaceholderCount {
+  template: string | null;
+  template_id: string | null;
+  placeholder: string;
+  /** Rows that render the placeholder blank with no default. */
+  empty: number;
+}
+
+export interface LintReport {
+  issues: LintIssue[];
+  placeholders: PlaceholderCount[];
+  /** Rows the counts are over, after the filter. */
+  rows: number;
+  errors: number;
+  warnings: number;
+}
+
+/**
+ * Check the subject and templates against the loaded headers. Only a bad
+ * filter fails; template problems are issues in the report. Pass empty data
+ * before anything is loaded to skip the placeholder checks.
+ */
+export async function lintTemplates(
+  data: { rows: Record<string, string>[]; headers: string[] },
+  templates: Template[],
+  overrides: Record<string, string>,
+  subjectTemplate: string,
+  roles: ColumnRoles = {},
+  filter?: string
+): Promise<EngineResponse<LintReport>> {
+  return invokeEngine<LintReport>("lint_templates", {
+    request: { data, templates, overrides, subject: subjectTemplate, roles, filter: filter || null },
+  });
 }
 
 // ============================================================
@@ -131,29 +638,130 @@ export async function generateEmails(
   overrides: Record<string, string>,
   subjectTemplate: string,
   resumePath?: string,
-  dryRun: boolean = false
+  dryRun: boolean = false,
+  roles: ColumnRoles = {},
+  dedup: DedupPolicy = "keep_first",
+  filter?: string,
+  writeBack?: WriteBack
 ): Promise<EngineResponse<GenerateResult>> {
-  const args = [
-    "generate",
-    "--data",
-    JSON.stringify(data),
-    "--templates",
-    JSON.stringify(templates),
-    "--overrides",
-    JSON.stringify(overrides),
-    "--subject",
-    subjectTemplate,
-  ];
-
-  if (resumePath) {
-    args.push("--resume", resumePath);
-  }
+  return invokeEngine<GenerateResult>("generate", {
+    request: {
+      data,
+      templates,
+      overrides,
+      subject: subjectTemplate,
+      resume_path: resumePath || null,
+      dry_run: dryRun,
+      roles,
+      dedup,
+      filter: filter || null,
+      write_back: writeBack || null,
+    },
+  });
+}
 
-  if (dryRun) {
-    args.push("--dry-run");
-  }
+/**
+ * Subscribe to per-recipient progress while generateEmails runs.
+ */
+export async function onGenerateProgress(
+  handler: (progress: GenerateProgress) => void
+): Promise<UnlistenFn> {
+  return listen<GenerateProgress>("generate-progress", (event) => handler(event.payload));
+}
+
+/**
+ * Subscribe to the summary emitted once generateEmails finishes.
+ */
+export async function onGenerateComplete(
+  handler: (summary: GenerateSummary) => void
+): Promise<UnlistenFn> {
+  return listen<GenerateSummary>("generate-complete", (event) => handler(event.payload));
+}
+
+// ============================================================
+// App Settings
+// ============================================================
+
+export interface AppSettings {
+  /** Per-subcommand engine timeout overrides, in seconds. */
+  timeouts: Record<string, number>;
+  /** Interpreter to run the engine with; null falls back to DRAFTMATE_PYTHON, .venv, then python3. */
+  python_path: string | null;
+  /** Directory containing the engine folder; null falls back to DRAFTMATE_ENGINE_DIR, then the bundled copy. */
+  engine_dir: string | null;
+}
+
+export interface PythonInfo {
+  path: string;
+  source: "settings" | "env" | "venv" | "path";
+  version: string;
+}
+
+export async function getSettings(): Promise<AppSettings> {
+  return invoke<AppSettings>("get_settings");
+}
 
-  return runEngineCommand<GenerateResult>(args);
+export async function saveSettings(value: AppSettings): Promise<void> {
+  return invoke<void>("save_settings", { value });
+}
+
+/**
+ * Report the Python interpreter the engine runs with and why it was chosen.
+ */
+export async function getPythonInfo(): Promise<PythonInfo> {
+  return invoke<PythonInfo>("python_info");
+}
+
+// ============================================================
+// Diagnostics
+// ============================================================
+
+export interface ModuleCheck {
+  name: string;
+  importable: boolean;
+  error: string | null;
+}
+
+export interface DiagnosticsReport {
+  /** Engine, Python and modules are usable; Outlook support is reported separately. */
+  ok: boolean;
+  engine_dir: string | null;
+  engine_dir_error: EngineError | null;
+  python: PythonInfo | null;
+  python_error: EngineError | null;
+  modules: ModuleCheck[];
+  certifi: { installed: boolean; version: string | null; error: string | null };
+  outlook: {
+    platform: string;
+    supported: boolean;
+    osascript_available: boolean;
+    outlook_installed: boolean;
+  };
+}
+
+/**
+ * Gather a structured report on the engine setup for the support screen.
+ */
+export async function diagnose(): Promise<DiagnosticsReport> {
+  return invoke<DiagnosticsReport>("diagnose");
+}
+
+// ============================================================
+// Jobs
+// ============================================================
+
+/**
+ * List engine jobs that are queued or running.
+ */
+export async function listJobs(): Promise<JobInfo[]> {
+  return invoke<JobInfo[]>("list_jobs");
+}
+
+/**
+ * Cancel a running engine job, e.g. the job_id from a generate-progress event.
+ */
+export async function cancelJob(id: number): Promise<CancelReport> {
+  return invoke<CancelReport>("cancel_job", { id });
 }
 
 // ============================================================
@@ -172,6 +780,18 @@ export async function pickCsvFile(): Promise<string | null> {
   }
 }
 
+export async function pickWorkbookFile(): Promise<string | null> {
+  try {
+    const selected = await open({
+      multiple: false,
+      filters: [{ name: "Spreadsheets", extensions: ["xlsx", "xlsm", "xlsb", "xls", "ods"] }],
+    });
+    return selected as string | null;
+  } catch {
+    return null;
+  }
+}
+
 export async function pickResumeFile(): Promise<string | null> {
   try {
     const selected = await open({
@@ -231,7 +851,7 @@ export async function pickTemplateFiles(): Promise<TemplateFile[] | null> {
     const paths = Array.isArray(selected) ? selected : [selected];
 
     // Use engine to read the files
-    const result = await runEngineCommand<{ files: TemplateFile[] }>(["read-files", ...paths]);
+    const result = await invokeEngine<{ files: TemplateFile[] }>("read_files", { paths });
     if (result.success && result.data) {
       return result.data.files;
     }
@@ -251,7 +871,7 @@ export interface ExportResult {
  * Export templates to a ZIP file in the Downloads folder.
  */
 export async function exportTemplates(templates: Template[]): Promise<ExportResult> {
-  const result = await runEngineCommand<{ path: string }>(["export-templates", "--templates", JSON.stringify(templates)]);
+  const result = await invokeEngine<{ path: string }>("export_templates", { templates });
   if (result.success && result.data) {
     return { success: true, message: `Exported to ${result.data.path}` };
   }
@@ -285,6 +905,12 @@ export function createDefaultProfile(name: string): Profile {
     subjectTemplate: "{first name} - Networking Request",
     templates: [createDefaultTemplate()],
     overrides: {},
+    columnRoles: {},
+    dedupPolicy: "keep_first",
+    filter: "",
+    joinSources: [],
+    joinKey: "email",
+    joinHeaders: "merge",
   };
 }
 
@@ -338,5 +964,5 @@ export interface LicenseResult {
 }
 
 export async function validateLicense(licenseKey: string): Promise<EngineResponse<LicenseResult>> {
-  return runEngineCommand<LicenseResult>(["validate-license", licenseKey]);
+  return invokeEngine<LicenseResult>("validate_license", { licenseKey });
 }